@@ -0,0 +1,523 @@
+//! Rust register constants generator.
+//! This generates a module tree of `const` items, a lightweight alternative
+//! to a full peripheral access crate for bootloaders and tests:
+//!
+//! - a module per peripheral with its `BASE` address,
+//! - a module per cluster and register with its `OFFSET` from the parent
+//!   and its `ADDRESS`, and `SIZE` and `RESET_VALUE` for registers,
+//! - a module per field with its `OFFSET`, `WIDTH` and `MASK`,
+//! - enumerated values as a `#[repr]` enum or as constants, see [`EnumStyle`].
+//!
+//! Arrays get `DIM` and `INCREMENT` constants, and the constants depending on the
+//! element become functions of the index: `base(n)` for peripherals, `offset(n)` and
+//! `mask(n)` for fields. Addresses in arrays are given by an `address` function, with
+//! one index argument for each enclosing array, named after its module.
+//!
+//! Peripherals derived from another peripheral without registers of their own
+//! get the registers of that peripheral.
+//! Bits of field masks above bit 63 are dropped.
+//!
+//! ```
+//! use svd_consts::{generate, Config};
+//! use svd_rs::Device;
+//!
+//! fn write(device: &Device) -> std::io::Result<()> {
+//!     let consts = generate(device, &Config::default()).unwrap();
+//!     std::fs::write("registers.rs", consts)
+//! }
+//! ```
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use svd_rs as svd;
+
+use crate::svd::{
+    checked_shl, Cluster, Device, DimElement, EnumeratedValues, Field, Peripheral, Register,
+    RegisterCluster, RegisterProperties,
+};
+
+/// Errors of [`generate`]
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    /// Two items of a module have the same name after conversion to an identifier
+    #[error("Name `{1}` is used twice in `{0}`")]
+    DuplicateName(String, String),
+}
+
+/// Representation of enumerated values
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnumStyle {
+    /// A `#[repr]` enum per enumerated values container
+    Enums,
+    /// A module of constants per enumerated values container
+    Consts,
+}
+
+impl Default for EnumStyle {
+    fn default() -> Self {
+        Self::Enums
+    }
+}
+
+/// Options of [`generate`]
+#[derive(Clone, Copy, Debug, Default)]
+#[non_exhaustive]
+pub struct Config {
+    /// Representation of enumerated values, enums by default
+    pub enum_style: EnumStyle,
+}
+
+impl Config {
+    /// Set the representation of enumerated values
+    pub fn enum_style(mut self, style: EnumStyle) -> Self {
+        self.enum_style = style;
+        self
+    }
+}
+
+/// Generate the Rust module tree of register constants of the device
+pub fn generate(device: &Device, config: &Config) -> Result<String, Error> {
+    let mut g = Generator {
+        device,
+        config,
+        out: String::new(),
+        indent: 0,
+    };
+    g.line(&format!(
+        "//! Register constants of `{}`, generated from its SVD description",
+        device.name
+    ));
+    g.line("");
+    g.line("#![allow(non_camel_case_types, non_upper_case_globals, dead_code, clippy::all)]");
+    let mut names = Names::new(&device.name);
+    for p in &device.peripherals {
+        g.peripheral(&mut names, p)?;
+    }
+    Ok(g.out)
+}
+
+struct Generator<'a> {
+    device: &'a Device,
+    config: &'a Config,
+    out: String,
+    indent: usize,
+}
+
+/// Address of an item: a fixed part and the increments of the enclosing arrays
+#[derive(Clone, Debug)]
+struct Address {
+    base: u64,
+    arrays: Vec<(String, u64)>,
+}
+
+impl Address {
+    fn offset(&self, offset: u64) -> Self {
+        Self {
+            base: self.base + offset,
+            arrays: self.arrays.clone(),
+        }
+    }
+    fn array(&self, name: &str, increment: u64) -> Self {
+        let mut arrays = self.arrays.clone();
+        arrays.push((name.to_string(), increment));
+        Self {
+            base: self.base,
+            arrays,
+        }
+    }
+}
+
+/// Identifiers used in a module
+struct Names {
+    path: String,
+    used: HashSet<String>,
+}
+
+impl Names {
+    fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            used: HashSet::new(),
+        }
+    }
+    fn add(&mut self, name: &str) -> Result<(), Error> {
+        if self.used.insert(name.to_string()) {
+            Ok(())
+        } else {
+            Err(Error::DuplicateName(self.path.clone(), name.to_string()))
+        }
+    }
+    fn child(&self, name: &str) -> Self {
+        Self::new(&format!("{}::{}", self.path, name))
+    }
+}
+
+impl<'a> Generator<'a> {
+    fn line(&mut self, line: &str) {
+        if line.is_empty() {
+            self.out.push('\n');
+        } else {
+            writeln!(self.out, "{:indent$}{}", "", line, indent = self.indent * 4).unwrap();
+        }
+    }
+
+    fn doc(&mut self, doc: &str) {
+        self.line(&format!("/// {}", doc));
+    }
+
+    fn open(&mut self, description: Option<&str>, name: &str) {
+        self.line("");
+        if let Some(description) = description {
+            self.doc(&one_line(description));
+        }
+        self.line(&format!("pub mod {} {{", name));
+        self.indent += 1;
+    }
+
+    fn close(&mut self) {
+        self.indent -= 1;
+        self.line("}");
+    }
+
+    fn dim(&mut self, dim: &DimElement) {
+        self.doc("Number of elements");
+        self.line(&format!("pub const DIM: usize = {};", dim.dim));
+        self.doc("Distance between elements");
+        self.line(&format!(
+            "pub const INCREMENT: usize = {:#x};",
+            dim.dim_increment
+        ));
+        if let Some(index) = &dim.dim_index {
+            self.doc(&format!("Element indexes are {}", index.join(", ")));
+        }
+    }
+
+    /// Write `ADDRESS`, or the `address` function in arrays
+    fn address(&mut self, address: &Address) {
+        if address.arrays.is_empty() {
+            self.doc("Address");
+            self.line(&format!("pub const ADDRESS: usize = {:#x};", address.base));
+        } else {
+            let params: Vec<_> = address
+                .arrays
+                .iter()
+                .map(|(name, _)| format!("{}: usize", name))
+                .collect();
+            let terms: Vec<_> = address
+                .arrays
+                .iter()
+                .map(|(name, increment)| format!(" + {} * {:#x}", name, increment))
+                .collect();
+            self.doc("Address of the element at the indexes of the enclosing arrays");
+            self.line(&format!(
+                "pub const fn address({}) -> usize {{",
+                params.join(", ")
+            ));
+            self.line(&format!("    {:#x}{}", address.base, terms.concat()));
+            self.line("}");
+        }
+    }
+
+    fn peripheral(&mut self, names: &mut Names, p: &Peripheral) -> Result<(), Error> {
+        let name = module(&p.name);
+        names.add(&name)?;
+        self.open(p.description.as_deref(), &name);
+        let address = match p {
+            Peripheral::Single(_) => {
+                self.doc("Base address");
+                self.line(&format!("pub const BASE: usize = {:#x};", p.base_address));
+                Address {
+                    base: p.base_address,
+                    arrays: Vec::new(),
+                }
+            }
+            Peripheral::Array(_, dim) => {
+                self.dim(dim);
+                self.doc("Base address of element `n`");
+                self.line("pub const fn base(n: usize) -> usize {");
+                self.line(&format!("    {:#x} + n * INCREMENT", p.base_address));
+                self.line("}");
+                Address {
+                    base: p.base_address,
+                    arrays: vec![(name.clone(), dim.dim_increment as u64)],
+                }
+            }
+        };
+        let source = self.source(p);
+        let props = source
+            .default_register_properties
+            .inherit(&self.device.default_register_properties);
+        let mut children = names.child(&name);
+        for rc in source.registers.iter().flatten() {
+            self.register_cluster(&mut children, rc, &address, &props)?;
+        }
+        self.close();
+        Ok(())
+    }
+
+    /// Peripheral defining the registers of `p`
+    fn source(&self, p: &'a Peripheral) -> &'a Peripheral {
+        let mut source = p;
+        for _ in 0..self.device.peripherals.len() {
+            if source.registers.is_some() {
+                break;
+            }
+            match source
+                .derived_from
+                .as_ref()
+                .and_then(|name| self.device.peripherals.iter().find(|p| &p.name == name))
+            {
+                Some(parent) => source = parent,
+                None => break,
+            }
+        }
+        source
+    }
+
+    fn register_cluster(
+        &mut self,
+        names: &mut Names,
+        rc: &RegisterCluster,
+        parent: &Address,
+        props: &RegisterProperties,
+    ) -> Result<(), Error> {
+        match rc {
+            RegisterCluster::Register(r) => self.register(names, r, parent, props),
+            RegisterCluster::Cluster(c) => self.cluster(names, c, parent, props),
+        }
+    }
+
+    fn cluster(
+        &mut self,
+        names: &mut Names,
+        c: &Cluster,
+        parent: &Address,
+        props: &RegisterProperties,
+    ) -> Result<(), Error> {
+        let name = module(&c.name);
+        names.add(&name)?;
+        self.open(c.description.as_deref(), &name);
+        self.doc("Offset from the parent");
+        self.line(&format!(
+            "pub const OFFSET: usize = {:#x};",
+            c.address_offset
+        ));
+        let mut address = parent.offset(c.address_offset as u64);
+        if let Cluster::Array(_, dim) = c {
+            self.dim(dim);
+            address = address.array(&name, dim.dim_increment as u64);
+        }
+        self.address(&address);
+        let props = c.default_register_properties.inherit(props);
+        let mut children = names.child(&name);
+        for rc in &c.children {
+            self.register_cluster(&mut children, rc, &address, &props)?;
+        }
+        self.close();
+        Ok(())
+    }
+
+    fn register(
+        &mut self,
+        names: &mut Names,
+        r: &Register,
+        parent: &Address,
+        props: &RegisterProperties,
+    ) -> Result<(), Error> {
+        let name = module(&r.name);
+        names.add(&name)?;
+        self.open(r.description.as_deref(), &name);
+        self.doc("Offset from the parent");
+        self.line(&format!(
+            "pub const OFFSET: usize = {:#x};",
+            r.address_offset
+        ));
+        let mut address = parent.offset(r.address_offset as u64);
+        if let Register::Array(_, dim) = r {
+            self.dim(dim);
+            address = address.array(&name, dim.dim_increment as u64);
+        }
+        self.address(&address);
+        let props = r.properties.inherit(props);
+        let size = props.size.unwrap_or(32);
+        let ty = uint(size);
+        self.doc("Size in bits");
+        self.line(&format!("pub const SIZE: u32 = {};", size));
+        if let Some(reset) = props.reset_value {
+            self.doc("Reset value");
+            self.line(&format!("pub const RESET_VALUE: {} = {:#x};", ty, reset));
+        }
+        let mut children = names.child(&name);
+        for f in r.fields.iter().flatten() {
+            self.field(&mut children, f, ty)?;
+        }
+        self.close();
+        Ok(())
+    }
+
+    fn field(&mut self, names: &mut Names, f: &Field, ty: &str) -> Result<(), Error> {
+        let name = module(&f.name);
+        names.add(&name)?;
+        self.open(f.description.as_deref(), &name);
+        let width = f.bit_range.width;
+        let offset = f.bit_range.offset;
+        self.doc("Width in bits");
+        self.line(&format!("pub const WIDTH: u32 = {};", width));
+        let mask = if width >= 64 {
+            u64::MAX
+        } else {
+            (1 << width) - 1
+        };
+        match f {
+            Field::Single(_) => {
+                self.doc("Offset of the lowest bit");
+                self.line(&format!("pub const OFFSET: u32 = {};", offset));
+                self.doc("Mask of the field bits in the register");
+                let mask = checked_shl(mask, offset);
+                self.line(&format!("pub const MASK: {} = {:#x};", ty, mask));
+            }
+            Field::Array(_, dim) => {
+                self.doc("Number of elements");
+                self.line(&format!("pub const DIM: u32 = {};", dim.dim));
+                self.doc("Distance between elements in bits");
+                self.line(&format!(
+                    "pub const INCREMENT: u32 = {};",
+                    dim.dim_increment
+                ));
+                self.doc("Offset of the lowest bit of element `n`");
+                self.line("pub const fn offset(n: u32) -> u32 {");
+                self.line(&format!("    {} + n * INCREMENT", offset));
+                self.line("}");
+                self.doc("Mask of the bits of element `n` in the register");
+                self.line(&format!("pub const fn mask(n: u32) -> {} {{", ty));
+                self.line(&format!("    {:#x} << offset(n)", mask));
+                self.line("}");
+            }
+        }
+        let mut children = names.child(&name);
+        let containers = f.enumerated_values.len();
+        for e in &f.enumerated_values {
+            let name = if containers > 1 {
+                format!("{}Values", camel(e.usage().as_str()))
+            } else {
+                "Values".to_string()
+            };
+            self.enumerated_values(&mut children, &name, e, uint(width))?;
+        }
+        self.close();
+        Ok(())
+    }
+
+    fn enumerated_values(
+        &mut self,
+        names: &mut Names,
+        name: &str,
+        e: &EnumeratedValues,
+        ty: &str,
+    ) -> Result<(), Error> {
+        let values: Vec<_> = e.values.iter().filter(|v| v.value.is_some()).collect();
+        if values.is_empty() {
+            return Ok(());
+        }
+        self.line("");
+        self.doc(&format!("Enumerated values for {}", e.usage().as_str()));
+        match self.config.enum_style {
+            EnumStyle::Enums => {
+                names.add(name)?;
+                self.line("#[derive(Clone, Copy, Debug, PartialEq, Eq)]");
+                self.line(&format!("#[repr({})]", ty));
+                self.line(&format!("pub enum {} {{", name));
+                self.indent += 1;
+                let mut variants = names.child(name);
+                for v in values {
+                    let variant = ident(&v.name);
+                    variants.add(&variant)?;
+                    if let Some(description) = &v.description {
+                        self.doc(&one_line(description));
+                    }
+                    self.line(&format!("{} = {:#x},", variant, v.value.unwrap()));
+                }
+                self.close();
+            }
+            EnumStyle::Consts => {
+                let module = module(name);
+                names.add(&module)?;
+                self.line(&format!("pub mod {} {{", module));
+                self.indent += 1;
+                let mut consts = names.child(&module);
+                for v in values {
+                    let constant = ident(&v.name).to_uppercase();
+                    consts.add(&constant)?;
+                    if let Some(description) = &v.description {
+                        self.doc(&one_line(description));
+                    }
+                    self.line(&format!(
+                        "pub const {}: {} = {:#x};",
+                        constant,
+                        ty,
+                        v.value.unwrap()
+                    ));
+                }
+                self.close();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Smallest unsigned integer type with `bits`
+fn uint(bits: u32) -> &'static str {
+    match bits {
+        0..=8 => "u8",
+        9..=16 => "u16",
+        17..=32 => "u32",
+        _ => "u64",
+    }
+}
+
+/// Strict and reserved keywords, which can't be used as identifiers
+const KEYWORDS: &[&str] = &[
+    "Self", "abstract", "as", "async", "await", "become", "box", "break", "const", "continue",
+    "crate", "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Convert an SVD name to a module name
+fn module(name: &str) -> String {
+    ident(&name.to_lowercase())
+}
+
+/// Convert an SVD name to a Rust identifier, without the `%s` placeholder of arrays
+fn ident(name: &str) -> String {
+    let name = name.replace("[%s]", "").replace("%s", "");
+    let mut ident: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    if KEYWORDS.contains(&ident.as_str()) {
+        ident.push('_');
+    }
+    ident
+}
+
+fn camel(name: &str) -> String {
+    name.split('-')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
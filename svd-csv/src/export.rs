@@ -0,0 +1,291 @@
+use super::COLUMNS;
+use crate::svd::{
+    checked_shr, Cluster, Device, DimElement, EnumeratedValues, Field, Peripheral, Register,
+    RegisterCluster, RegisterProperties, Usage,
+};
+
+/// Cells of a row, in the order of [`COLUMNS`]
+type Row = [String; 11];
+
+pub(crate) fn export(device: &Device) -> String {
+    let mut rows = Vec::new();
+    for p in &device.peripherals {
+        let source = source(device, p);
+        let properties = source
+            .default_register_properties
+            .inherit(&device.default_register_properties);
+        let dim = match p {
+            Peripheral::Single(_) => None,
+            Peripheral::Array(_, dim) => Some(dim),
+        };
+        for (name, base) in instances(&p.name, p.base_address, dim) {
+            let mut exporter = Exporter {
+                device,
+                rows: &mut rows,
+                peripheral: name,
+                base: format!("0x{:08X}", base),
+            };
+            let children = source.registers.as_deref().unwrap_or(&[]);
+            let description = p.description.as_deref().or(source.description.as_deref());
+            if children.is_empty() || description.is_some() {
+                let mut row = exporter.row();
+                row[9] = description.map(one_line).unwrap_or_default();
+                exporter.rows.push(row);
+            }
+            exporter.children(children, "", 0, &properties);
+        }
+    }
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(COLUMNS.iter()).unwrap();
+    for row in rows {
+        writer.write_record(row.iter()).unwrap();
+    }
+    String::from_utf8(writer.into_inner().unwrap()).unwrap()
+}
+
+struct Exporter<'a, 'r> {
+    device: &'a Device,
+    rows: &'r mut Vec<Row>,
+    peripheral: String,
+    base: String,
+}
+
+impl<'a, 'r> Exporter<'a, 'r> {
+    /// Row with the columns of the peripheral
+    fn row(&self) -> Row {
+        let mut row = Row::default();
+        row[0] = self.peripheral.clone();
+        row[1] = self.base.clone();
+        row
+    }
+
+    fn children(
+        &mut self,
+        children: &'a [RegisterCluster],
+        prefix: &str,
+        offset: u64,
+        properties: &RegisterProperties,
+    ) {
+        for rc in children {
+            match rc {
+                RegisterCluster::Register(r) => {
+                    self.register(children, r, prefix, offset, properties)
+                }
+                RegisterCluster::Cluster(c) => {
+                    let source = self.cluster_source(children, c);
+                    let properties = c
+                        .default_register_properties
+                        .inherit(&source.default_register_properties)
+                        .inherit(properties);
+                    let dim = match c {
+                        Cluster::Single(_) => None,
+                        Cluster::Array(_, dim) => Some(dim),
+                    };
+                    for (name, cluster_offset) in
+                        instances(&c.name, offset + c.address_offset as u64, dim)
+                    {
+                        let prefix = format!("{}{}_", prefix, name);
+                        self.children(&source.children, &prefix, cluster_offset, &properties);
+                    }
+                }
+            }
+        }
+    }
+
+    fn register(
+        &mut self,
+        siblings: &'a [RegisterCluster],
+        r: &'a Register,
+        prefix: &str,
+        offset: u64,
+        properties: &RegisterProperties,
+    ) {
+        let source = self.register_source(siblings, r);
+        let properties = r.properties.inherit(&source.properties).inherit(properties);
+        let size = properties.size.unwrap_or(32);
+        let fields = source.fields.as_deref().unwrap_or(&[]);
+        let description = r.description.as_deref().or(source.description.as_deref());
+        let dim = match r {
+            Register::Single(_) => None,
+            Register::Array(_, dim) => Some(dim),
+        };
+        for (name, register_offset) in instances(&r.name, offset + r.address_offset as u64, dim) {
+            let mut register = self.row();
+            register[2] = format!("{}{}", prefix, name);
+            register[3] = format!("0x{:X}", register_offset);
+            register[4] = size.to_string();
+            if fields.is_empty() || description.is_some() {
+                let mut row = register.clone();
+                row[7] = properties
+                    .access
+                    .map(|a| a.as_str().to_string())
+                    .unwrap_or_default();
+                if fields.is_empty() {
+                    row[8] = properties
+                        .reset_value
+                        .map(|v| format!("0x{:X}", v))
+                        .unwrap_or_default();
+                }
+                row[9] = description.map(one_line).unwrap_or_default();
+                self.rows.push(row);
+            }
+            for f in fields {
+                let dim = match f {
+                    Field::Single(_) => None,
+                    Field::Array(_, dim) => Some(dim),
+                };
+                for (field, bit_offset) in instances(&f.name, f.bit_range.offset as u64, dim) {
+                    let bit_offset = bit_offset as u32;
+                    let width = f.bit_range.width;
+                    let mask = if width >= 64 {
+                        u64::MAX
+                    } else {
+                        (1 << width) - 1
+                    };
+                    let mut row = register.clone();
+                    row[5] = field;
+                    row[6] = format!("[{}:{}]", bit_offset + width - 1, bit_offset);
+                    row[7] = f
+                        .access
+                        .or(properties.access)
+                        .map(|a| a.as_str().to_string())
+                        .unwrap_or_default();
+                    if let Some(reset) = properties.reset_value {
+                        let reset_mask = properties.reset_mask.unwrap_or(u64::MAX);
+                        if checked_shr(reset_mask, bit_offset) & mask == mask {
+                            row[8] = format!("0x{:X}", checked_shr(reset, bit_offset) & mask);
+                        }
+                    }
+                    row[9] = f.description.as_deref().map(one_line).unwrap_or_default();
+                    row[10] = values(&f.enumerated_values);
+                    self.rows.push(row);
+                }
+            }
+        }
+    }
+
+    /// Cluster defining the registers of `c`
+    fn cluster_source(&self, siblings: &'a [RegisterCluster], c: &'a Cluster) -> &'a Cluster {
+        let mut source = c;
+        // Limit the depth in case of circular references
+        for _ in 0..=siblings.len() {
+            if !source.children.is_empty() {
+                break;
+            }
+            match source
+                .derived_from
+                .as_ref()
+                .and_then(|path| self.find(siblings, path))
+            {
+                Some(RegisterCluster::Cluster(parent)) => source = parent,
+                _ => break,
+            }
+        }
+        source
+    }
+
+    /// Register defining the fields of `r`
+    fn register_source(&self, siblings: &'a [RegisterCluster], r: &'a Register) -> &'a Register {
+        let mut source = r;
+        for _ in 0..=siblings.len() {
+            if source.fields.is_some() {
+                break;
+            }
+            match source
+                .derived_from
+                .as_ref()
+                .and_then(|path| self.find(siblings, path))
+            {
+                Some(RegisterCluster::Register(parent)) => source = parent,
+                _ => break,
+            }
+        }
+        source
+    }
+
+    /// Find the register or cluster of a `derivedFrom`, a name of one of the `siblings`
+    /// or a path starting with the name of a peripheral
+    fn find(&self, siblings: &'a [RegisterCluster], path: &str) -> Option<&'a RegisterCluster> {
+        let find = |children: &'a [RegisterCluster], name: &str| {
+            children.iter().find(|rc| match rc {
+                RegisterCluster::Register(r) => r.name == name,
+                RegisterCluster::Cluster(c) => c.name == name,
+            })
+        };
+        let mut names = path.split('.');
+        let first = names.next()?;
+        let peripheral = match self.device.peripherals.iter().find(|p| p.name == first) {
+            Some(p) if path.contains('.') => source(self.device, p),
+            _ => return find(siblings, path),
+        };
+        let mut children = peripheral.registers.as_deref()?;
+        let mut found = None;
+        for name in names {
+            let rc = find(children, name)?;
+            children = match rc {
+                RegisterCluster::Cluster(c) => &c.children,
+                RegisterCluster::Register(_) => &[],
+            };
+            found = Some(rc);
+        }
+        found
+    }
+}
+
+/// Enumerated values of the `read-write` container, or the first one
+fn values(values: &[EnumeratedValues]) -> String {
+    let e = match values
+        .iter()
+        .find(|e| e.usage() == Usage::ReadWrite)
+        .or_else(|| values.first())
+    {
+        Some(e) => e,
+        None => return String::new(),
+    };
+    e.values
+        .iter()
+        .filter_map(|v| v.value.map(|value| format!("{}={}", v.name, value)))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Peripheral defining the registers of `p`
+fn source<'a>(device: &'a Device, p: &'a Peripheral) -> &'a Peripheral {
+    let mut source = p;
+    for _ in 0..device.peripherals.len() {
+        if source.registers.is_some() {
+            break;
+        }
+        match source
+            .derived_from
+            .as_ref()
+            .and_then(|name| device.peripherals.iter().find(|p| &p.name == name))
+        {
+            Some(parent) => source = parent,
+            None => break,
+        }
+    }
+    source
+}
+
+/// Names and offsets of the elements of an array, or of a single element
+fn instances(name: &str, offset: u64, dim: Option<&DimElement>) -> Vec<(String, u64)> {
+    match dim {
+        None => vec![(name.to_string(), offset)],
+        Some(dim) => dim
+            .indexes()
+            .enumerate()
+            .map(|(i, idx)| {
+                (
+                    name.replace("[%s]", &idx).replace("%s", &idx),
+                    offset + i as u64 * dim.dim_increment as u64,
+                )
+            })
+            .collect(),
+    }
+}
+
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,381 @@
+use csv::{ReaderBuilder, StringRecord, Trim};
+
+use super::{Error, COLUMNS};
+use crate::svd::{
+    checked_shl, Access, Device, EnumeratedValue, EnumeratedValues, Field, FieldInfo,
+    PeripheralInfo, RegisterCluster, RegisterInfo, ValidateLevel,
+};
+
+const PERIPHERAL: usize = 0;
+const BASE: usize = 1;
+const REGISTER: usize = 2;
+const OFFSET: usize = 3;
+const SIZE: usize = 4;
+const FIELD: usize = 5;
+const BITS: usize = 6;
+const ACCESS: usize = 7;
+const RESET: usize = 8;
+const DESCRIPTION: usize = 9;
+const VALUES: usize = 10;
+
+/// Columns which must be in the header
+const REQUIRED: &[usize] = &[PERIPHERAL, BASE, REGISTER, OFFSET, FIELD, BITS];
+
+/// Rows of a peripheral
+struct PeripheralRows {
+    name: String,
+    row: u64,
+    base: Option<u64>,
+    description: Option<String>,
+    registers: Vec<RegisterRows>,
+}
+
+/// Rows of a register
+struct RegisterRows {
+    name: String,
+    row: u64,
+    offset: Option<u32>,
+    size: Option<u64>,
+    access: Option<Access>,
+    reset: Option<u64>,
+    description: Option<String>,
+    fields: Vec<FieldRow>,
+}
+
+/// A field with its row, the text of its bit range and its reset value
+struct FieldRow {
+    row: u64,
+    bits: String,
+    field: Field,
+    reset: Option<u64>,
+}
+
+/// Cells of a row
+struct Cells<'a> {
+    row: u64,
+    record: &'a StringRecord,
+    columns: &'a [Option<usize>],
+}
+
+pub(crate) fn import(name: &str, csv: &str, lvl: ValidateLevel) -> Result<Device, Error> {
+    let mut reader = ReaderBuilder::new()
+        .trim(Trim::All)
+        .flexible(true)
+        .from_reader(csv.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|e| Error::Csv(e.to_string()))?
+        .clone();
+    let columns: Vec<Option<usize>> = COLUMNS
+        .iter()
+        .map(|c| headers.iter().position(|h| h.eq_ignore_ascii_case(c)))
+        .collect();
+    for &c in REQUIRED {
+        if columns[c].is_none() {
+            return Err(Error::MissingColumn(COLUMNS[c].to_string()));
+        }
+    }
+
+    let mut peripherals: Vec<PeripheralRows> = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| Error::Csv(e.to_string()))?;
+        if record.iter().all(str::is_empty) {
+            continue;
+        }
+        let cells = Cells {
+            row: record.position().map(|p| p.line()).unwrap_or(0),
+            record: &record,
+            columns: &columns,
+        };
+        let peripheral = cells.required(PERIPHERAL)?;
+        let index = match peripherals.iter().position(|p| p.name == peripheral) {
+            Some(index) => index,
+            None => {
+                peripherals.push(PeripheralRows {
+                    name: peripheral.to_string(),
+                    row: cells.row,
+                    base: None,
+                    description: None,
+                    registers: Vec::new(),
+                });
+                peripherals.len() - 1
+            }
+        };
+        let p = &mut peripherals[index];
+        cells.consistent(BASE, &mut p.base, cells.number(BASE)?)?;
+
+        let register = cells.cell(REGISTER);
+        if register.is_empty() {
+            cells.consistent(DESCRIPTION, &mut p.description, cells.text(DESCRIPTION))?;
+            continue;
+        }
+        let index = match p.registers.iter().position(|r| r.name == register) {
+            Some(index) => index,
+            None => {
+                p.registers.push(RegisterRows {
+                    name: register.to_string(),
+                    row: cells.row,
+                    offset: None,
+                    size: None,
+                    access: None,
+                    reset: None,
+                    description: None,
+                    fields: Vec::new(),
+                });
+                p.registers.len() - 1
+            }
+        };
+        let r = &mut p.registers[index];
+        cells.consistent(OFFSET, &mut r.offset, cells.offset()?)?;
+        cells.consistent(SIZE, &mut r.size, cells.size()?)?;
+
+        if cells.cell(FIELD).is_empty() {
+            cells.consistent(ACCESS, &mut r.access, cells.access()?)?;
+            cells.consistent(RESET, &mut r.reset, cells.number(RESET)?)?;
+            cells.consistent(DESCRIPTION, &mut r.description, cells.text(DESCRIPTION))?;
+        } else {
+            r.fields.push(cells.field(lvl)?);
+        }
+    }
+
+    let mut result = Vec::new();
+    for p in peripherals {
+        let base = p
+            .base
+            .ok_or_else(|| Error::MissingValue(p.row, COLUMNS[BASE].to_string()))?;
+        let row = p.row;
+        let mut registers = Vec::new();
+        for r in p.registers {
+            registers.push(RegisterCluster::Register(register(r, lvl)?));
+        }
+        let peripheral = PeripheralInfo::builder()
+            .name(p.name)
+            .description(p.description)
+            .base_address(base)
+            .registers(if registers.is_empty() {
+                None
+            } else {
+                Some(registers)
+            })
+            .build(lvl)
+            .map_err(|e| Error::Svd(row, COLUMNS[PERIPHERAL].to_string(), e))?;
+        result.push(peripheral.single());
+    }
+    Device::builder()
+        .name(name.to_string())
+        .peripherals(result)
+        .build(lvl)
+        .map_err(Error::Device)
+}
+
+/// Build the register, with the reset values of its fields
+fn register(r: RegisterRows, lvl: ValidateLevel) -> Result<crate::svd::Register, Error> {
+    let offset = r
+        .offset
+        .ok_or_else(|| Error::MissingValue(r.row, COLUMNS[OFFSET].to_string()))?;
+    let size = r.size.unwrap_or(32);
+    let mut field_reset = None;
+    let mut field_mask = 0;
+    for f in &r.fields {
+        let range = f.field.bit_range;
+        if range.offset as u64 + range.width as u64 > size {
+            return Err(Error::InvalidValue(
+                f.row,
+                COLUMNS[BITS].to_string(),
+                f.bits.clone(),
+            ));
+        }
+        if let Some(value) = f.reset {
+            field_reset = Some(field_reset.unwrap_or(0) | checked_shl(value, range.offset));
+            field_mask |= checked_shl(mask_of(range.width), range.offset);
+        }
+    }
+    // A reset value in the row of the register takes precedence
+    let (reset, reset_mask) = match r.reset {
+        Some(reset) => (Some(reset), None),
+        None => (field_reset, field_reset.map(|_| field_mask)),
+    };
+    let row = r.row;
+    let fields: Vec<_> = r.fields.into_iter().map(|f| f.field).collect();
+    let register = RegisterInfo::builder()
+        .name(r.name)
+        .description(r.description)
+        .address_offset(offset)
+        .size(Some(size as u32))
+        .access(r.access)
+        .reset_value(reset)
+        .reset_mask(reset_mask)
+        .fields(if fields.is_empty() {
+            None
+        } else {
+            Some(fields)
+        })
+        .build(lvl)
+        .map_err(|e| Error::Svd(row, COLUMNS[REGISTER].to_string(), e))?;
+    Ok(register.single())
+}
+
+impl<'a> Cells<'a> {
+    fn cell(&self, column: usize) -> &'a str {
+        self.columns[column]
+            .and_then(|c| self.record.get(c))
+            .unwrap_or("")
+    }
+
+    fn text(&self, column: usize) -> Option<String> {
+        match self.cell(column) {
+            "" => None,
+            text => Some(text.to_string()),
+        }
+    }
+
+    fn required(&self, column: usize) -> Result<&'a str, Error> {
+        match self.cell(column) {
+            "" => Err(Error::MissingValue(self.row, COLUMNS[column].to_string())),
+            text => Ok(text),
+        }
+    }
+
+    fn invalid(&self, column: usize, text: &str) -> Error {
+        Error::InvalidValue(self.row, COLUMNS[column].to_string(), text.to_string())
+    }
+
+    fn number(&self, column: usize) -> Result<Option<u64>, Error> {
+        match self.cell(column) {
+            "" => Ok(None),
+            text => parse_number(text)
+                .map(Some)
+                .ok_or_else(|| self.invalid(column, text)),
+        }
+    }
+
+    /// Address offset of the register, which must fit in 32 bits
+    fn offset(&self) -> Result<Option<u32>, Error> {
+        match self.number(OFFSET)? {
+            Some(offset) if offset > u32::MAX as u64 => {
+                Err(self.invalid(OFFSET, self.cell(OFFSET)))
+            }
+            offset => Ok(offset.map(|offset| offset as u32)),
+        }
+    }
+
+    /// Size of the register in bits, at most 64
+    fn size(&self) -> Result<Option<u64>, Error> {
+        match self.number(SIZE)? {
+            Some(size) if size > 64 => Err(self.invalid(SIZE, self.cell(SIZE))),
+            size => Ok(size),
+        }
+    }
+
+    fn access(&self) -> Result<Option<Access>, Error> {
+        match self.cell(ACCESS) {
+            "" => Ok(None),
+            text => Access::parse_str(text)
+                .map(Some)
+                .ok_or_else(|| self.invalid(ACCESS, text)),
+        }
+    }
+
+    /// Set `previous` to `value`, unless it was set to a different value by a previous row
+    fn consistent<T: PartialEq>(
+        &self,
+        column: usize,
+        previous: &mut Option<T>,
+        value: Option<T>,
+    ) -> Result<(), Error> {
+        match (previous.as_ref(), value) {
+            (Some(p), Some(v)) if *p != v => Err(Error::Inconsistent(
+                self.row,
+                COLUMNS[column].to_string(),
+                self.cell(column).to_string(),
+            )),
+            (None, Some(v)) => {
+                *previous = Some(v);
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn field(&self, lvl: ValidateLevel) -> Result<FieldRow, Error> {
+        let bits = self.required(BITS)?;
+        let mut range = bits
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .splitn(2, ':')
+            .map(|b| b.trim().parse::<u32>().ok());
+        let (msb, lsb) = match (range.next().flatten(), range.next()) {
+            (Some(msb), None) if msb < 64 => (msb, msb),
+            (Some(msb), Some(Some(lsb))) if msb >= lsb && msb < 64 => (msb, lsb),
+            _ => return Err(self.invalid(BITS, bits)),
+        };
+        let width = msb - lsb + 1;
+
+        let reset = self.number(RESET)?;
+        if matches!(reset, Some(reset) if reset & !mask_of(width) != 0) {
+            return Err(self.invalid(RESET, self.cell(RESET)));
+        }
+
+        let mut values = Vec::new();
+        let text = self.cell(VALUES);
+        for entry in text.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+            let mut parts = entry.splitn(2, '=').map(str::trim);
+            let (name, value) = match (parts.next(), parts.next().and_then(parse_number)) {
+                (Some(name), Some(value)) if !name.is_empty() => (name, value),
+                _ => return Err(self.invalid(VALUES, entry)),
+            };
+            values.push(
+                EnumeratedValue::builder()
+                    .name(name.to_string())
+                    .value(Some(value))
+                    .build(lvl)
+                    .map_err(|e| Error::Svd(self.row, COLUMNS[VALUES].to_string(), e))?,
+            );
+        }
+        let enumerated_values = if values.is_empty() {
+            Vec::new()
+        } else {
+            vec![EnumeratedValues::builder()
+                .values(values)
+                .build(lvl)
+                .map_err(|e| Error::Svd(self.row, COLUMNS[VALUES].to_string(), e))?]
+        };
+
+        let field = FieldInfo::builder()
+            .name(self.cell(FIELD).to_string())
+            .description(self.text(DESCRIPTION))
+            .bit_offset(lsb)
+            .bit_width(width)
+            .access(self.access()?)
+            .enumerated_values(enumerated_values)
+            .build(lvl)
+            .map_err(|e| Error::Svd(self.row, COLUMNS[FIELD].to_string(), e))?;
+        Ok(FieldRow {
+            row: self.row,
+            bits: bits.to_string(),
+            field: field.single(),
+            reset,
+        })
+    }
+}
+
+fn mask_of(width: u32) -> u64 {
+    if width >= 64 {
+        u64::MAX
+    } else {
+        (1 << width) - 1
+    }
+}
+
+/// Parse a decimal, `0x` hexadecimal or `0b` binary number
+fn parse_number(text: &str) -> Option<u64> {
+    let text = text.replace('_', "");
+    let lower = text.to_lowercase();
+    if lower.starts_with("0x") {
+        u64::from_str_radix(&text[2..], 16).ok()
+    } else if lower.starts_with("0b") {
+        u64::from_str_radix(&text[2..], 2).ok()
+    } else {
+        text.parse().ok()
+    }
+}
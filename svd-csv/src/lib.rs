@@ -0,0 +1,94 @@
+//! CSV register listings.
+//! This converts between [`Device`]s and tables with a row per field, as used in
+//! spreadsheets of register maps. The table starts with a header naming the
+//! [`COLUMNS`], in any order:
+//!
+//! | column        | content                                                  |
+//! |---------------|----------------------------------------------------------|
+//! | `peripheral`  | name of the peripheral                                   |
+//! | `base`        | base address of the peripheral                           |
+//! | `register`    | name of the register                                     |
+//! | `offset`      | address offset of the register                           |
+//! | `size`        | size of the register in bits, at most 64                 |
+//! | `field`       | name of the field                                        |
+//! | `bits`        | bit range of the field, like `[7:4]`, `7:4` or `3`       |
+//! | `access`      | access of the field, like `read-write`                   |
+//! | `reset`       | reset value of the field                                 |
+//! | `description` | description of the field                                 |
+//! | `values`      | enumerated values of the field, like `Off=0; On=1`       |
+//!
+//! Columns of the peripheral and the register are repeated on each of their rows.
+//! A row without a `field` describes the register itself, with its `access`,
+//! `reset` value and `description`, and a row without a `register` describes the
+//! peripheral. These rows are only needed for registers without fields and
+//! peripherals without registers, or to give a description.
+//!
+//! Numbers are decimal, or hexadecimal with `0x` or binary with `0b`. The reset
+//! values of the fields are combined into the `resetValue` and `resetMask` of their
+//! register.
+//!
+//! The table has no hierarchy, so arrays and derived peripherals are expanded on
+//! export and clusters are flattened into registers named `CLUSTER_REGISTER`.
+//! Only the `read-write` enumerated values of a field are listed, or the first
+//! ones if there are none.
+
+use svd_rs as svd;
+
+use crate::svd::{Device, SvdError, ValidateLevel};
+
+mod export;
+mod import;
+
+/// Names of the columns, in the order of the export
+pub const COLUMNS: [&str; 11] = [
+    "peripheral",
+    "base",
+    "register",
+    "offset",
+    "size",
+    "field",
+    "bits",
+    "access",
+    "reset",
+    "description",
+    "values",
+];
+
+/// Errors of [`import`].
+/// Rows are numbered like in a spreadsheet, the header being row 1.
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum Error {
+    /// The table is not valid CSV
+    #[error("CSV error: {0}")]
+    Csv(String),
+    /// A required column is missing in the header
+    #[error("Missing column `{0}`")]
+    MissingColumn(String),
+    /// A required cell is empty
+    #[error("Missing value in row {0}, column `{1}`")]
+    MissingValue(u64, String),
+    /// A cell can't be parsed
+    #[error("Invalid value `{2}` in row {0}, column `{1}`")]
+    InvalidValue(u64, String, String),
+    /// A cell differs from the same column in a previous row of the peripheral or register
+    #[error("Value `{2}` in row {0}, column `{1}` differs from a previous row")]
+    Inconsistent(u64, String, String),
+    /// An element failed the validation of its builder
+    #[error("Invalid element in row {0}, column `{1}`: {2}")]
+    Svd(u64, String, SvdError),
+    /// The device failed the validation of its builder
+    #[error("Invalid device: {0}")]
+    Device(SvdError),
+}
+
+/// Import the table as a device named `name`.
+///
+/// The elements are validated with `lvl`.
+pub fn import(name: &str, csv: &str, lvl: ValidateLevel) -> Result<Device, Error> {
+    import::import(name, csv, lvl)
+}
+
+/// Export the device as a table
+pub fn export(device: &Device) -> String {
+    export::export(device)
+}
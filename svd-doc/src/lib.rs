@@ -0,0 +1,738 @@
+//! Register map documentation renderer.
+//! This renders a single page of documentation of a [`Device`], in Markdown or HTML:
+//!
+//! - the device properties and the list of interrupts,
+//! - an index of the peripherals with their base addresses,
+//! - per peripheral, a table of its registers with offset, size, access and reset value,
+//! - per register, a bit-field diagram, a table of its fields and tables of their
+//!   enumerated values.
+//!
+//! Every peripheral, cluster, register and enumerated values container gets an anchor
+//! derived from its path, like `uart0-cr` for `UART0.CR`. Interrupts link to their
+//! peripheral, and `derivedFrom` of peripherals, clusters, registers and enumerated
+//! values links to the source element. Arrays are not expanded, the `%s` placeholder
+//! is removed from names.
+
+use std::fmt::Write;
+
+use svd_rs as svd;
+
+use crate::svd::{
+    checked_shr, Cluster, Device, EnumeratedValues, Field, Peripheral, Register, RegisterCluster,
+    RegisterProperties,
+};
+
+/// Output format of [`render`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// CommonMark with tables
+    Markdown,
+    /// A standalone HTML page
+    Html,
+}
+
+/// Render the documentation of the device
+pub fn render(device: &Device, format: Format) -> String {
+    match format {
+        Format::Markdown => {
+            let mut m = Markdown(String::new());
+            document(&mut m, device);
+            m.0
+        }
+        Format::Html => {
+            let mut h = Html(String::new());
+            h.0.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+            writeln!(h.0, "<title>{}</title>", escape_html(&device.name)).unwrap();
+            h.0.push_str(STYLE);
+            h.0.push_str("</head>\n<body>\n");
+            document(&mut h, device);
+            h.0.push_str("</body>\n</html>\n");
+            h.0
+        }
+    }
+}
+
+const STYLE: &str = "<style>
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #aaa; padding: 2px 6px; }
+table.bitfield td { text-align: center; }
+table.bitfield td.reserved { background: #eee; }
+</style>
+";
+
+/// Bits `msb..=lsb` of a register, with the name of the field or `None` if reserved
+struct Segment {
+    name: Option<String>,
+    msb: u32,
+    lsb: u32,
+}
+
+/// Building blocks of the document
+trait Markup {
+    fn heading(&mut self, level: usize, id: &str, text: &str);
+    fn paragraph(&mut self, text: &str);
+    fn table(&mut self, header: &[&str], rows: &[Vec<String>]);
+    fn diagram(&mut self, rows: &[Vec<Segment>]);
+    /// Link to the anchor `id`, `text` is escaped
+    fn link(&self, id: &str, text: &str) -> String;
+    /// Inline code, `text` is escaped
+    fn code(&self, text: &str) -> String;
+    /// Escape plain text
+    fn text(&self, text: &str) -> String;
+}
+
+struct Markdown(String);
+
+impl Markup for Markdown {
+    fn heading(&mut self, level: usize, id: &str, text: &str) {
+        writeln!(
+            self.0,
+            "{} <a id=\"{}\"></a>{}\n",
+            "#".repeat(level),
+            id,
+            self.text(text)
+        )
+        .unwrap();
+    }
+    fn paragraph(&mut self, text: &str) {
+        writeln!(self.0, "{}\n", text).unwrap();
+    }
+    fn table(&mut self, header: &[&str], rows: &[Vec<String>]) {
+        writeln!(self.0, "| {} |", header.join(" | ")).unwrap();
+        writeln!(self.0, "|{}", "---|".repeat(header.len())).unwrap();
+        for row in rows {
+            writeln!(self.0, "| {} |", row.join(" | ")).unwrap();
+        }
+        self.0.push('\n');
+    }
+    fn diagram(&mut self, rows: &[Vec<Segment>]) {
+        self.0.push_str("```text\n");
+        for row in rows {
+            let msb = row.first().map(|s| s.msb).unwrap_or(0);
+            let lsb = row.last().map(|s| s.lsb).unwrap_or(0);
+            let bits: String = (lsb..=msb).rev().map(|b| format!("{:>3}", b)).collect();
+            writeln!(self.0, "{}", bits).unwrap();
+            let mut line = String::new();
+            for s in row {
+                let width = (s.msb - s.lsb + 1) as usize * 3 - 1;
+                let mut name = s.name.clone().unwrap_or_else(|| "-".to_string());
+                name.truncate(width);
+                write!(line, "|{:^width$}", name, width = width).unwrap();
+            }
+            writeln!(self.0, "{}|", line).unwrap();
+        }
+        self.0.push_str("```\n\n");
+    }
+    fn link(&self, id: &str, text: &str) -> String {
+        format!("[{}](#{})", self.text(text), id)
+    }
+    fn code(&self, text: &str) -> String {
+        format!("`{}`", text.replace('`', "'"))
+    }
+    fn text(&self, text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        for c in one_line(text).chars() {
+            if "\\|*_[]<>`#".contains(c) {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+        }
+        escaped
+    }
+}
+
+struct Html(String);
+
+impl Markup for Html {
+    fn heading(&mut self, level: usize, id: &str, text: &str) {
+        writeln!(
+            self.0,
+            "<h{0} id=\"{1}\">{2}</h{0}>",
+            level,
+            id,
+            self.text(text)
+        )
+        .unwrap();
+    }
+    fn paragraph(&mut self, text: &str) {
+        writeln!(self.0, "<p>{}</p>", text).unwrap();
+    }
+    fn table(&mut self, header: &[&str], rows: &[Vec<String>]) {
+        self.0.push_str("<table>\n<tr>");
+        for h in header {
+            write!(self.0, "<th>{}</th>", h).unwrap();
+        }
+        self.0.push_str("</tr>\n");
+        for row in rows {
+            self.0.push_str("<tr>");
+            for cell in row {
+                write!(self.0, "<td>{}</td>", cell).unwrap();
+            }
+            self.0.push_str("</tr>\n");
+        }
+        self.0.push_str("</table>\n");
+    }
+    fn diagram(&mut self, rows: &[Vec<Segment>]) {
+        self.0.push_str("<table class=\"bitfield\">\n");
+        for row in rows {
+            let msb = row.first().map(|s| s.msb).unwrap_or(0);
+            let lsb = row.last().map(|s| s.lsb).unwrap_or(0);
+            self.0.push_str("<tr>");
+            for b in (lsb..=msb).rev() {
+                write!(self.0, "<th>{}</th>", b).unwrap();
+            }
+            self.0.push_str("</tr>\n<tr>");
+            for s in row {
+                let span = s.msb - s.lsb + 1;
+                match &s.name {
+                    Some(name) => {
+                        write!(self.0, "<td colspan=\"{}\">{}</td>", span, self.text(name))
+                    }
+                    None => write!(self.0, "<td colspan=\"{}\" class=\"reserved\"></td>", span),
+                }
+                .unwrap();
+            }
+            self.0.push_str("</tr>\n");
+        }
+        self.0.push_str("</table>\n");
+    }
+    fn link(&self, id: &str, text: &str) -> String {
+        format!("<a href=\"#{}\">{}</a>", id, self.text(text))
+    }
+    fn code(&self, text: &str) -> String {
+        format!("<code>{}</code>", escape_html(text))
+    }
+    fn text(&self, text: &str) -> String {
+        escape_html(&one_line(text))
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Remove the `%s` placeholder from the name of an array
+fn strip(name: &str) -> String {
+    name.replace("[%s]", "").replace("%s", "")
+}
+
+/// Anchor of the element with the path
+fn anchor(path: &str) -> String {
+    strip(path)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+fn hex(value: u64) -> String {
+    format!("0x{:08X}", value)
+}
+
+/// A register or cluster with the path and offset of its parent, and its effective properties
+struct Entry<'a> {
+    item: Item<'a>,
+    scope: String,
+    offset: u64,
+    properties: RegisterProperties,
+}
+
+#[derive(Clone, Copy)]
+enum Item<'a> {
+    Register(&'a Register),
+    Cluster(&'a Cluster),
+}
+
+impl<'a> Entry<'a> {
+    fn path(&self) -> String {
+        let name = match self.item {
+            Item::Register(r) => &r.name,
+            Item::Cluster(c) => &c.name,
+        };
+        format!("{}.{}", self.scope, strip(name))
+    }
+
+    fn address(&self) -> u64 {
+        self.offset
+            + match self.item {
+                Item::Register(r) => r.address_offset,
+                Item::Cluster(c) => c.address_offset,
+            } as u64
+    }
+
+    fn description(&self) -> Option<&'a str> {
+        match self.item {
+            Item::Register(r) => r.description.as_deref(),
+            Item::Cluster(c) => c.description.as_deref(),
+        }
+    }
+
+    /// Path of the element the entry is derived from,
+    /// a name without a path refers to an element of the same scope
+    fn derived_from(&self) -> Option<(String, &'a str)> {
+        let derived = match self.item {
+            Item::Register(r) => r.derived_from.as_deref(),
+            Item::Cluster(c) => c.derived_from.as_deref(),
+        }?;
+        let target = if derived.contains('.') {
+            derived.to_string()
+        } else {
+            format!("{}.{}", self.scope, derived)
+        };
+        Some((target, derived))
+    }
+}
+
+fn document(m: &mut impl Markup, device: &Device) {
+    m.heading(1, &anchor(&device.name), &device.name);
+    if let Some(description) = &device.description {
+        m.paragraph(&m.text(description));
+    }
+    let mut info = Vec::new();
+    if let Some(version) = &device.version {
+        info.push(vec![m.text("Version"), m.text(version)]);
+    }
+    if let Some(cpu) = &device.cpu {
+        info.push(vec![
+            m.text("CPU"),
+            m.text(&format!("{} {}", cpu.name, cpu.revision)),
+        ]);
+    }
+    if let Some(bits) = device.address_unit_bits {
+        info.push(vec![m.text("Address unit bits"), bits.to_string()]);
+    }
+    if let Some(width) = device.width {
+        info.push(vec![m.text("Width"), width.to_string()]);
+    }
+    m.table(&["Property", "Value"], &info);
+
+    interrupts(m, device);
+
+    m.heading(2, "peripherals", "Peripherals");
+    let rows: Vec<_> = device
+        .peripherals
+        .iter()
+        .map(|p| {
+            vec![
+                m.link(&anchor(&p.name), &strip(&p.name)),
+                m.code(&hex(p.base_address)),
+                p.group_name
+                    .as_deref()
+                    .map(|g| m.text(g))
+                    .unwrap_or_default(),
+                p.derived_from
+                    .as_deref()
+                    .map(|d| m.link(&anchor(d), d))
+                    .unwrap_or_default(),
+                p.description
+                    .as_deref()
+                    .map(|d| m.text(d))
+                    .unwrap_or_default(),
+            ]
+        })
+        .collect();
+    m.table(
+        &[
+            "Name",
+            "Base address",
+            "Group",
+            "Derived from",
+            "Description",
+        ],
+        &rows,
+    );
+
+    for p in &device.peripherals {
+        peripheral(m, device, p);
+    }
+}
+
+fn interrupts(m: &mut impl Markup, device: &Device) {
+    let mut interrupts: Vec<_> = device
+        .peripherals
+        .iter()
+        .flat_map(|p| p.interrupt.iter().map(move |i| (i, p)))
+        .collect();
+    if interrupts.is_empty() {
+        return;
+    }
+    interrupts.sort_by_key(|(i, _)| i.value);
+    m.heading(2, "interrupts", "Interrupts");
+    let rows: Vec<_> = interrupts
+        .iter()
+        .map(|(i, p)| {
+            vec![
+                i.value.to_string(),
+                m.text(&i.name),
+                m.link(&anchor(&p.name), &strip(&p.name)),
+                i.description
+                    .as_deref()
+                    .map(|d| m.text(d))
+                    .unwrap_or_default(),
+            ]
+        })
+        .collect();
+    m.table(&["Value", "Name", "Peripheral", "Description"], &rows);
+}
+
+fn peripheral(m: &mut impl Markup, device: &Device, p: &Peripheral) {
+    let name = strip(&p.name);
+    m.heading(2, &anchor(&p.name), &name);
+    if let Some(description) = &p.description {
+        m.paragraph(&m.text(description));
+    }
+    let mut info = vec![vec![m.text("Base address"), m.code(&hex(p.base_address))]];
+    if let Peripheral::Array(_, dim) = p {
+        info.push(vec![
+            m.text("Array"),
+            m.text(&format!(
+                "{} elements, {:#x} apart",
+                dim.dim, dim.dim_increment
+            )),
+        ]);
+    }
+    if let Some(derived) = &p.derived_from {
+        info.push(vec![
+            m.text("Derived from"),
+            m.link(&anchor(derived), derived),
+        ]);
+    }
+    m.table(&["Property", "Value"], &info);
+
+    let registers = match &p.registers {
+        Some(registers) => registers,
+        None => {
+            if let Some(derived) = &p.derived_from {
+                m.paragraph(&format!(
+                    "{} {}.",
+                    m.text("Registers are described in"),
+                    m.link(&anchor(derived), derived)
+                ));
+            }
+            return;
+        }
+    };
+    let properties = p
+        .default_register_properties
+        .inherit(&device.default_register_properties);
+    let mut entries = Vec::new();
+    collect(&mut entries, registers, &name, 0, &properties);
+
+    let rows: Vec<_> = entries
+        .iter()
+        .map(|e| {
+            let path = e.path();
+            let mut row = vec![
+                m.link(&anchor(&path), &path[name.len() + 1..]),
+                m.code(&format!("{:#x}", e.address())),
+            ];
+            match e.item {
+                Item::Register(_) => row.extend(vec![
+                    e.properties.size.unwrap_or(32).to_string(),
+                    m.text(e.properties.access.unwrap_or_default().as_str()),
+                    e.properties
+                        .reset_value
+                        .map(|v| m.code(&format!("{:#x}", v)))
+                        .unwrap_or_default(),
+                ]),
+                Item::Cluster(_) => row.extend(vec![String::new(); 3]),
+            }
+            row.push(e.description().map(|d| m.text(d)).unwrap_or_default());
+            row
+        })
+        .collect();
+    m.table(
+        &[
+            "Register",
+            "Offset",
+            "Size",
+            "Access",
+            "Reset value",
+            "Description",
+        ],
+        &rows,
+    );
+
+    for e in &entries {
+        match e.item {
+            Item::Register(r) => register(m, e, r),
+            Item::Cluster(c) => cluster(m, e, c),
+        }
+    }
+}
+
+/// Collect the registers and clusters of the children, with the registers of
+/// clusters following their cluster
+fn collect<'a>(
+    entries: &mut Vec<Entry<'a>>,
+    children: &'a [RegisterCluster],
+    scope: &str,
+    offset: u64,
+    properties: &RegisterProperties,
+) {
+    for rc in children {
+        match rc {
+            RegisterCluster::Register(r) => entries.push(Entry {
+                item: Item::Register(r),
+                scope: scope.to_string(),
+                offset,
+                properties: r.properties.inherit(properties),
+            }),
+            RegisterCluster::Cluster(c) => {
+                let entry = Entry {
+                    item: Item::Cluster(c),
+                    scope: scope.to_string(),
+                    offset,
+                    properties: c.default_register_properties.inherit(properties),
+                };
+                let (path, address, properties) = (entry.path(), entry.address(), entry.properties);
+                entries.push(entry);
+                collect(entries, &c.children, &path, address, &properties);
+            }
+        }
+    }
+}
+
+fn cluster(m: &mut impl Markup, e: &Entry, c: &Cluster) {
+    let path = e.path();
+    m.heading(3, &anchor(&path), &path);
+    if let Some(description) = &c.description {
+        m.paragraph(&m.text(description));
+    }
+    let mut info = vec![vec![
+        m.text("Offset"),
+        m.code(&format!("{:#x}", e.address())),
+    ]];
+    if let Cluster::Array(_, dim) = c {
+        info.push(vec![
+            m.text("Array"),
+            m.text(&format!(
+                "{} elements, {:#x} apart",
+                dim.dim, dim.dim_increment
+            )),
+        ]);
+    }
+    let derived = e.derived_from();
+    if let Some((target, derived)) = &derived {
+        info.push(vec![
+            m.text("Derived from"),
+            m.link(&anchor(target), derived),
+        ]);
+    }
+    m.table(&["Property", "Value"], &info);
+    if let (Some((target, derived)), true) = (&derived, c.children.is_empty()) {
+        m.paragraph(&format!(
+            "{} {}.",
+            m.text("Registers are described in"),
+            m.link(&anchor(target), derived)
+        ));
+    }
+}
+
+fn register(m: &mut impl Markup, e: &Entry, r: &Register) {
+    let path = e.path();
+    m.heading(3, &anchor(&path), &path);
+    if let Some(description) = &r.description {
+        m.paragraph(&m.text(description));
+    }
+    let size = e.properties.size.unwrap_or(32);
+    let mut info = vec![vec![
+        m.text("Offset"),
+        m.code(&format!("{:#x}", e.address())),
+    ]];
+    if let Register::Array(_, dim) = r {
+        info.push(vec![
+            m.text("Array"),
+            m.text(&format!(
+                "{} elements, {:#x} apart",
+                dim.dim, dim.dim_increment
+            )),
+        ]);
+    }
+    if let Some(reset) = e.properties.reset_value {
+        info.push(vec![
+            m.text("Reset value"),
+            m.code(&format!("{:#x}", reset)),
+        ]);
+    }
+    if let Some((target, derived)) = e.derived_from() {
+        info.push(vec![
+            m.text("Derived from"),
+            m.link(&anchor(&target), derived),
+        ]);
+    }
+    if let Some(alternate) = &r.alternate_register {
+        let target = format!("{}.{}", e.scope, alternate);
+        info.push(vec![
+            m.text("Alternate of"),
+            m.link(&anchor(&target), alternate),
+        ]);
+    }
+    m.table(&["Property", "Value"], &info);
+
+    let fields = match &r.fields {
+        Some(fields) if !fields.is_empty() => fields,
+        _ => return,
+    };
+    m.diagram(&diagram(fields, size));
+
+    let access = e.properties.access.unwrap_or_default();
+    let rows: Vec<_> = fields
+        .iter()
+        .map(|f| {
+            let reset = e.properties.reset_value.map(|reset| {
+                let width = f.bit_range.width;
+                let mask = if width >= 64 {
+                    u64::MAX
+                } else {
+                    (1 << width) - 1
+                };
+                m.code(&format!(
+                    "{:#x}",
+                    checked_shr(reset, f.bit_range.offset) & mask
+                ))
+            });
+            vec![
+                m.text(&f.bit_range.bit_range()),
+                m.text(&strip(&f.name)),
+                m.text(f.access.unwrap_or(access).as_str()),
+                reset.unwrap_or_default(),
+                f.description
+                    .as_deref()
+                    .map(|d| m.text(d))
+                    .unwrap_or_default(),
+            ]
+        })
+        .collect();
+    m.table(&["Bits", "Field", "Access", "Reset", "Description"], &rows);
+
+    for f in fields {
+        for ev in &f.enumerated_values {
+            enumerated_values(m, &path, fields, f, ev);
+        }
+    }
+}
+
+/// Anchor of enumerated values of a field of the register at `path`
+fn values_anchor(path: &str, f: &Field, e: &EnumeratedValues) -> String {
+    let name = e.name.as_deref().unwrap_or_else(|| e.usage().as_str());
+    anchor(&format!("{}.{}.{}", path, f.name, name))
+}
+
+fn enumerated_values(
+    m: &mut impl Markup,
+    path: &str,
+    fields: &[Field],
+    f: &Field,
+    e: &EnumeratedValues,
+) {
+    let title = match &e.name {
+        Some(name) => format!(
+            "{} values ({}, {})",
+            strip(&f.name),
+            name,
+            e.usage().as_str()
+        ),
+        None => format!("{} values ({})", strip(&f.name), e.usage().as_str()),
+    };
+    m.heading(4, &values_anchor(path, f, e), &title);
+    if let Some(derived) = &e.derived_from {
+        // A name without a path refers to enumerated values of the same register
+        let source = fields.iter().find_map(|f| {
+            f.enumerated_values
+                .iter()
+                .find(|ev| ev.name.as_deref() == Some(derived.as_str()))
+                .map(|ev| values_anchor(path, f, ev))
+        });
+        let target = match source {
+            Some(target) => Some(target),
+            None if derived.contains('.') => Some(anchor(derived)),
+            None => None,
+        };
+        let link = match target {
+            Some(target) => m.link(&target, derived),
+            None => m.text(derived),
+        };
+        m.paragraph(&format!("{} {}.", m.text("Derived from"), link));
+    }
+    if e.values.is_empty() {
+        return;
+    }
+    let rows: Vec<_> = e
+        .values
+        .iter()
+        .map(|v| {
+            vec![
+                match (v.value, v.is_default) {
+                    (Some(value), _) => m.code(&format!("{:#x}", value)),
+                    (None, Some(true)) => m.text("default"),
+                    _ => String::new(),
+                },
+                m.text(&v.name),
+                v.description
+                    .as_deref()
+                    .map(|d| m.text(d))
+                    .unwrap_or_default(),
+            ]
+        })
+        .collect();
+    m.table(&["Value", "Name", "Description"], &rows);
+}
+
+/// Split the bits of a register into rows of 16 bits, from the most significant bit
+fn diagram(fields: &[Field], size: u32) -> Vec<Vec<Segment>> {
+    let size = if size == 0 { 1 } else { size.min(64) };
+    let mut owners: Vec<Option<String>> = vec![None; size as usize];
+    for f in fields {
+        let elements: Vec<(String, u32)> = match f {
+            Field::Single(_) => vec![(f.name.clone(), f.bit_range.offset)],
+            Field::Array(_, dim) => dim
+                .indexes()
+                .enumerate()
+                .map(|(i, idx)| {
+                    (
+                        f.name.replace("[%s]", &idx).replace("%s", &idx),
+                        f.bit_range.offset + i as u32 * dim.dim_increment,
+                    )
+                })
+                .collect(),
+        };
+        for (name, lsb) in elements {
+            for bit in lsb.min(size)..(lsb + f.bit_range.width).min(size) {
+                owners[bit as usize] = Some(name.clone());
+            }
+        }
+    }
+    let mut rows = Vec::new();
+    let mut top = size;
+    while top > 0 {
+        let bottom = top.saturating_sub(16);
+        let mut row: Vec<Segment> = Vec::new();
+        for bit in (bottom..top).rev() {
+            let owner = &owners[bit as usize];
+            match row.last_mut() {
+                Some(s) if &s.name == owner => s.lsb = bit,
+                _ => row.push(Segment {
+                    name: owner.clone(),
+                    msb: bit,
+                    lsb: bit,
+                }),
+            }
+        }
+        rows.push(row);
+        top = bottom;
+    }
+    rows
+}
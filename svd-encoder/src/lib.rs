@@ -32,12 +32,71 @@ pub trait EncodeChildren {
 /// Encodes a device object to an SVD (XML) string
 pub fn encode(d: &Device) -> Result<String, EncodeError> {
     let root = d.encode()?;
+    Ok(write_element(&root))
+}
+
+/// A device split into a manifest and one file per peripheral
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SplitDevice {
+    /// Device description, where each peripheral is replaced with `<include file="..."/>`
+    pub manifest: String,
+    /// Peripheral descriptions as `(file name, content)` pairs, in the order of the device
+    pub peripherals: Vec<(String, String)>,
+}
+
+/// Encodes a device object to a manifest and one SVD (XML) fragment per peripheral.
+///
+/// File names are derived from the peripheral names. The device can be
+/// reassembled with `svd_parser::parse_split`.
+pub fn encode_split(d: &Device) -> Result<SplitDevice, EncodeError> {
+    let mut peripherals = Vec::with_capacity(d.peripherals.len());
+    let mut includes = Vec::with_capacity(d.peripherals.len());
+    for p in &d.peripherals {
+        let file = split_file_name(&p.name, &peripherals);
+        let mut include = Element::new("include");
+        include
+            .attributes
+            .insert(String::from("file"), file.clone());
+        includes.push(XMLNode::Element(include));
+        peripherals.push((file, write_element(&p.encode()?)));
+    }
+    let manifest = write_element(&device::encode_device(d, includes)?);
+    Ok(SplitDevice {
+        manifest,
+        peripherals,
+    })
+}
+
+/// Make a unique file name from the peripheral name, replacing `%s` placeholders
+fn split_file_name(name: &str, used: &[(String, String)]) -> String {
+    let stem: String = name
+        .replace("[%s]", "")
+        .replace("%s", "x")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let mut file = format!("{}.svd", stem);
+    let mut i = 1;
+    while used.iter().any(|(f, _)| f == &file) {
+        file = format!("{}_{}.svd", stem, i);
+        i += 1;
+    }
+    file
+}
+
+fn write_element(root: &Element) -> String {
     let mut wr = Vec::new();
     let mut cfg = EmitterConfig::new();
     cfg.perform_indent = true;
     cfg.pad_self_closing = false;
     root.write_with_config(&mut wr, cfg).unwrap();
-    Ok(String::from_utf8(wr).unwrap())
+    String::from_utf8(wr).unwrap()
 }
 
 /// Defines extensions for implementation over xmltree::Element
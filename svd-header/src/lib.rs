@@ -0,0 +1,793 @@
+//! CMSIS C header generator.
+//! This generates a device header with the layout of the headers generated by SVDConv:
+//!
+//! - the `IRQn_Type` enum with the Cortex-M exceptions and the device interrupts,
+//! - the processor configuration and the CMSIS core header include, if the device has a `cpu`,
+//! - a `typedef struct` for each peripheral and cluster, with `RESERVED` padding,
+//!   anonymous unions for registers at the same offset and `__IM`/`__OM`/`__IOM`
+//!   qualifiers derived from `access`,
+//! - enums for `dimArrayIndex`,
+//! - `_BASE` and peripheral pointer macros for each peripheral instance,
+//! - `_Pos` and `_Msk` macros and enums of enumerated values for each field.
+//!
+//! Type names use `headerStructName`, or `dimName` for cluster arrays, and register
+//! names get `prependToName` and `appendToName` of their peripheral. Peripherals
+//! derived from another peripheral without registers of their own share its type.
+//! Clusters derived from another cluster without registers of their own use its
+//! registers and `headerStructName`, and a struct is only generated once for each
+//! type name.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use svd_rs as svd;
+
+use crate::svd::{
+    checked_shl, Access, Cluster, Device, DimElement, Field, Peripheral, PeripheralInfo, Register,
+    RegisterCluster, RegisterProperties,
+};
+
+/// Errors of [`generate`]
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    /// A member starts inside of the previous member of the struct
+    #[error("`{1}` overlaps the previous member of `{0}`")]
+    Overlap(String, String),
+    /// Register size doesn't match a C integer type
+    #[error("Register `{0}` has unsupported size {1}")]
+    UnsupportedSize(String, u32),
+    /// Cluster is larger than the increment of its array
+    #[error("Cluster `{0}` is larger than its `dimIncrement`")]
+    ClusterTooLarge(String),
+}
+
+/// Generate the C header of the device
+pub fn generate(device: &Device) -> Result<String, Error> {
+    let mut g = Generator {
+        device,
+        types: String::new(),
+        enums: String::new(),
+        fields: String::new(),
+        emitted: HashSet::new(),
+        cluster_sizes: HashMap::new(),
+    };
+    let mut out = String::new();
+    g.preamble(&mut out);
+    g.interrupts(&mut out);
+    g.processor(&mut out);
+    for p in &device.peripherals {
+        g.peripheral(p)?;
+    }
+    section(&mut out, "Device Specific Cluster and Enum Section");
+    out.push_str(&g.enums);
+    section(&mut out, "Device Specific Peripheral Section");
+    out.push_str(&g.types);
+    g.memory_map(&mut out);
+    section(&mut out, "Pos/Mask Peripheral Section");
+    out.push_str(&g.fields);
+    let guard = guard(&device.name);
+    writeln!(out, "#ifdef __cplusplus").unwrap();
+    writeln!(out, "}}").unwrap();
+    writeln!(out, "#endif").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "#endif /* {} */", guard).unwrap();
+    Ok(out)
+}
+
+/// Cortex-M exceptions, `true` if only present on ARMv7-M and later
+const EXCEPTIONS: &[(&str, i32, &str, bool)] = &[
+    (
+        "Reset",
+        -15,
+        "Reset Vector, invoked on Power up and warm reset",
+        false,
+    ),
+    (
+        "NonMaskableInt",
+        -14,
+        "Non maskable Interrupt, cannot be stopped or preempted",
+        false,
+    ),
+    ("HardFault", -13, "Hard Fault, all classes of Fault", false),
+    (
+        "MemoryManagement",
+        -12,
+        "Memory Management, MPU mismatch, including Access Violation and No Match",
+        true,
+    ),
+    (
+        "BusFault",
+        -11,
+        "Bus Fault, Pre-Fetch-, Memory Access Fault, other address/memory related Fault",
+        true,
+    ),
+    (
+        "UsageFault",
+        -10,
+        "Usage Fault, i.e. Undef Instruction, Illegal State Transition",
+        true,
+    ),
+    (
+        "SVCall",
+        -5,
+        "System Service Call via SVC instruction",
+        false,
+    ),
+    ("DebugMonitor", -4, "Debug Monitor", true),
+    ("PendSV", -2, "Pendable request for system service", false),
+    ("SysTick", -1, "System Tick Timer", false),
+];
+
+struct Generator<'a> {
+    device: &'a Device,
+    /// Struct definitions
+    types: String,
+    /// `dimArrayIndex` enums
+    enums: String,
+    /// `_Pos`/`_Msk` macros and enumerated values
+    fields: String,
+    /// Names of the generated structs
+    emitted: HashSet<String>,
+    /// Sizes of the generated cluster structs
+    cluster_sizes: HashMap<String, u64>,
+}
+
+/// A member of a struct
+struct Member {
+    offset: u64,
+    size: u64,
+    name: String,
+    decl: String,
+    description: Option<String>,
+}
+
+impl<'a> Generator<'a> {
+    fn preamble(&self, out: &mut String) {
+        let d = self.device;
+        writeln!(out, "/*").unwrap();
+        writeln!(out, " * @file     {}.h", d.name).unwrap();
+        writeln!(
+            out,
+            " * @brief    CMSIS device header for {}",
+            d.description
+                .as_deref()
+                .map(one_line)
+                .unwrap_or_else(|| d.name.clone())
+        )
+        .unwrap();
+        if let Some(version) = &d.version {
+            writeln!(out, " * @version  {}", version).unwrap();
+        }
+        writeln!(out, " *").unwrap();
+        writeln!(out, " * Generated from the SVD description, do not edit.").unwrap();
+        writeln!(out, " */").unwrap();
+        writeln!(out).unwrap();
+        let guard = guard(&d.name);
+        writeln!(out, "#ifndef {}", guard).unwrap();
+        writeln!(out, "#define {}", guard).unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "#ifdef __cplusplus").unwrap();
+        writeln!(out, "extern \"C\" {{").unwrap();
+        writeln!(out, "#endif").unwrap();
+        writeln!(out).unwrap();
+    }
+
+    fn interrupts(&self, out: &mut String) {
+        section(out, "Interrupt Number Definition");
+        writeln!(out, "typedef enum {{").unwrap();
+        if let Some(cpu) = &self.device.cpu {
+            let v6m = matches!(cpu.name.as_str(), "CM0" | "CM0PLUS" | "CM0+" | "CM1");
+            writeln!(out, "/* ARM Cortex-M Specific Interrupt Numbers */").unwrap();
+            for (name, value, description, v7m) in EXCEPTIONS {
+                if !(v6m && *v7m) {
+                    member_line(
+                        out,
+                        &format!("  {}_IRQn = {},", name, value),
+                        &format!("{} {}", value, description),
+                    );
+                }
+            }
+        }
+        writeln!(out, "/* {} Specific Interrupt Numbers */", self.device.name).unwrap();
+        let mut interrupts: Vec<_> = self
+            .device
+            .peripherals
+            .iter()
+            .flat_map(|p| p.interrupt.iter())
+            .collect();
+        interrupts.sort_by_key(|i| i.value);
+        interrupts.dedup_by_key(|i| i.value);
+        for i in interrupts {
+            let description = match &i.description {
+                Some(d) => format!("{} {}", i.value, one_line(d)),
+                None => i.value.to_string(),
+            };
+            member_line(
+                out,
+                &format!("  {}_IRQn = {},", i.name, i.value),
+                &description,
+            );
+        }
+        writeln!(out, "}} IRQn_Type;").unwrap();
+        writeln!(out).unwrap();
+    }
+
+    fn processor(&self, out: &mut String) {
+        section(out, "Processor and Core Peripheral Section");
+        if let Some(cpu) = &self.device.cpu {
+            let core = cpu.name.replace('+', "PLUS");
+            define(
+                out,
+                &format!("__{}_REV", core),
+                &format!("0x{:04X}U", revision(&cpu.revision)),
+            );
+            define(out, "__NVIC_PRIO_BITS", &cpu.nvic_priority_bits.to_string());
+            define(
+                out,
+                "__Vendor_SysTickConfig",
+                &(cpu.has_vendor_systick as u8).to_string(),
+            );
+            define(out, "__MPU_PRESENT", &(cpu.mpu_present as u8).to_string());
+            define(out, "__FPU_PRESENT", &(cpu.fpu_present as u8).to_string());
+            if let Some(dp) = cpu.fpu_double_precision {
+                define(out, "__FPU_DP", &(dp as u8).to_string());
+            }
+            if let Some(dsp) = cpu.dsp_present {
+                define(out, "__DSP_PRESENT", &(dsp as u8).to_string());
+            }
+            if let Some(vtor) = cpu.vtor_present {
+                define(out, "__VTOR_PRESENT", &(vtor as u8).to_string());
+            }
+            writeln!(out).unwrap();
+            writeln!(out, "#include \"core_{}.h\"", core.to_lowercase()).unwrap();
+            writeln!(out, "#include \"system_{}.h\"", self.device.name).unwrap();
+        } else {
+            writeln!(out, "#include <stdint.h>").unwrap();
+        }
+        writeln!(out).unwrap();
+        for (q, def) in &[
+            ("__IM", "volatile const"),
+            ("__OM", "volatile"),
+            ("__IOM", "volatile"),
+        ] {
+            writeln!(out, "#ifndef {}", q).unwrap();
+            writeln!(out, "  #define {} {}", q, def).unwrap();
+            writeln!(out, "#endif").unwrap();
+        }
+        writeln!(out).unwrap();
+    }
+
+    fn peripheral(&mut self, p: &'a Peripheral) -> Result<(), Error> {
+        let children = match &p.registers {
+            Some(children) => children,
+            None => return Ok(()),
+        };
+        let base = type_base(p);
+        let type_name = format!("{}_Type", base);
+        if !self.emitted.insert(type_name.clone()) {
+            return Ok(());
+        }
+        if let Peripheral::Array(_, dim) = p {
+            self.dim_enum(&base, dim);
+        }
+        let props = p
+            .default_register_properties
+            .inherit(&self.device.default_register_properties);
+        let mut types = String::new();
+        self.structure(
+            &mut types, &type_name, &base, &base, p, children, &props, None,
+        )?;
+        writeln!(self.types, "/* {} */", p.name.replace("%s", "")).unwrap();
+        self.types.push_str(&types);
+        Ok(())
+    }
+
+    /// Write the struct `type_name` of the children, and the structs of its clusters
+    /// before it, and return its size
+    #[allow(clippy::too_many_arguments)]
+    fn structure(
+        &mut self,
+        out: &mut String,
+        type_name: &str,
+        base: &str,
+        macro_base: &str,
+        p: &PeripheralInfo,
+        children: &[RegisterCluster],
+        props: &RegisterProperties,
+        pad_to: Option<u64>,
+    ) -> Result<u64, Error> {
+        let mut members = Vec::new();
+        for rc in children {
+            match rc {
+                RegisterCluster::Register(r) => {
+                    self.register(&mut members, macro_base, p, r, props)?;
+                }
+                RegisterCluster::Cluster(c) => {
+                    let name = strip(&c.name);
+                    let source = cluster_source(p, children, c);
+                    let props = c
+                        .default_register_properties
+                        .inherit(&source.default_register_properties)
+                        .inherit(props);
+                    let dim = match c {
+                        Cluster::Single(_) => None,
+                        Cluster::Array(_, dim) => Some(dim),
+                    };
+                    let cluster_base = format!("{}_{}", base, name);
+                    let header_struct_name = c
+                        .header_struct_name
+                        .as_ref()
+                        .or(source.header_struct_name.as_ref());
+                    let cluster_type = match (header_struct_name, dim) {
+                        (Some(n), _) => format!("{}_Type", n),
+                        (
+                            None,
+                            Some(DimElement {
+                                dim_name: Some(n), ..
+                            }),
+                        ) => n.clone(),
+                        _ => format!("{}_Type", cluster_base),
+                    };
+                    let macro_base = format!("{}_{}", macro_base, name);
+                    let pad = dim.map(|d| d.dim_increment as u64);
+                    let size = if self.emitted.insert(cluster_type.clone()) {
+                        let size = self.structure(
+                            out,
+                            &cluster_type,
+                            &cluster_base,
+                            &macro_base,
+                            p,
+                            &source.children,
+                            &props,
+                            pad,
+                        )?;
+                        self.cluster_sizes.insert(cluster_type.clone(), size);
+                        size
+                    } else {
+                        let size = self.cluster_sizes[&cluster_type];
+                        if matches!(pad, Some(pad) if size > pad) {
+                            return Err(Error::ClusterTooLarge(cluster_type));
+                        }
+                        size
+                    };
+                    if let Some(dim) = dim {
+                        self.dim_enum(&cluster_base, dim);
+                    }
+                    let (decl, size) = match dim {
+                        None => (format!("{} {};", cluster_type, name), size),
+                        Some(dim) => (
+                            format!("{} {}[{}];", cluster_type, name, dim.dim),
+                            dim.dim_increment as u64 * dim.dim as u64,
+                        ),
+                    };
+                    members.push(Member {
+                        offset: c.address_offset as u64,
+                        size,
+                        name: name.clone(),
+                        decl,
+                        description: c.description.clone(),
+                    });
+                }
+            }
+        }
+
+        members.sort_by_key(|m| m.offset);
+        let mut body = String::new();
+        let mut pos = 0;
+        let mut reserved = 0;
+        let mut i = 0;
+        while i < members.len() {
+            let offset = members[i].offset;
+            let group = members[i..]
+                .iter()
+                .take_while(|m| m.offset == offset)
+                .count();
+            if offset < pos {
+                return Err(Error::Overlap(
+                    type_name.to_string(),
+                    members[i].name.clone(),
+                ));
+            }
+            if offset > pos {
+                padding(&mut body, &mut reserved, pos, offset - pos, "  ");
+            }
+            let mut size = 0;
+            if group == 1 {
+                member(&mut body, &members[i], "  ");
+                size = members[i].size;
+            } else {
+                writeln!(body, "  union {{").unwrap();
+                for m in &members[i..i + group] {
+                    member(&mut body, m, "    ");
+                    size = size.max(m.size);
+                }
+                writeln!(body, "  }};").unwrap();
+            }
+            pos = offset + size;
+            i += group;
+        }
+        if let Some(pad_to) = pad_to {
+            if pos > pad_to {
+                return Err(Error::ClusterTooLarge(type_name.to_string()));
+            }
+            if pad_to > pos {
+                padding(&mut body, &mut reserved, pos, pad_to - pos, "  ");
+                pos = pad_to;
+            }
+        }
+
+        writeln!(out, "typedef struct {{").unwrap();
+        out.push_str(&body);
+        member_line(
+            out,
+            &format!("}} {};", type_name),
+            &format!("Size = {} (0x{:x})", pos, pos),
+        );
+        writeln!(out).unwrap();
+        Ok(pos)
+    }
+
+    fn register(
+        &mut self,
+        members: &mut Vec<Member>,
+        macro_base: &str,
+        p: &PeripheralInfo,
+        r: &Register,
+        props: &RegisterProperties,
+    ) -> Result<(), Error> {
+        let props = r.properties.inherit(props);
+        let size = props.size.unwrap_or(32);
+        let ty = match size {
+            8 => "uint8_t",
+            16 => "uint16_t",
+            32 => "uint32_t",
+            64 => "uint64_t",
+            _ => return Err(Error::UnsupportedSize(r.name.clone(), size)),
+        };
+        let qualifier = match props.access.unwrap_or_default() {
+            Access::ReadOnly => "__IM ",
+            Access::WriteOnly | Access::WriteOnce => "__OM ",
+            _ => "__IOM",
+        };
+        let bytes = size as u64 / 8;
+        let name = format!(
+            "{}{}{}",
+            p.prepend_to_name.as_deref().unwrap_or(""),
+            strip(&r.name),
+            p.append_to_name.as_deref().unwrap_or("")
+        );
+        match r {
+            Register::Single(_) => members.push(Member {
+                offset: r.address_offset as u64,
+                size: bytes,
+                name: name.clone(),
+                decl: format!("{} {} {};", qualifier, ty, name),
+                description: r.description.clone(),
+            }),
+            Register::Array(_, dim) if dim.dim_increment as u64 == bytes => {
+                self.dim_enum(&format!("{}_{}", macro_base, name), dim);
+                members.push(Member {
+                    offset: r.address_offset as u64,
+                    size: bytes * dim.dim as u64,
+                    name: name.clone(),
+                    decl: format!("{} {} {}[{}];", qualifier, ty, name, dim.dim),
+                    description: r.description.clone(),
+                })
+            }
+            Register::Array(_, dim) => {
+                for (i, idx) in dim.indexes().enumerate() {
+                    let name = format!(
+                        "{}{}{}",
+                        p.prepend_to_name.as_deref().unwrap_or(""),
+                        instance(&r.name, &idx),
+                        p.append_to_name.as_deref().unwrap_or("")
+                    );
+                    members.push(Member {
+                        offset: r.address_offset as u64 + i as u64 * dim.dim_increment as u64,
+                        size: bytes,
+                        decl: format!("{} {} {};", qualifier, ty, name),
+                        name,
+                        description: r.description.clone(),
+                    });
+                }
+            }
+        }
+        self.field_macros(&format!("{}_{}", macro_base, name), r, size);
+        Ok(())
+    }
+
+    fn field_macros(&mut self, prefix: &str, r: &Register, size: u32) {
+        let fields = match &r.fields {
+            Some(fields) if !fields.is_empty() => fields,
+            _ => return,
+        };
+        writeln!(self.fields, "/* {} */", prefix).unwrap();
+        // `UL` is only 32 bits wide on some targets
+        let suffix = if size > 32 { "ULL" } else { "UL" };
+        for f in fields {
+            let instances: Vec<(String, u32)> = match f {
+                Field::Single(info) => vec![(strip(&info.name), info.bit_range.offset)],
+                Field::Array(info, dim) => dim
+                    .indexes()
+                    .enumerate()
+                    .map(|(i, idx)| {
+                        (
+                            instance(&info.name, &idx),
+                            info.bit_range.offset + i as u32 * dim.dim_increment,
+                        )
+                    })
+                    .collect(),
+            };
+            let width = f.bit_range.width;
+            let mask = if width >= 64 {
+                u64::MAX
+            } else {
+                (1 << width) - 1
+            };
+            for (name, offset) in &instances {
+                let description = f.description.as_deref().map(one_line);
+                let description = description.as_deref().unwrap_or(name);
+                member_line(
+                    &mut self.fields,
+                    &format!("#define {}_{}_Pos ({}UL)", prefix, name, offset),
+                    description,
+                );
+                member_line(
+                    &mut self.fields,
+                    &format!(
+                        "#define {}_{}_Msk (0x{:x}{})",
+                        prefix,
+                        name,
+                        checked_shl(mask, *offset),
+                        suffix
+                    ),
+                    description,
+                );
+            }
+            let usages = f.enumerated_values.len();
+            for e in &f.enumerated_values {
+                let values: Vec<_> = e.values.iter().filter(|v| v.value.is_some()).collect();
+                if values.is_empty() {
+                    continue;
+                }
+                let field_prefix = format!("{}_{}", prefix, strip(&f.name));
+                let enum_name = if usages > 1 {
+                    format!(
+                        "{}_{}_Enum",
+                        field_prefix,
+                        e.usage().as_str().replace('-', "_").to_uppercase()
+                    )
+                } else {
+                    format!("{}_Enum", field_prefix)
+                };
+                writeln!(self.fields, "typedef enum {{").unwrap();
+                for v in values {
+                    member_line(
+                        &mut self.fields,
+                        &format!("  {}_{} = {},", field_prefix, v.name, v.value.unwrap()),
+                        &v.description.as_deref().map(one_line).unwrap_or_default(),
+                    );
+                }
+                writeln!(self.fields, "}} {};", enum_name).unwrap();
+            }
+        }
+        writeln!(self.fields).unwrap();
+    }
+
+    /// Write the enum of the `dimArrayIndex` of an array
+    fn dim_enum(&mut self, base: &str, dim: &DimElement) {
+        let index = match &dim.dim_array_index {
+            Some(index) if !index.values.is_empty() => index,
+            _ => return,
+        };
+        let name = index.header_enum_name.as_deref().unwrap_or(base);
+        writeln!(self.enums, "typedef enum {{").unwrap();
+        for v in index.values.iter().filter(|v| v.value.is_some()) {
+            member_line(
+                &mut self.enums,
+                &format!("  {}_{} = {},", name, v.name, v.value.unwrap()),
+                &v.description.as_deref().map(one_line).unwrap_or_default(),
+            );
+        }
+        writeln!(self.enums, "}} {}_Enum;", name).unwrap();
+        writeln!(self.enums).unwrap();
+    }
+
+    fn memory_map(&self, out: &mut String) {
+        section(out, "Device Specific Peripheral Address Map");
+        let mut pointers = String::new();
+        for p in &self.device.peripherals {
+            let type_name = format!("{}_Type", type_base(self.source(p)));
+            let instances: Vec<(String, u64)> = match p {
+                Peripheral::Single(_) => vec![(p.name.clone(), p.base_address)],
+                Peripheral::Array(_, dim) => dim
+                    .indexes()
+                    .enumerate()
+                    .map(|(i, idx)| {
+                        (
+                            instance(&p.name, &idx),
+                            p.base_address + i as u64 * dim.dim_increment as u64,
+                        )
+                    })
+                    .collect(),
+            };
+            for (name, address) in instances {
+                define(
+                    out,
+                    &format!("{}_BASE", name),
+                    &format!("0x{:08X}UL", address),
+                );
+                if self.emitted.contains(&type_name) {
+                    define(
+                        &mut pointers,
+                        &name,
+                        &format!("(({}*) {}_BASE)", type_name, name),
+                    );
+                }
+            }
+        }
+        writeln!(out).unwrap();
+        section(out, "Peripheral declaration");
+        out.push_str(&pointers);
+        writeln!(out).unwrap();
+    }
+
+    /// Peripheral defining the registers of `p`
+    fn source(&self, p: &'a Peripheral) -> &'a Peripheral {
+        let mut source = p;
+        for _ in 0..self.device.peripherals.len() {
+            if source.registers.is_some() {
+                break;
+            }
+            match source
+                .derived_from
+                .as_ref()
+                .and_then(|name| self.device.peripherals.iter().find(|p| &p.name == name))
+            {
+                Some(parent) => source = parent,
+                None => break,
+            }
+        }
+        source
+    }
+}
+
+/// Cluster defining the children of `c`.
+///
+/// `derivedFrom` is looked up in `siblings`, or as a path in the peripheral.
+fn cluster_source<'c>(
+    p: &'c PeripheralInfo,
+    siblings: &'c [RegisterCluster],
+    c: &'c Cluster,
+) -> &'c Cluster {
+    let mut source = c;
+    // Limit the depth in case of circular references
+    for _ in 0..=siblings.len() {
+        if !source.children.is_empty() {
+            break;
+        }
+        match source
+            .derived_from
+            .as_ref()
+            .and_then(|path| find_cluster(p, siblings, path))
+        {
+            Some(parent) => source = parent,
+            None => break,
+        }
+    }
+    source
+}
+
+fn find_cluster<'c>(
+    p: &'c PeripheralInfo,
+    siblings: &'c [RegisterCluster],
+    path: &str,
+) -> Option<&'c Cluster> {
+    let find = |children: &'c [RegisterCluster], name: &str| {
+        children.iter().find_map(|rc| match rc {
+            RegisterCluster::Cluster(c) if c.name == name => Some(c),
+            _ => None,
+        })
+    };
+    if !path.contains('.') {
+        return find(siblings, path);
+    }
+    let mut names = path.split('.').peekable();
+    if names.peek() == Some(&p.name.as_str()) {
+        names.next();
+    }
+    let mut children = p.registers.as_deref()?;
+    let mut cluster = None;
+    for name in names {
+        let c = find(children, name)?;
+        children = &c.children;
+        cluster = Some(c);
+    }
+    cluster
+}
+
+fn section(out: &mut String, title: &str) {
+    writeln!(out, "/* {:=^100} */", format!(" {} ", title)).unwrap();
+    writeln!(out).unwrap();
+}
+
+fn define(out: &mut String, name: &str, value: &str) {
+    writeln!(out, "#define {:<40}{}", name, value).unwrap();
+}
+
+fn member_line(out: &mut String, code: &str, comment: &str) {
+    if comment.is_empty() {
+        writeln!(out, "{}", code).unwrap();
+    } else {
+        writeln!(out, "{:<48}/*!< {} */", code, comment).unwrap();
+    }
+}
+
+fn member(out: &mut String, m: &Member, indent: &str) {
+    let comment = match &m.description {
+        Some(d) => format!("(@ 0x{:08x}) {}", m.offset, one_line(d)),
+        None => format!("(@ 0x{:08x})", m.offset),
+    };
+    member_line(out, &format!("{}{}", indent, m.decl), &comment);
+}
+
+/// Write `RESERVED` members for `size` bytes starting at `offset`,
+/// with bytes up to the next word boundary, then words, then the remaining bytes
+fn padding(out: &mut String, reserved: &mut usize, offset: u64, size: u64, indent: &str) {
+    let head = ((4 - offset % 4) % 4).min(size);
+    let words = (size - head) / 4;
+    let tail = size - head - words * 4;
+    for (ty, count) in &[("uint8_t", head), ("uint32_t", words), ("uint8_t", tail)] {
+        if *count == 0 {
+            continue;
+        }
+        let name = if *reserved == 0 {
+            "RESERVED".to_string()
+        } else {
+            format!("RESERVED{}", reserved)
+        };
+        *reserved += 1;
+        if *count == 1 {
+            writeln!(out, "{}__IM  {} {};", indent, ty, name).unwrap();
+        } else {
+            writeln!(out, "{}__IM  {} {}[{}];", indent, ty, name, count).unwrap();
+        }
+    }
+}
+
+/// Base of the type and macro names of the peripheral
+fn type_base(p: &Peripheral) -> String {
+    match &p.header_struct_name {
+        Some(name) => name.clone(),
+        None => strip(&p.name),
+    }
+}
+
+/// Remove the `%s` placeholder from the name of an array
+fn strip(name: &str) -> String {
+    name.replace("[%s]", "").replace("%s", "")
+}
+
+fn instance(name: &str, index: &str) -> String {
+    name.replace("[%s]", index).replace("%s", index)
+}
+
+fn guard(name: &str) -> String {
+    format!("{}_H", name.to_uppercase())
+}
+
+/// Parse a CPU revision like `r1p2` into `0x0102`
+fn revision(revision: &str) -> u32 {
+    let revision = revision.to_lowercase();
+    let mut parts = revision.trim_start_matches('r').split('p');
+    let major = parts.next().and_then(|r| r.parse::<u32>().ok());
+    let minor = parts.next().and_then(|p| p.parse::<u32>().ok());
+    match (major, minor) {
+        (Some(major), Some(minor)) => (major << 8) | minor,
+        _ => 0,
+    }
+}
+
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
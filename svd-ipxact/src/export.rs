@@ -0,0 +1,621 @@
+use xmltree::{Element, EmitterConfig, XMLNode};
+
+use super::{diagnostic, Diagnostic, Error, Export, NAMESPACE};
+use crate::svd::{
+    checked_shr, Cluster, Device, DimElement, EnumeratedValues, Field, FieldInfo, Peripheral,
+    ReadAction, Register, RegisterCluster, RegisterInfo, RegisterProperties, Usage,
+    WriteConstraint,
+};
+
+struct Exporter<'a> {
+    device: &'a Device,
+    diagnostics: Vec<Diagnostic>,
+    address_unit_bits: u64,
+}
+
+pub(crate) fn export(device: &Device) -> Result<Export, Error> {
+    let mut exporter = Exporter {
+        device,
+        diagnostics: Vec::new(),
+        address_unit_bits: device.address_unit_bits.unwrap_or(8) as u64,
+    };
+    let mut root = Element::new("ipxact:component");
+    root.attributes
+        .insert("xmlns:ipxact".to_string(), NAMESPACE.to_string());
+    push(&mut root, "vendor", "svd");
+    push(&mut root, "library", "svd");
+    push(&mut root, "name", &device.name);
+    push(
+        &mut root,
+        "version",
+        device.version.as_deref().unwrap_or("1.0"),
+    );
+    if device.cpu.is_some() {
+        diagnostic(
+            &mut exporter.diagnostics,
+            &device.name,
+            "`cpu` is not exported",
+        );
+    }
+
+    let mut map = element("memoryMap");
+    push(&mut map, "name", &device.name);
+    for p in &device.peripherals {
+        exporter.peripheral(&mut map, p);
+    }
+    push(
+        &mut map,
+        "addressUnitBits",
+        &exporter.address_unit_bits.to_string(),
+    );
+    let mut maps = element("memoryMaps");
+    maps.children.push(XMLNode::Element(map));
+    root.children.push(XMLNode::Element(maps));
+    if let Some(description) = &device.description {
+        push(&mut root, "description", description);
+    }
+
+    let mut xml = Vec::new();
+    let mut config = EmitterConfig::new();
+    config.perform_indent = true;
+    config.pad_self_closing = false;
+    root.write_with_config(&mut xml, config)
+        .map_err(|e| Error::Xml(e.to_string()))?;
+    Ok(Export {
+        xml: String::from_utf8(xml).unwrap(),
+        diagnostics: exporter.diagnostics,
+    })
+}
+
+fn element(name: &str) -> Element {
+    Element::new(&format!("ipxact:{}", name))
+}
+
+fn push(parent: &mut Element, name: &str, text: &str) {
+    let mut e = element(name);
+    e.children.push(XMLNode::Text(text.to_string()));
+    parent.children.push(XMLNode::Element(e));
+}
+
+fn hex(value: u64) -> String {
+    format!("'h{:X}", value)
+}
+
+/// Names and offsets of the elements of an array, or of a single element
+fn instances(name: &str, offset: u64, dim: Option<&DimElement>) -> Vec<(String, u64)> {
+    match dim {
+        None => vec![(name.to_string(), offset)],
+        Some(dim) => dim
+            .indexes()
+            .enumerate()
+            .map(|(i, idx)| {
+                (
+                    name.replace("[%s]", &idx).replace("%s", &idx),
+                    offset + i as u64 * dim.dim_increment as u64,
+                )
+            })
+            .collect(),
+    }
+}
+
+/// Return `true` if the array can be exported with `dim`, which only has numeric indexes
+fn numeric(dim: &DimElement) -> bool {
+    dim.indexes()
+        .enumerate()
+        .all(|(i, idx)| idx.parse::<usize>().ok() == Some(i))
+}
+
+fn strip(name: &str) -> String {
+    name.replace("[%s]", "").replace("%s", "")
+}
+
+impl<'a> Exporter<'a> {
+    fn peripheral(&mut self, map: &mut Element, p: &'a Peripheral) {
+        if !p.interrupt.is_empty() {
+            diagnostic(
+                &mut self.diagnostics,
+                &p.name,
+                "interrupts are not exported",
+            );
+        }
+        let source = self.source(p);
+        let properties = source
+            .default_register_properties
+            .inherit(&self.device.default_register_properties);
+        let children = source.registers.as_deref().unwrap_or(&[]);
+        let range = match &p.address_block {
+            Some(blocks) if !blocks.is_empty() => blocks
+                .iter()
+                .map(|b| b.offset as u64 + b.size as u64)
+                .max()
+                .unwrap_or(0),
+            _ => self.extent(children, &properties),
+        };
+        let dim = match p {
+            Peripheral::Single(_) => None,
+            Peripheral::Array(_, dim) => Some(dim),
+        };
+        for (name, base_address) in instances(&p.name, p.base_address, dim) {
+            let mut block = element("addressBlock");
+            push(&mut block, "name", &name);
+            if let Some(description) = &p.description {
+                push(&mut block, "description", description);
+            }
+            push(&mut block, "baseAddress", &hex(base_address));
+            push(&mut block, "range", &hex(range));
+            push(
+                &mut block,
+                "width",
+                &properties.size.unwrap_or(32).to_string(),
+            );
+            push(&mut block, "usage", "register");
+            if let Some(access) = properties.access {
+                push(&mut block, "access", access.as_str());
+            }
+            self.children(&mut block, &name, children, &properties);
+            map.children.push(XMLNode::Element(block));
+        }
+    }
+
+    /// Peripheral defining the registers of `p`
+    fn source(&self, p: &'a Peripheral) -> &'a Peripheral {
+        let mut source = p;
+        for _ in 0..self.device.peripherals.len() {
+            if source.registers.is_some() {
+                break;
+            }
+            match source
+                .derived_from
+                .as_ref()
+                .and_then(|name| self.device.peripherals.iter().find(|p| &p.name == name))
+            {
+                Some(parent) => source = parent,
+                None => break,
+            }
+        }
+        source
+    }
+
+    /// Cluster defining the registers of `c`
+    fn cluster_source(&self, siblings: &'a [RegisterCluster], c: &'a Cluster) -> &'a Cluster {
+        let mut source = c;
+        // Limit the depth in case of circular references
+        for _ in 0..=siblings.len() {
+            if !source.children.is_empty() {
+                break;
+            }
+            match source
+                .derived_from
+                .as_ref()
+                .and_then(|path| self.find(siblings, path))
+            {
+                Some(RegisterCluster::Cluster(parent)) => source = parent,
+                _ => break,
+            }
+        }
+        source
+    }
+
+    /// Find the register or cluster of a `derivedFrom`, a name of one of the `siblings`
+    /// or a path starting with the name of a peripheral
+    fn find(&self, siblings: &'a [RegisterCluster], path: &str) -> Option<&'a RegisterCluster> {
+        let find = |children: &'a [RegisterCluster], name: &str| {
+            children.iter().find(|rc| match rc {
+                RegisterCluster::Register(r) => r.name == name,
+                RegisterCluster::Cluster(c) => c.name == name,
+            })
+        };
+        let mut names = path.split('.');
+        let first = names.next()?;
+        let peripheral = match self.device.peripherals.iter().find(|p| p.name == first) {
+            Some(p) if path.contains('.') => self.source(p),
+            _ => return find(siblings, path),
+        };
+        let mut children = peripheral.registers.as_deref()?;
+        let mut found = None;
+        for name in names {
+            let rc = find(children, name)?;
+            children = match rc {
+                RegisterCluster::Cluster(c) => &c.children,
+                RegisterCluster::Register(_) => &[],
+            };
+            found = Some(rc);
+        }
+        found
+    }
+
+    /// Size in address units of the registers and clusters
+    fn extent(&self, children: &'a [RegisterCluster], properties: &RegisterProperties) -> u64 {
+        children
+            .iter()
+            .map(|rc| match rc {
+                RegisterCluster::Register(r) => {
+                    let size = r.properties.inherit(properties).size.unwrap_or(32) as u64;
+                    let size = (size.max(1) - 1) / self.address_unit_bits + 1;
+                    let count = match r {
+                        Register::Single(_) => 0,
+                        Register::Array(_, dim) => (dim.dim as u64 - 1) * dim.dim_increment as u64,
+                    };
+                    r.address_offset as u64 + count + size
+                }
+                RegisterCluster::Cluster(c) => {
+                    let source = self.cluster_source(children, c);
+                    let properties = c
+                        .default_register_properties
+                        .inherit(&source.default_register_properties)
+                        .inherit(properties);
+                    let size = self.extent(&source.children, &properties);
+                    match c {
+                        Cluster::Single(_) => c.address_offset as u64 + size,
+                        Cluster::Array(_, dim) => {
+                            c.address_offset as u64 + dim.dim as u64 * dim.dim_increment as u64
+                        }
+                    }
+                }
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn children(
+        &mut self,
+        parent: &mut Element,
+        path: &str,
+        children: &'a [RegisterCluster],
+        properties: &RegisterProperties,
+    ) {
+        let registers: Vec<&Register> = children
+            .iter()
+            .filter_map(|rc| match rc {
+                RegisterCluster::Register(r) => Some(r),
+                _ => None,
+            })
+            .collect();
+        for rc in children {
+            match rc {
+                RegisterCluster::Register(r) => {
+                    if is_alternate(r) {
+                        if primary(&registers, r).is_none() {
+                            diagnostic(
+                                &mut self.diagnostics,
+                                &format!("{}.{}", path, r.name),
+                                "alternate register without a primary register is exported as a register",
+                            );
+                        } else {
+                            continue;
+                        }
+                    }
+                    let alternates: Vec<_> = registers
+                        .iter()
+                        .filter(|a| {
+                            is_alternate(a)
+                                && matches!(primary(&registers, a), Some(p) if std::ptr::eq(p, r))
+                        })
+                        .copied()
+                        .collect();
+                    self.register(parent, path, r, &alternates, properties);
+                }
+                RegisterCluster::Cluster(c) => self.cluster(parent, path, children, c, properties),
+            }
+        }
+    }
+
+    fn cluster(
+        &mut self,
+        parent: &mut Element,
+        path: &str,
+        siblings: &'a [RegisterCluster],
+        c: &'a Cluster,
+        properties: &RegisterProperties,
+    ) {
+        let source = self.cluster_source(siblings, c);
+        if c.derived_from.is_some() && source.children.is_empty() {
+            diagnostic(
+                &mut self.diagnostics,
+                &format!("{}.{}", path, c.name),
+                "`derivedFrom` is not resolved",
+            );
+        }
+        let properties = c
+            .default_register_properties
+            .inherit(&source.default_register_properties)
+            .inherit(properties);
+        let elements = match c {
+            Cluster::Array(_, dim) if numeric(dim) => {
+                vec![(strip(&c.name), c.address_offset as u64, Some(dim.dim))]
+            }
+            Cluster::Array(_, dim) => instances(&c.name, c.address_offset as u64, Some(dim))
+                .into_iter()
+                .map(|(name, offset)| (name, offset, None))
+                .collect(),
+            Cluster::Single(_) => vec![(c.name.clone(), c.address_offset as u64, None)],
+        };
+        let range = match c {
+            Cluster::Array(_, dim) => dim.dim_increment as u64,
+            Cluster::Single(_) => self.extent(&source.children, &properties),
+        };
+        for (name, offset, dim) in elements {
+            let cluster_path = format!("{}.{}", path, name);
+            let mut file = element("registerFile");
+            push(&mut file, "name", &name);
+            if let Some(description) = c.description.as_ref().or(source.description.as_ref()) {
+                push(&mut file, "description", description);
+            }
+            if let Some(dim) = dim {
+                push(&mut file, "dim", &dim.to_string());
+            }
+            push(&mut file, "addressOffset", &hex(offset));
+            push(&mut file, "range", &hex(range));
+            self.children(&mut file, &cluster_path, &source.children, &properties);
+            parent.children.push(XMLNode::Element(file));
+        }
+    }
+
+    fn register(
+        &mut self,
+        parent: &mut Element,
+        path: &str,
+        r: &Register,
+        alternates: &[&Register],
+        parent_properties: &RegisterProperties,
+    ) {
+        let properties = r.properties.inherit(parent_properties);
+        let size = properties.size.unwrap_or(32) as u64;
+        let elements = match r {
+            Register::Array(_, dim)
+                if numeric(dim) && dim.dim_increment as u64 * self.address_unit_bits == size =>
+            {
+                vec![(strip(&r.name), r.address_offset as u64, Some(dim.dim))]
+            }
+            Register::Array(_, dim) => instances(&r.name, r.address_offset as u64, Some(dim))
+                .into_iter()
+                .map(|(name, offset)| (name, offset, None))
+                .collect(),
+            Register::Single(_) => vec![(r.name.clone(), r.address_offset as u64, None)],
+        };
+        let register_path = format!("{}.{}", path, r.name);
+        if r.derived_from.is_some() {
+            diagnostic(
+                &mut self.diagnostics,
+                &register_path,
+                "`derivedFrom` is not resolved",
+            );
+        }
+        for (name, offset, dim) in elements {
+            let mut e = element("register");
+            push(&mut e, "name", &name);
+            if let Some(description) = &r.description {
+                push(&mut e, "description", description);
+            }
+            if let Some(dim) = dim {
+                push(&mut e, "dim", &dim.to_string());
+            }
+            push(&mut e, "addressOffset", &hex(offset));
+            push(&mut e, "size", &size.to_string());
+            if let Some(access) = properties.access {
+                push(&mut e, "access", access.as_str());
+            }
+            self.fields(&mut e, &register_path, r, &properties);
+            if !alternates.is_empty() {
+                let mut group = element("alternateRegisters");
+                for a in alternates {
+                    let alternate = self.alternate(path, size, a, parent_properties);
+                    group.children.push(XMLNode::Element(alternate));
+                }
+                e.children.push(XMLNode::Element(group));
+            }
+            parent.children.push(XMLNode::Element(e));
+        }
+    }
+
+    fn alternate(
+        &mut self,
+        path: &str,
+        size: u64,
+        a: &RegisterInfo,
+        parent_properties: &RegisterProperties,
+    ) -> Element {
+        let properties = a.properties.inherit(parent_properties);
+        let alternate_path = format!("{}.{}", path, a.name);
+        if properties.size.unwrap_or(32) as u64 != size {
+            diagnostic(
+                &mut self.diagnostics,
+                &alternate_path,
+                "size of the alternate register is not exported",
+            );
+        }
+        let mut e = element("alternateRegister");
+        push(&mut e, "name", &strip(&a.name));
+        if let Some(description) = &a.description {
+            push(&mut e, "description", description);
+        }
+        let mut groups = element("alternateGroups");
+        push(
+            &mut groups,
+            "alternateGroup",
+            a.alternate_group.as_deref().unwrap_or(&a.name),
+        );
+        e.children.push(XMLNode::Element(groups));
+        if let Some(access) = properties.access {
+            push(&mut e, "access", access.as_str());
+        }
+        self.fields(&mut e, &alternate_path, a, &properties);
+        e
+    }
+
+    fn fields(
+        &mut self,
+        parent: &mut Element,
+        path: &str,
+        r: &RegisterInfo,
+        properties: &RegisterProperties,
+    ) {
+        let size = properties.size.unwrap_or(32);
+        let fields = r.fields.as_deref().unwrap_or(&[]);
+        if fields.is_empty() {
+            let field = FieldInfo::builder()
+                .name(strip(&r.name))
+                .bit_offset(0)
+                .bit_width(size)
+                .build(crate::svd::ValidateLevel::Disabled)
+                .unwrap();
+            self.field(parent, path, &field, &field.name, 0, properties, r);
+            return;
+        }
+        for f in fields {
+            let elements = match f {
+                Field::Single(_) => vec![(f.name.clone(), f.bit_range.offset as u64)],
+                Field::Array(_, dim) => instances(&f.name, f.bit_range.offset as u64, Some(dim)),
+            };
+            for (name, offset) in elements {
+                self.field(parent, path, f, &name, offset as u32, properties, r);
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn field(
+        &mut self,
+        parent: &mut Element,
+        path: &str,
+        f: &FieldInfo,
+        name: &str,
+        offset: u32,
+        properties: &RegisterProperties,
+        r: &RegisterInfo,
+    ) {
+        let field_path = format!("{}.{}", path, name);
+        if f.derived_from.is_some() {
+            diagnostic(
+                &mut self.diagnostics,
+                &field_path,
+                "`derivedFrom` is not resolved",
+            );
+        }
+        let width = f.bit_range.width;
+        let mask = if width >= 64 {
+            u64::MAX
+        } else {
+            (1 << width) - 1
+        };
+        let mut e = element("field");
+        push(&mut e, "name", name);
+        if let Some(description) = &f.description {
+            push(&mut e, "description", description);
+        }
+        push(&mut e, "bitOffset", &offset.to_string());
+        if let Some(reset) = properties.reset_value {
+            let reset_mask = checked_shr(properties.reset_mask.unwrap_or(u64::MAX), offset) & mask;
+            if reset_mask != 0 {
+                let mut resets = element("resets");
+                let mut reset_element = element("reset");
+                push(
+                    &mut reset_element,
+                    "value",
+                    &hex(checked_shr(reset, offset) & mask),
+                );
+                if reset_mask != mask {
+                    push(&mut reset_element, "mask", &hex(reset_mask));
+                }
+                resets.children.push(XMLNode::Element(reset_element));
+                e.children.push(XMLNode::Element(resets));
+            }
+        }
+        push(&mut e, "bitWidth", &width.to_string());
+        if let Some(access) = f.access {
+            push(&mut e, "access", access.as_str());
+        }
+        self.enumerated_values(&mut e, &field_path, &f.enumerated_values);
+        if let Some(mwv) = f.modified_write_values.or(r.modified_write_values) {
+            push(&mut e, "modifiedWriteValue", mwv.as_str());
+        }
+        if let Some(constraint) = f.write_constraint.or(r.write_constraint) {
+            let mut c = element("writeValueConstraint");
+            match constraint {
+                WriteConstraint::WriteAsRead(b) => push(&mut c, "writeAsRead", &b.to_string()),
+                WriteConstraint::UseEnumeratedValues(b) => {
+                    push(&mut c, "useEnumeratedValues", &b.to_string())
+                }
+                WriteConstraint::Range(range) => {
+                    push(&mut c, "minimum", &hex(range.min));
+                    push(&mut c, "maximum", &hex(range.max));
+                }
+            }
+            e.children.push(XMLNode::Element(c));
+        }
+        match f.read_action.or(r.read_action) {
+            Some(ReadAction::ModifyExternal) => diagnostic(
+                &mut self.diagnostics,
+                &field_path,
+                "`readAction` `modifyExternal` is not supported",
+            ),
+            Some(action) => push(&mut e, "readAction", action.as_str()),
+            None => {}
+        }
+        parent.children.push(XMLNode::Element(e));
+    }
+
+    fn enumerated_values(&mut self, parent: &mut Element, path: &str, values: &[EnumeratedValues]) {
+        let mut container = element("enumeratedValues");
+        for e in values {
+            if e.derived_from.is_some() {
+                diagnostic(
+                    &mut self.diagnostics,
+                    path,
+                    "`derivedFrom` of enumerated values is not resolved",
+                );
+            }
+            if let Some(name) = &e.name {
+                diagnostic(
+                    &mut self.diagnostics,
+                    path,
+                    format!("name of enumerated values `{}` is not exported", name),
+                );
+            }
+            for v in &e.values {
+                let value = match v.value {
+                    Some(value) => value,
+                    None => {
+                        diagnostic(
+                            &mut self.diagnostics,
+                            &format!("{}.{}", path, v.name),
+                            "`isDefault` enumerated value is not exported",
+                        );
+                        continue;
+                    }
+                };
+                let mut ev = element("enumeratedValue");
+                if e.usage() != Usage::ReadWrite {
+                    ev.attributes
+                        .insert("usage".to_string(), e.usage().as_str().to_string());
+                }
+                push(&mut ev, "name", &v.name);
+                if let Some(description) = &v.description {
+                    push(&mut ev, "description", description);
+                }
+                push(&mut ev, "value", &hex(value));
+                container.children.push(XMLNode::Element(ev));
+            }
+        }
+        if !container.children.is_empty() {
+            parent.children.push(XMLNode::Element(container));
+        }
+    }
+}
+
+fn is_alternate(r: &Register) -> bool {
+    r.alternate_register.is_some() || r.alternate_group.is_some()
+}
+
+/// Register which an alternate register is nested in: the register named by
+/// `alternateRegister`, or the register at the same offset which is not an alternate
+fn primary<'r>(registers: &[&'r Register], a: &Register) -> Option<&'r Register> {
+    registers
+        .iter()
+        .find(|r| match &a.alternate_register {
+            Some(name) => &r.name == name,
+            None => r.address_offset == a.address_offset && !is_alternate(r),
+        })
+        .copied()
+        .filter(|r| !is_alternate(r))
+}
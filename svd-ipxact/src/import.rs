@@ -0,0 +1,532 @@
+use roxmltree::{Document, Node};
+
+use super::{diagnostic, Diagnostic, Error, Import};
+use crate::svd::{
+    checked_shl, Access, AddressBlock, AddressBlockUsage, ClusterInfo, Device, DimElement,
+    EnumeratedValue, EnumeratedValues, Field, FieldInfo, ModifiedWriteValues, Peripheral,
+    PeripheralInfo, ReadAction, RegisterCluster, RegisterInfo, RegisterProperties, SvdError, Usage,
+    ValidateLevel, WriteConstraint, WriteConstraintRange,
+};
+
+struct Importer {
+    diagnostics: Vec<Diagnostic>,
+    address_unit_bits: u32,
+}
+
+/// Reason for not importing an element
+enum Skip {
+    /// The conversion fails
+    Error(Error),
+    /// A value of the element at the path refers to parameters
+    Parameterized(String, String),
+}
+
+impl From<Error> for Skip {
+    fn from(e: Error) -> Self {
+        Self::Error(e)
+    }
+}
+
+impl From<SvdError> for Skip {
+    fn from(e: SvdError) -> Self {
+        Self::Error(e.into())
+    }
+}
+
+pub(crate) fn import(xml: &str) -> Result<Import, Error> {
+    let doc = Document::parse(xml).map_err(|e| Error::Xml(e.to_string()))?;
+    let root = doc.root_element();
+    if root.tag_name().name() != "component" {
+        return Err(Error::NotComponent(root.tag_name().name().to_string()));
+    }
+    let name = required_text(root, "name", "component")?;
+    let mut importer = Importer {
+        diagnostics: Vec::new(),
+        address_unit_bits: 8,
+    };
+    let mut peripherals = Vec::new();
+    let mut address_unit_bits = None;
+    for map in child(root, "memoryMaps")
+        .into_iter()
+        .flat_map(|maps| children(maps, "memoryMap"))
+    {
+        let map_name = text(map, "name").unwrap_or_default();
+        let bits = integer(map, "addressUnitBits", &map_name);
+        if let Some(Some(bits)) = importer.element(bits)? {
+            importer.address_unit_bits = bits as u32;
+            address_unit_bits = Some(bits as u32);
+        }
+        for n in map.children().filter(Node::is_element) {
+            match n.tag_name().name() {
+                "addressBlock" => {
+                    let block = importer.block(n);
+                    peripherals.extend(importer.element(block)?);
+                }
+                "bank" | "subspaceMap" => diagnostic(
+                    &mut importer.diagnostics,
+                    &text(n, "name").unwrap_or_default(),
+                    format!("`{}` is not supported", n.tag_name().name()),
+                ),
+                _ => {}
+            }
+        }
+    }
+    let device = Device::builder()
+        .name(name)
+        .version(text(root, "version"))
+        .description(text(root, "description"))
+        .address_unit_bits(address_unit_bits)
+        .peripherals(peripherals)
+        .build(ValidateLevel::Weak)?;
+    Ok(Import {
+        device,
+        diagnostics: importer.diagnostics,
+    })
+}
+
+impl Importer {
+    /// Get the imported element, or `None` with a diagnostic if it is skipped
+    fn element<T>(&mut self, result: Result<T, Skip>) -> Result<Option<T>, Error> {
+        match result {
+            Ok(element) => Ok(Some(element)),
+            Err(Skip::Error(e)) => Err(e),
+            Err(Skip::Parameterized(path, value)) => {
+                diagnostic(
+                    &mut self.diagnostics,
+                    &path,
+                    format!("parameterized value `{}` is not supported", value),
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    fn block(&mut self, n: Node) -> Result<Peripheral, Skip> {
+        let name = required_text(n, "name", "addressBlock")?;
+        let base_address = required_integer(n, "baseAddress", &name)?;
+        let range = required_u32(n, "range", &name)?;
+        let usage = match text(n, "usage").as_deref() {
+            None | Some("register") => AddressBlockUsage::Registers,
+            Some("memory") => AddressBlockUsage::Buffer,
+            Some("reserved") => AddressBlockUsage::Reserved,
+            Some(other) => return Err(Error::InvalidValue(name, other.to_string()).into()),
+        };
+        let properties = RegisterProperties::new().access(self.access(n, &name)?);
+        let mut registers = Vec::new();
+        self.children(n, &name, &mut registers)?;
+        let block = AddressBlock::builder()
+            .offset(0)
+            .size(range)
+            .usage(usage)
+            .build(ValidateLevel::Weak)?;
+        Ok(PeripheralInfo::builder()
+            .name(name)
+            .description(text(n, "description"))
+            .base_address(base_address)
+            .default_register_properties(properties)
+            .address_block(Some(vec![block]))
+            .registers(if registers.is_empty() {
+                None
+            } else {
+                Some(registers)
+            })
+            .build(ValidateLevel::Weak)?
+            .single())
+    }
+
+    fn children(
+        &mut self,
+        n: Node,
+        path: &str,
+        children: &mut Vec<RegisterCluster>,
+    ) -> Result<(), Error> {
+        for c in n.children().filter(Node::is_element) {
+            match c.tag_name().name() {
+                "register" => {
+                    let registers = self.register(c, path);
+                    children.extend(self.element(registers)?.into_iter().flatten());
+                }
+                "registerFile" => {
+                    let file = self.register_file(c, path);
+                    children.extend(self.element(file)?);
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the array of an element with `dim` children and a stride of `increment`
+    fn dim(&mut self, n: Node, path: &str, increment: u32) -> Result<Option<DimElement>, Skip> {
+        let mut dim: u32 = 1;
+        let mut count = 0;
+        for d in children(n, "dim") {
+            let text = d.text().unwrap_or_default();
+            dim = match dim.checked_mul(to_u32(value(text, path)?, path, text)?) {
+                Some(dim) => dim,
+                None => {
+                    return Err(Error::InvalidValue(path.to_string(), text.trim().into()).into())
+                }
+            };
+            count += 1;
+        }
+        if count == 0 {
+            return Ok(None);
+        }
+        if count > 1 {
+            diagnostic(
+                &mut self.diagnostics,
+                path,
+                "multidimensional array is flattened",
+            );
+        }
+        Ok(Some(
+            DimElement::builder()
+                .dim(dim)
+                .dim_increment(increment)
+                .build(ValidateLevel::Weak)?,
+        ))
+    }
+
+    fn register_file(&mut self, n: Node, parent: &str) -> Result<RegisterCluster, Skip> {
+        let name = required_text(n, "name", parent)?;
+        let path = format!("{}.{}", parent, name);
+        let offset = required_u32(n, "addressOffset", &path)?;
+        let range = required_u32(n, "range", &path)?;
+        let dim = self.dim(n, &path, range)?;
+        let mut children = Vec::new();
+        self.children(n, &path, &mut children)?;
+        let info = ClusterInfo::builder()
+            .name(if dim.is_some() {
+                format!("{}[%s]", name)
+            } else {
+                name
+            })
+            .description(text(n, "description"))
+            .address_offset(offset)
+            .children(children)
+            .build(ValidateLevel::Weak)?;
+        Ok(RegisterCluster::Cluster(match dim {
+            Some(dim) => info.array(dim),
+            None => info.single(),
+        }))
+    }
+
+    /// Register with its alternate registers
+    fn register(&mut self, n: Node, parent: &str) -> Result<Vec<RegisterCluster>, Skip> {
+        let name = required_text(n, "name", parent)?;
+        let path = format!("{}.{}", parent, name);
+        let offset = required_u32(n, "addressOffset", &path)?;
+        let size = required_u32(n, "size", &path)?;
+        let dim = self.dim(n, &path, size / self.address_unit_bits.max(1))?;
+        let mut register = self.register_info(n, &name, &path, offset, size)?;
+        if dim.is_some() {
+            register.name.push_str("[%s]");
+        }
+        let alternates: Vec<_> = child(n, "alternateRegisters")
+            .into_iter()
+            .flat_map(|a| children(a, "alternateRegister"))
+            .collect();
+        let mut registers = vec![RegisterCluster::Register(match dim.clone() {
+            Some(dim) => register.array(dim),
+            None => register.single(),
+        })];
+        for a in alternates {
+            let alternate_name = required_text(a, "name", &path)?;
+            let alternate_path = format!("{}.{}", parent, alternate_name);
+            let group = child(a, "alternateGroups")
+                .and_then(|groups| text(groups, "alternateGroup"))
+                .unwrap_or_else(|| name.clone());
+            let alternate = self.register_info(a, &alternate_name, &alternate_path, offset, size);
+            let mut alternate = match self.element(alternate)? {
+                Some(alternate) => alternate,
+                None => continue,
+            };
+            alternate.alternate_group = Some(group);
+            registers.push(RegisterCluster::Register(match dim.clone() {
+                Some(dim) => {
+                    alternate.name.push_str("[%s]");
+                    alternate.array(dim)
+                }
+                None => alternate.single(),
+            }));
+        }
+        Ok(registers)
+    }
+
+    /// Register or alternate register, with the reset value of the fields
+    fn register_info(
+        &mut self,
+        n: Node,
+        name: &str,
+        path: &str,
+        offset: u32,
+        size: u32,
+    ) -> Result<RegisterInfo, Skip> {
+        let mut fields = Vec::new();
+        let mut reset = None;
+        let mut reset_mask = 0;
+        for f in children(n, "field") {
+            let field = self.field(f, path);
+            let (field, field_reset) = match self.element(field)? {
+                Some(field) => field,
+                None => continue,
+            };
+            if let Some((value, mask)) = field_reset {
+                let shift = field.bit_range.offset;
+                if shift >= 64 {
+                    diagnostic(
+                        &mut self.diagnostics,
+                        &format!("{}.{}", path, field.name),
+                        "reset value above bit 63 is not imported",
+                    );
+                } else {
+                    reset = Some(reset.unwrap_or(0) | checked_shl(value & mask, shift));
+                    reset_mask |= checked_shl(mask, shift);
+                }
+            }
+            fields.push(field);
+        }
+        Ok(RegisterInfo::builder()
+            .name(name.to_string())
+            .description(text(n, "description"))
+            .address_offset(offset)
+            .size(Some(size))
+            .access(self.access(n, path)?)
+            .reset_value(reset)
+            .reset_mask(reset.map(|_| reset_mask))
+            .fields(if fields.is_empty() {
+                None
+            } else {
+                Some(fields)
+            })
+            .build(ValidateLevel::Weak)?)
+    }
+
+    /// Field with its reset value and mask, shifted to bit 0
+    fn field(&mut self, n: Node, parent: &str) -> Result<(Field, Option<(u64, u64)>), Skip> {
+        let name = required_text(n, "name", parent)?;
+        let path = format!("{}.{}", parent, name);
+        let offset = required_u32(n, "bitOffset", &path)?;
+        let width = required_u32(n, "bitWidth", &path)?;
+        let width_mask = if width >= 64 {
+            u64::MAX
+        } else {
+            (1 << width) - 1
+        };
+
+        let mut reset = None;
+        for r in child(n, "resets")
+            .into_iter()
+            .flat_map(|resets| children(resets, "reset"))
+        {
+            match r.attribute("resetTypeRef") {
+                None | Some("HARD") => {
+                    let value = required_integer(r, "value", &path)?;
+                    let mask = integer(r, "mask", &path)?.unwrap_or(width_mask);
+                    reset = Some((value, mask & width_mask));
+                }
+                Some(other) => diagnostic(
+                    &mut self.diagnostics,
+                    &path,
+                    format!("reset of type `{}` is not supported", other),
+                ),
+            }
+        }
+
+        let modified_write_values = match text(n, "modifiedWriteValue") {
+            Some(v) => Some(
+                ModifiedWriteValues::parse_str(&v).ok_or(Error::InvalidValue(path.clone(), v))?,
+            ),
+            None => None,
+        };
+        let read_action = match text(n, "readAction") {
+            Some(v) => Some(ReadAction::parse_str(&v).ok_or(Error::InvalidValue(path.clone(), v))?),
+            None => None,
+        };
+        let write_constraint = match child(n, "writeValueConstraint") {
+            Some(c) => self.write_constraint(c, &path)?,
+            None => None,
+        };
+        if text(n, "reserved").as_deref() == Some("true") {
+            diagnostic(&mut self.diagnostics, &path, "`reserved` is not supported");
+        }
+
+        let field = FieldInfo::builder()
+            .name(name)
+            .description(text(n, "description"))
+            .bit_offset(offset)
+            .bit_width(width)
+            .access(self.access(n, &path)?)
+            .modified_write_values(modified_write_values)
+            .read_action(read_action)
+            .write_constraint(write_constraint)
+            .enumerated_values(self.enumerated_values(n, &path)?)
+            .build(ValidateLevel::Weak)?
+            .single();
+        Ok((field, reset))
+    }
+
+    fn write_constraint(&mut self, n: Node, path: &str) -> Result<Option<WriteConstraint>, Skip> {
+        if text(n, "writeAsRead").as_deref() == Some("true") {
+            return Ok(Some(WriteConstraint::WriteAsRead(true)));
+        }
+        if text(n, "useEnumeratedValues").as_deref() == Some("true") {
+            return Ok(Some(WriteConstraint::UseEnumeratedValues(true)));
+        }
+        match (integer(n, "minimum", path)?, integer(n, "maximum", path)?) {
+            (Some(min), Some(max)) => Ok(Some(WriteConstraint::Range(WriteConstraintRange {
+                min,
+                max,
+            }))),
+            _ => Ok(None),
+        }
+    }
+
+    /// Enumerated values, grouped in a container per `usage`
+    fn enumerated_values(&mut self, n: Node, path: &str) -> Result<Vec<EnumeratedValues>, Skip> {
+        let mut groups: Vec<(Usage, Vec<EnumeratedValue>)> = Vec::new();
+        for v in child(n, "enumeratedValues")
+            .into_iter()
+            .flat_map(|values| children(values, "enumeratedValue"))
+        {
+            let usage = match v.attribute("usage") {
+                Some(u) => Usage::parse_str(u)
+                    .ok_or_else(|| Error::InvalidValue(path.to_string(), u.to_string()))?,
+                None => Usage::ReadWrite,
+            };
+            let name = required_text(v, "name", path)?;
+            let number = required_integer(v, "value", &format!("{}.{}", path, name));
+            let number = match self.element(number)? {
+                Some(number) => number,
+                None => continue,
+            };
+            let value = EnumeratedValue::builder()
+                .description(text(v, "description"))
+                .value(Some(number))
+                .name(name)
+                .build(ValidateLevel::Weak)?;
+            match groups.iter_mut().find(|(u, _)| *u == usage) {
+                Some((_, values)) => values.push(value),
+                None => groups.push((usage, vec![value])),
+            }
+        }
+        groups
+            .into_iter()
+            .map(|(usage, values)| {
+                Ok(EnumeratedValues::builder()
+                    .usage(if usage == Usage::ReadWrite {
+                        None
+                    } else {
+                        Some(usage)
+                    })
+                    .values(values)
+                    .build(ValidateLevel::Weak)?)
+            })
+            .collect()
+    }
+
+    fn access(&mut self, n: Node, path: &str) -> Result<Option<Access>, Error> {
+        match text(n, "access") {
+            Some(a) => Ok(Some(
+                Access::parse_str(&a).ok_or_else(|| Error::InvalidValue(path.to_string(), a))?,
+            )),
+            None => Ok(None),
+        }
+    }
+}
+
+fn child<'a, 'input>(n: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    n.children()
+        .find(|c| c.is_element() && c.tag_name().name() == name)
+}
+
+fn children<'a, 'input: 'a>(
+    n: Node<'a, 'input>,
+    name: &'a str,
+) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    n.children()
+        .filter(move |c| c.is_element() && c.tag_name().name() == name)
+}
+
+fn text(n: Node, name: &str) -> Option<String> {
+    child(n, name)
+        .and_then(|c| c.text())
+        .map(|t| t.trim().to_string())
+}
+
+fn required_text(n: Node, name: &str, path: &str) -> Result<String, Error> {
+    text(n, name).ok_or_else(|| Error::MissingElement(path.to_string(), name.to_string()))
+}
+
+fn integer(n: Node, name: &str, path: &str) -> Result<Option<u64>, Skip> {
+    match text(n, name) {
+        Some(t) => value(&t, path).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn required_integer(n: Node, name: &str, path: &str) -> Result<u64, Skip> {
+    integer(n, name, path)?
+        .ok_or_else(|| Error::MissingElement(path.to_string(), name.to_string()).into())
+}
+
+/// Get a required integer which fits in 32 bits
+fn required_u32(n: Node, name: &str, path: &str) -> Result<u32, Skip> {
+    let value = required_integer(n, name, path)?;
+    to_u32(value, path, &text(n, name).unwrap_or_default())
+}
+
+/// Check that `value`, parsed from `text`, fits in 32 bits
+fn to_u32(value: u64, path: &str, text: &str) -> Result<u32, Skip> {
+    if value > u32::MAX as u64 {
+        Err(Error::InvalidValue(path.to_string(), text.trim().to_string()).into())
+    } else {
+        Ok(value as u32)
+    }
+}
+
+/// Parse the value of the element at `path`, which is skipped if the value is an expression
+fn value(text: &str, path: &str) -> Result<u64, Skip> {
+    match parse_integer(text) {
+        Some(value) => Ok(value),
+        None if is_expression(text) => Err(Skip::Parameterized(
+            path.to_string(),
+            text.trim().to_string(),
+        )),
+        None => Err(Error::InvalidValue(path.to_string(), text.trim().to_string()).into()),
+    }
+}
+
+/// Return `true` if `text` is not a literal, like a parameter name or an operation
+fn is_expression(text: &str) -> bool {
+    let text = text.trim();
+    text.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '$' || c == '(')
+        || text.contains(|c: char| "+-*/%()?:<>&|~^ ".contains(c))
+}
+
+/// Parse an integer literal: decimal, `0x` hexadecimal, or a SystemVerilog
+/// literal like `'h1F` or `8'b1010`. Expressions are not supported.
+fn parse_integer(text: &str) -> Option<u64> {
+    let text = text.trim().replace('_', "");
+    if let Some(i) = text.find('\'') {
+        // The optional width in bits
+        if !text[..i].chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let literal = &text[i + 1..];
+        let literal = literal.trim_start_matches(&['s', 'S'][..]);
+        let radix = match literal.chars().next()? {
+            'h' | 'H' => 16,
+            'd' | 'D' => 10,
+            'o' | 'O' => 8,
+            'b' | 'B' => 2,
+            _ => return None,
+        };
+        return u64::from_str_radix(&literal[1..], radix).ok();
+    }
+    if text.starts_with("0x") || text.starts_with("0X") {
+        return u64::from_str_radix(&text[2..], 16).ok();
+    }
+    text.parse().ok()
+}
@@ -0,0 +1,112 @@
+//! IP-XACT conversion.
+//! This converts between the memory maps of IP-XACT (IEEE 1685-2014) components
+//! and [`Device`]s:
+//!
+//! | IP-XACT            | SVD                                   |
+//! |--------------------|---------------------------------------|
+//! | `component`        | device                                |
+//! | `addressBlock`     | peripheral, with an `addressBlock`    |
+//! | `registerFile`     | cluster                               |
+//! | `register`         | register                              |
+//! | `alternateRegister`| register with `alternateGroup`        |
+//! | `field`            | field                                 |
+//! | `enumeratedValue`  | enumerated value, in a container per `usage` |
+//!
+//! `access`, `modifiedWriteValue`, `readAction` and `writeValueConstraint` use the same
+//! values in both formats, except for the `modifyExternal` read action of SVD which
+//! has no equivalent in IP-XACT. IP-XACT gives reset values per field, they are combined
+//! into the `resetValue` and `resetMask` of the register.
+//!
+//! Constructs which can't be represented in the other format are left out and
+//! reported as [`Diagnostic`]s, like the `cpu` and interrupts of a device or the
+//! `modifyExternal` read action on export, or banks on import. Elements with a
+//! parameterized value, like a parameter name or an expression in `addressOffset`,
+//! are left out on import.
+
+use svd_rs as svd;
+
+use crate::svd::{Device, SvdError};
+
+mod export;
+mod import;
+
+/// Namespace of IP-XACT 1685-2014
+pub const NAMESPACE: &str = "http://www.accellera.org/XMLSchema/IPXACT/1685-2014";
+
+/// Errors of the conversion
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum Error {
+    /// The document is not well-formed XML
+    #[error("XML error: {0}")]
+    Xml(String),
+    /// The root element is not a `component`
+    #[error("Expected a `component`, found `{0}`")]
+    NotComponent(String),
+    /// A required element is missing
+    #[error("`{0}` has no `{1}`")]
+    MissingElement(String, String),
+    /// A value can't be parsed
+    #[error("Invalid value `{1}` in `{0}`")]
+    InvalidValue(String, String),
+    /// The imported device is not valid
+    #[error("Invalid device: {0}")]
+    Svd(#[from] SvdError),
+}
+
+/// A construct which was not converted
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Path of the element, like `UART0.CR.EN`
+    pub path: String,
+    /// What was left out
+    pub message: String,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Result of [`import`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Import {
+    /// The imported device
+    pub device: Device,
+    /// Constructs of the component which were not imported
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Result of [`export`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Export {
+    /// The IP-XACT component
+    pub xml: String,
+    /// Constructs of the device which were not exported
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Import the memory maps of an IP-XACT component as a device.
+///
+/// All address blocks of all memory maps become peripherals of the device.
+pub fn import(xml: &str) -> Result<Import, Error> {
+    import::import(xml)
+}
+
+/// Export the device as an IP-XACT component with a single memory map.
+///
+/// The vendor and library of the component are `svd`, its version is the version
+/// of the device or `1.0`. Peripheral arrays and derived peripherals are expanded
+/// to one address block per instance, and derived clusters get the registers of
+/// their source. Registers without fields get a field covering the register, as
+/// IP-XACT requires one.
+pub fn export(device: &Device) -> Result<Export, Error> {
+    export::export(device)
+}
+
+fn diagnostic(diagnostics: &mut Vec<Diagnostic>, path: &str, message: impl Into<String>) {
+    diagnostics.push(Diagnostic {
+        path: path.to_string(),
+        message: message.into(),
+    });
+}
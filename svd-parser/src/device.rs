@@ -14,25 +14,39 @@ impl Parse for Device {
             return Err(SVDError::NotExpectedTag("device".to_string()).at(tree.id()));
         }
 
-        Device::builder()
-            .name(tree.get_child_text("name")?)
-            .version(tree.get_child_text_opt("version")?)
-            .description(tree.get_child_text_opt("description")?)
-            .cpu(optional::<Cpu>("cpu", tree, config)?)
-            .address_unit_bits(optional::<u32>("addressUnitBits", tree, &())?)
-            .width(optional::<u32>("width", tree, &())?)
-            .default_register_properties(RegisterProperties::parse(tree, config)?)
-            .peripherals({
-                let ps: Result<Vec<_>, _> = tree
-                    .get_child_elem("peripherals")?
+        parse_device(
+            tree,
+            || {
+                tree.get_child_elem("peripherals")?
                     .children()
                     .filter(Node::is_element)
                     .map(|t| Peripheral::parse(&t, config))
-                    .collect();
-                ps?
-            })
-            .schema_version(tree.attribute("schemaVersion").map(|s| s.to_string()))
-            .build(config.validate_level)
-            .map_err(|e| SVDError::from(e).at(tree.id()))
+                    .collect()
+            },
+            config,
+        )
     }
 }
+
+/// Parse the device element, getting its peripherals from `peripherals`
+pub(crate) fn parse_device<F>(
+    tree: &Node,
+    peripherals: F,
+    config: &Config,
+) -> Result<Device, SVDErrorAt>
+where
+    F: FnOnce() -> Result<Vec<Peripheral>, SVDErrorAt>,
+{
+    Device::builder()
+        .name(tree.get_child_text("name")?)
+        .version(tree.get_child_text_opt("version")?)
+        .description(tree.get_child_text_opt("description")?)
+        .cpu(optional::<Cpu>("cpu", tree, config)?)
+        .address_unit_bits(optional::<u32>("addressUnitBits", tree, &())?)
+        .width(optional::<u32>("width", tree, &())?)
+        .default_register_properties(RegisterProperties::parse(tree, config)?)
+        .peripherals(peripherals()?)
+        .schema_version(tree.attribute("schemaVersion").map(|s| s.to_string()))
+        .build(config.validate_level)
+        .map_err(|e| SVDError::from(e).at(tree.id()))
+}
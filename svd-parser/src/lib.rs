@@ -88,53 +88,119 @@ pub fn parse(xml: &str) -> anyhow::Result<Device> {
 }
 /// Parses the contents of an SVD (XML) string
 pub fn parse_with_config(xml: &str, config: &Config) -> anyhow::Result<Device> {
-    fn get_name<'a>(node: &'a Node) -> Option<&'a str> {
-        node.children()
-            .find(|t| t.has_tag_name("name"))
-            .and_then(|t| t.text())
-    }
-
     let xml = trim_utf8_bom(xml);
     let tree = Document::parse(xml)?;
     let root = tree.root();
     let device = root
         .get_child("device")
         .ok_or_else(|| SVDError::MissingTag("device".to_string()).at(root.id()))?;
-    match Device::parse(&device, config) {
-        Ok(o) => Ok(o),
-        Err(e) => {
-            let id = e.id;
-            let node = tree.get_node(id).unwrap();
-            let pos = tree.text_pos_at(node.range().start);
-            let tagname = node.tag_name().name();
-            let mut res = Err(e.into());
-            if tagname.is_empty() {
-                res = res.with_context(|| format!("at {}", pos))
-            } else if let Some(name) = get_name(&node) {
-                res = res.with_context(|| format!("Parsing {} `{}` at {}", tagname, name, pos))
-            } else {
-                res = res.with_context(|| format!("Parsing unknown {} at {}", tagname, pos))
-            }
-            for parent in node.ancestors().skip(1) {
-                if parent.id() == NodeId::new(0) {
-                    break;
-                }
-                let tagname = parent.tag_name().name();
-                match tagname {
-                    "device" | "peripheral" | "register" | "field" | "enumeratedValue"
-                    | "interrupt" => {
-                        if let Some(name) = get_name(&parent) {
-                            res = res.with_context(|| format!("In {} `{}`", tagname, name));
-                        } else {
-                            res = res.with_context(|| format!("In unknown {}", tagname));
-                        }
-                    }
-                    _ => {}
+    Device::parse(&device, config).map_err(|e| error_with_context(&tree, e))
+}
+
+/// Parses a device split into a manifest and one file per peripheral.
+///
+/// The manifest is a device description, where each peripheral inside `<peripherals>`
+/// is replaced with `<include file="..."/>`. `load` is called with the value of
+/// the `file` attribute and must return the content of that file, which has
+/// a single `<peripheral>` as root element. Peripherals keep the order of includes.
+///
+/// ``` no_run
+/// let manifest = std::fs::read_to_string("device/device.svd").unwrap();
+/// let device = svd_parser::parse_split(&manifest, |file| {
+///     Ok(std::fs::read_to_string(std::path::Path::new("device").join(file))?)
+/// });
+/// ```
+pub fn parse_split<F>(manifest: &str, load: F) -> anyhow::Result<Device>
+where
+    F: FnMut(&str) -> anyhow::Result<String>,
+{
+    parse_split_with_config(manifest, load, &Config::default())
+}
+
+/// Parses a device split into a manifest and one file per peripheral. See [`parse_split`].
+pub fn parse_split_with_config<F>(
+    manifest: &str,
+    mut load: F,
+    config: &Config,
+) -> anyhow::Result<Device>
+where
+    F: FnMut(&str) -> anyhow::Result<String>,
+{
+    use crate::svd::Peripheral;
+
+    let manifest = trim_utf8_bom(manifest);
+    let tree = Document::parse(manifest)?;
+    let root = tree.root();
+    let device = root
+        .get_child("device")
+        .ok_or_else(|| SVDError::MissingTag("device".to_string()).at(root.id()))?;
+    let includes = device
+        .get_child_elem("peripherals")
+        .map_err(|e| error_with_context(&tree, e))?;
+
+    let mut peripherals = Vec::new();
+    for include in includes.children().filter(Node::is_element) {
+        if !include.has_tag_name("include") {
+            let e = SVDError::NotExpectedTag("include".to_string()).at(include.id());
+            return Err(error_with_context(&tree, e));
+        }
+        let file = include.attribute("file").ok_or_else(|| {
+            let e = SVDError::MissingAttribute("file".to_string()).at(include.id());
+            error_with_context(&tree, e)
+        })?;
+        let xml = load(file).with_context(|| format!("Loading `{}`", file))?;
+        let xml = trim_utf8_bom(&xml);
+        let ptree = Document::parse(xml).with_context(|| format!("In file `{}`", file))?;
+        let peripheral = match ptree.root().first_element_child() {
+            Some(p) => Peripheral::parse(&p, config).map_err(|e| error_with_context(&ptree, e)),
+            None => Err(SVDError::MissingTag("peripheral".to_string())
+                .at(ptree.root().id())
+                .into()),
+        };
+        peripherals.push(peripheral.with_context(|| format!("In file `{}`", file))?);
+    }
+
+    device::parse_device(&device, || Ok(peripherals), config)
+        .map_err(|e| error_with_context(&tree, e))
+}
+
+/// Add the position and the enclosing elements of the failed node to the error
+fn error_with_context(tree: &Document, e: SVDErrorAt) -> anyhow::Error {
+    fn get_name<'a>(node: &'a Node) -> Option<&'a str> {
+        node.children()
+            .find(|t| t.has_tag_name("name"))
+            .and_then(|t| t.text())
+    }
+
+    let id = e.id;
+    let node = tree.get_node(id).unwrap();
+    let pos = tree.text_pos_at(node.range().start);
+    let tagname = node.tag_name().name();
+    let mut res = anyhow::Error::from(e);
+    if tagname.is_empty() {
+        res = res.context(format!("at {}", pos))
+    } else if let Some(name) = get_name(&node) {
+        res = res.context(format!("Parsing {} `{}` at {}", tagname, name, pos))
+    } else {
+        res = res.context(format!("Parsing unknown {} at {}", tagname, pos))
+    }
+    for parent in node.ancestors().skip(1) {
+        if parent.id() == NodeId::new(0) {
+            break;
+        }
+        let tagname = parent.tag_name().name();
+        match tagname {
+            "device" | "peripheral" | "register" | "field" | "enumeratedValue" | "interrupt" => {
+                if let Some(name) = get_name(&parent) {
+                    res = res.context(format!("In {} `{}`", tagname, name));
+                } else {
+                    res = res.context(format!("In unknown {}", tagname));
                 }
             }
-            res
+            _ => {}
         }
     }
+    res
 }
 
 /// Return the &str trimmed UTF-8 BOM if the input &str contains the BOM.
@@ -179,6 +245,8 @@ pub enum SVDError {
     Svd(#[from] svd::SvdError),
     #[error("Expected a <{0}> tag, found none")]
     MissingTag(String),
+    #[error("Expected a `{0}` attribute, found none")]
+    MissingAttribute(String),
     #[error("Expected content in <{0}> tag, found none")]
     EmptyTag(String),
     #[error("Failed to parse `{0}`")]
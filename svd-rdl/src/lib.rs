@@ -0,0 +1,615 @@
+//! SystemRDL exporter.
+//! This writes a device as a SystemRDL 2.0 description:
+//!
+//! - an `addrmap` type for each peripheral with registers, shared by the peripherals
+//!   derived from it,
+//! - a root `addrmap` named after the device, instantiating the peripherals at their
+//!   base addresses,
+//! - a `regfile` for each cluster and a `reg` for each register, with `regwidth`
+//!   from the register `size`; clusters and registers derived from another one
+//!   get its registers or fields,
+//! - a `field` for each field, with the reset value from the `resetValue` and
+//!   `resetMask` of its register,
+//! - an `enum` for the enumerated values of a field, used as its `encode`.
+//!
+//! `sw` is derived from `access`, `onwrite` from `modifiedWriteValues` and `onread`
+//! from `readAction`. SVD doesn't describe the hardware access, so `hw` is `rw` for
+//! fields with side effects on read or write, `w` for the other read-only fields and
+//! `r` otherwise.
+//!
+//! Arrays with the indexes `0..dim` become SystemRDL arrays, other arrays are
+//! instantiated once per element. SystemRDL has a single encoding per field, so only
+//! the `read-write` enumerated values are used, or the first ones if there are none.
+//! Alternate registers and clusters, `isDefault` enumerated values and interrupts
+//! can't be represented and are left out.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use svd_rs as svd;
+
+use crate::svd::{
+    checked_shr, Access, Cluster, Device, DimElement, Field, FieldInfo, ModifiedWriteValues,
+    Peripheral, ReadAction, Register, RegisterCluster, RegisterInfo, RegisterProperties, Usage,
+};
+
+/// Keywords of SystemRDL 2.0, which are escaped in identifiers
+const KEYWORDS: &[&str] = &[
+    "abstract",
+    "accesstype",
+    "addressingtype",
+    "addrmap",
+    "alias",
+    "all",
+    "bit",
+    "boolean",
+    "bothedge",
+    "compact",
+    "component",
+    "componentwidth",
+    "constraint",
+    "default",
+    "encode",
+    "enum",
+    "external",
+    "false",
+    "field",
+    "fullalign",
+    "hw",
+    "inside",
+    "internal",
+    "level",
+    "longint",
+    "mem",
+    "na",
+    "negedge",
+    "nonsticky",
+    "number",
+    "onreadtype",
+    "onwritetype",
+    "posedge",
+    "property",
+    "r",
+    "rclr",
+    "ref",
+    "reg",
+    "regalign",
+    "regfile",
+    "rset",
+    "ruser",
+    "rw",
+    "rw1",
+    "signal",
+    "sparse",
+    "string",
+    "struct",
+    "sw",
+    "this",
+    "true",
+    "type",
+    "unsigned",
+    "w",
+    "w1",
+    "wclr",
+    "woclr",
+    "woset",
+    "wot",
+    "wr",
+    "wset",
+    "wuser",
+    "wzc",
+    "wzs",
+    "wzt",
+];
+
+/// Generate the SystemRDL description of the device
+pub fn generate(device: &Device) -> String {
+    let g = Generator { device };
+    let mut out = String::new();
+    writeln!(
+        out,
+        "// SystemRDL description of {}, generated from the SVD description.",
+        device.name
+    )
+    .unwrap();
+    writeln!(out).unwrap();
+    for p in &device.peripherals {
+        if p.registers.is_some() {
+            g.peripheral(&mut out, p);
+        }
+    }
+    writeln!(out, "addrmap {} {{", ident(&device.name)).unwrap();
+    if let Some(description) = &device.description {
+        property(&mut out, 1, "desc", &string(description));
+    }
+    for p in &device.peripherals {
+        let source = g.source(p);
+        if source.registers.is_none() {
+            continue;
+        }
+        let dim = match p {
+            Peripheral::Single(_) => None,
+            Peripheral::Array(_, dim) => Some(dim),
+        };
+        let instances = instances(&p.name, p.base_address, dim);
+        line(
+            &mut out,
+            1,
+            &format!("{} {};", ident(&source.name), instances.join(", ")),
+        );
+    }
+    writeln!(out, "}};").unwrap();
+    out
+}
+
+struct Generator<'a> {
+    device: &'a Device,
+}
+
+impl<'a> Generator<'a> {
+    fn peripheral(&self, out: &mut String, p: &'a Peripheral) {
+        writeln!(out, "addrmap {} {{", ident(&p.name)).unwrap();
+        if let Some(description) = &p.description {
+            property(out, 1, "desc", &string(description));
+        }
+        let properties = p
+            .default_register_properties
+            .inherit(&self.device.default_register_properties);
+        self.children(out, 1, p.registers.as_deref().unwrap_or(&[]), &properties);
+        writeln!(out, "}};").unwrap();
+        writeln!(out).unwrap();
+    }
+
+    fn children(
+        &self,
+        out: &mut String,
+        level: usize,
+        children: &'a [RegisterCluster],
+        properties: &RegisterProperties,
+    ) {
+        for rc in children {
+            match rc {
+                RegisterCluster::Register(r) => self.register(out, level, children, r, properties),
+                RegisterCluster::Cluster(c) => self.cluster(out, level, children, c, properties),
+            }
+        }
+    }
+
+    fn cluster(
+        &self,
+        out: &mut String,
+        level: usize,
+        siblings: &'a [RegisterCluster],
+        c: &'a Cluster,
+        properties: &RegisterProperties,
+    ) {
+        if let Some(primary) = &c.alternate_cluster {
+            line(
+                out,
+                level,
+                &format!(
+                    "// {} is an alternate of {} and is not exported",
+                    c.name, primary
+                ),
+            );
+            return;
+        }
+        let source = self.cluster_source(siblings, c);
+        let properties = c
+            .default_register_properties
+            .inherit(&source.default_register_properties)
+            .inherit(properties);
+        line(out, level, "regfile {");
+        description(
+            out,
+            level + 1,
+            c.description.as_ref().or(source.description.as_ref()),
+        );
+        self.children(out, level + 1, &source.children, &properties);
+        let dim = match c {
+            Cluster::Single(_) => None,
+            Cluster::Array(_, dim) => Some(dim),
+        };
+        let instances = instances(&c.name, c.address_offset as u64, dim);
+        line(out, level, &format!("}} {};", instances.join(", ")));
+    }
+
+    fn register(
+        &self,
+        out: &mut String,
+        level: usize,
+        siblings: &'a [RegisterCluster],
+        r: &'a Register,
+        properties: &RegisterProperties,
+    ) {
+        if r.alternate_group.is_some() || r.alternate_register.is_some() {
+            let primary = match &r.alternate_register {
+                Some(primary) => primary.clone(),
+                None => format!("offset 0x{:X}", r.address_offset),
+            };
+            line(
+                out,
+                level,
+                &format!(
+                    "// {} is an alternate of {} and is not exported",
+                    r.name, primary
+                ),
+            );
+            return;
+        }
+        let source = self.register_source(siblings, r);
+        let derived;
+        let info: &RegisterInfo = if std::ptr::eq(source, r) {
+            r
+        } else {
+            derived = derive_register(r, source);
+            &derived
+        };
+        let properties = info.properties.inherit(properties);
+        let size = properties.size.unwrap_or(32);
+        line(out, level, "reg {");
+        if let Some(name) = &info.display_name {
+            property(out, level + 1, "name", &string(name));
+        }
+        description(out, level + 1, info.description.as_ref());
+        if size != 32 {
+            property(out, level + 1, "regwidth", &size.to_string());
+        }
+        let fields = info.fields.as_deref().unwrap_or(&[]);
+        if fields.is_empty() {
+            let field = FieldInfo::builder()
+                .name(strip(&r.name))
+                .bit_offset(0)
+                .bit_width(size)
+                .build(svd::ValidateLevel::Disabled)
+                .unwrap();
+            self.field(out, level + 1, info, &properties, &field, None, None);
+        } else {
+            let mut enums = HashMap::new();
+            for f in fields {
+                let encode = self.enumeration(out, level + 1, f, &mut enums);
+                let dim = match f {
+                    Field::Single(_) => None,
+                    Field::Array(_, dim) => Some(dim),
+                };
+                self.field(out, level + 1, info, &properties, f, dim, encode.as_deref());
+            }
+        }
+        let dim = match r {
+            Register::Single(_) => None,
+            Register::Array(_, dim) => Some(dim),
+        };
+        let instances = instances(&r.name, r.address_offset as u64, dim);
+        line(out, level, &format!("}} {};", instances.join(", ")));
+    }
+
+    /// Write the `enum` of the field and return its name.
+    /// `enums` maps the names of the enumerated values in the register to their `enum`.
+    fn enumeration(
+        &self,
+        out: &mut String,
+        level: usize,
+        f: &Field,
+        enums: &mut HashMap<String, String>,
+    ) -> Option<String> {
+        let e = f
+            .enumerated_values
+            .iter()
+            .find(|e| e.usage() == Usage::ReadWrite)
+            .or_else(|| f.enumerated_values.first())?;
+        if let Some(derived) = &e.derived_from {
+            let name = derived.rsplit('.').next().unwrap_or(derived);
+            return enums.get(name).cloned();
+        }
+        let values: Vec<_> = e
+            .values
+            .iter()
+            .filter_map(|v| v.value.map(|value| (v, value)))
+            .collect();
+        if values.is_empty() {
+            return None;
+        }
+        let mut name = match &e.name {
+            Some(name) => ident(name),
+            None => format!("{}_e", ident(&f.name)),
+        };
+        if enums.values().any(|n| n == &name) {
+            name = format!("{}_{}_e", ident(&f.name), name);
+        }
+        line(out, level, &format!("enum {} {{", name));
+        for (v, value) in values {
+            let entry = format!("{} = {}'h{:X}", ident(&v.name), f.bit_range.width, value);
+            match &v.description {
+                Some(d) => line(
+                    out,
+                    level + 1,
+                    &format!("{} {{ desc = {}; }};", entry, string(d)),
+                ),
+                None => line(out, level + 1, &format!("{};", entry)),
+            }
+        }
+        line(out, level, "};");
+        enums.insert(e.name.clone().unwrap_or_else(|| name.clone()), name.clone());
+        Some(name)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn field(
+        &self,
+        out: &mut String,
+        level: usize,
+        r: &RegisterInfo,
+        properties: &RegisterProperties,
+        f: &FieldInfo,
+        dim: Option<&DimElement>,
+        encode: Option<&str>,
+    ) {
+        let access = f.access.or(properties.access).unwrap_or(Access::ReadWrite);
+        let onwrite = match f.modified_write_values.or(r.modified_write_values) {
+            Some(ModifiedWriteValues::OneToClear) => Some("woclr"),
+            Some(ModifiedWriteValues::OneToSet) => Some("woset"),
+            Some(ModifiedWriteValues::OneToToggle) => Some("wot"),
+            Some(ModifiedWriteValues::ZeroToClear) => Some("wzc"),
+            Some(ModifiedWriteValues::ZeroToSet) => Some("wzs"),
+            Some(ModifiedWriteValues::ZeroToToggle) => Some("wzt"),
+            Some(ModifiedWriteValues::Clear) => Some("wclr"),
+            Some(ModifiedWriteValues::Set) => Some("wset"),
+            Some(ModifiedWriteValues::Modify) | None => None,
+        };
+        let onread = match f.read_action.or(r.read_action) {
+            Some(ReadAction::Clear) => Some("rclr"),
+            Some(ReadAction::Set) => Some("rset"),
+            Some(ReadAction::Modify) | Some(ReadAction::ModifyExternal) => Some("ruser"),
+            None => None,
+        };
+        let hw = if onwrite.is_some() || onread.is_some() {
+            "rw"
+        } else if !access.can_write() {
+            "w"
+        } else {
+            "r"
+        };
+
+        line(out, level, "field {");
+        description(out, level + 1, f.description.as_ref());
+        property(out, level + 1, "sw", sw(access));
+        property(out, level + 1, "hw", hw);
+        if let Some(onwrite) = onwrite {
+            property(out, level + 1, "onwrite", onwrite);
+        }
+        if let Some(onread) = onread {
+            property(out, level + 1, "onread", onread);
+        }
+        if let Some(encode) = encode {
+            property(out, level + 1, "encode", encode);
+        }
+
+        let width = f.bit_range.width;
+        let mask = if width >= 64 {
+            u64::MAX
+        } else {
+            (1 << width) - 1
+        };
+        let elements = match dim {
+            None => vec![(f.name.clone(), f.bit_range.offset)],
+            Some(dim) => dim
+                .indexes()
+                .enumerate()
+                .map(|(i, idx)| {
+                    (
+                        instance(&f.name, &idx),
+                        f.bit_range.offset + i as u32 * dim.dim_increment,
+                    )
+                })
+                .collect(),
+        };
+        let instances: Vec<_> = elements
+            .into_iter()
+            .map(|(name, offset)| {
+                let mut instance = format!("{}[{}:{}]", ident(&name), offset + width - 1, offset);
+                if let Some(reset) = properties.reset_value {
+                    let reset_mask = properties.reset_mask.unwrap_or(u64::MAX);
+                    if checked_shr(reset_mask, offset) & mask == mask {
+                        write!(instance, " = 0x{:X}", checked_shr(reset, offset) & mask).unwrap();
+                    }
+                }
+                instance
+            })
+            .collect();
+        line(out, level, &format!("}} {};", instances.join(", ")));
+    }
+
+    /// Cluster defining the registers of `c`
+    fn cluster_source(&self, siblings: &'a [RegisterCluster], c: &'a Cluster) -> &'a Cluster {
+        let mut source = c;
+        // Limit the depth in case of circular references
+        for _ in 0..=siblings.len() {
+            if !source.children.is_empty() {
+                break;
+            }
+            match source
+                .derived_from
+                .as_ref()
+                .and_then(|path| self.find(siblings, path))
+            {
+                Some(RegisterCluster::Cluster(parent)) => source = parent,
+                _ => break,
+            }
+        }
+        source
+    }
+
+    /// Register defining the fields of `r`
+    fn register_source(&self, siblings: &'a [RegisterCluster], r: &'a Register) -> &'a Register {
+        let mut source = r;
+        for _ in 0..=siblings.len() {
+            if source.fields.is_some() {
+                break;
+            }
+            match source
+                .derived_from
+                .as_ref()
+                .and_then(|path| self.find(siblings, path))
+            {
+                Some(RegisterCluster::Register(parent)) => source = parent,
+                _ => break,
+            }
+        }
+        source
+    }
+
+    /// Find the register or cluster of a `derivedFrom`, a name of one of the `siblings`
+    /// or a path starting with the name of a peripheral
+    fn find(&self, siblings: &'a [RegisterCluster], path: &str) -> Option<&'a RegisterCluster> {
+        let find = |children: &'a [RegisterCluster], name: &str| {
+            children.iter().find(|rc| match rc {
+                RegisterCluster::Register(r) => r.name == name,
+                RegisterCluster::Cluster(c) => c.name == name,
+            })
+        };
+        let mut names = path.split('.');
+        let first = names.next()?;
+        let peripheral = match self.device.peripherals.iter().find(|p| p.name == first) {
+            Some(p) if path.contains('.') => self.source(p),
+            _ => return find(siblings, path),
+        };
+        let mut children = peripheral.registers.as_deref()?;
+        let mut found = None;
+        for name in names {
+            let rc = find(children, name)?;
+            children = match rc {
+                RegisterCluster::Cluster(c) => &c.children,
+                RegisterCluster::Register(_) => &[],
+            };
+            found = Some(rc);
+        }
+        found
+    }
+
+    /// Peripheral defining the registers of `p`
+    fn source(&self, p: &'a Peripheral) -> &'a Peripheral {
+        let mut source = p;
+        for _ in 0..self.device.peripherals.len() {
+            if source.registers.is_some() {
+                break;
+            }
+            match source
+                .derived_from
+                .as_ref()
+                .and_then(|name| self.device.peripherals.iter().find(|p| &p.name == name))
+            {
+                Some(parent) => source = parent,
+                None => break,
+            }
+        }
+        source
+    }
+}
+
+/// Instances of an element at `offset`, as a SystemRDL array if the indexes are `0..dim`
+fn instances(name: &str, offset: u64, dim: Option<&DimElement>) -> Vec<String> {
+    match dim {
+        None => vec![format!("{} @ 0x{:X}", ident(name), offset)],
+        Some(dim)
+            if dim
+                .indexes()
+                .enumerate()
+                .all(|(i, idx)| idx.parse::<usize>().ok() == Some(i)) =>
+        {
+            vec![format!(
+                "{}[{}] @ 0x{:X} += 0x{:X}",
+                ident(name),
+                dim.dim,
+                offset,
+                dim.dim_increment
+            )]
+        }
+        Some(dim) => dim
+            .indexes()
+            .enumerate()
+            .map(|(i, idx)| {
+                format!(
+                    "{} @ 0x{:X}",
+                    ident(&instance(name, &idx)),
+                    offset + i as u64 * dim.dim_increment as u64
+                )
+            })
+            .collect(),
+    }
+}
+
+fn sw(access: Access) -> &'static str {
+    match access {
+        Access::ReadOnly => "r",
+        Access::WriteOnly => "w",
+        Access::ReadWrite => "rw",
+        Access::WriteOnce => "w1",
+        Access::ReadWriteOnce => "rw1",
+    }
+}
+
+fn line(out: &mut String, level: usize, text: &str) {
+    writeln!(out, "{:indent$}{}", "", text, indent = level * 4).unwrap();
+}
+
+fn property(out: &mut String, level: usize, name: &str, value: &str) {
+    line(out, level, &format!("{} = {};", name, value));
+}
+
+/// Register `r` with the values it doesn't set taken from `source`
+fn derive_register(r: &RegisterInfo, source: &RegisterInfo) -> RegisterInfo {
+    let mut derived = r.clone();
+    derived.display_name = r
+        .display_name
+        .clone()
+        .or_else(|| source.display_name.clone());
+    derived.description = r.description.clone().or_else(|| source.description.clone());
+    derived.properties = r.properties.inherit(&source.properties);
+    derived.modified_write_values = r.modified_write_values.or(source.modified_write_values);
+    derived.write_constraint = r.write_constraint.or(source.write_constraint);
+    derived.read_action = r.read_action.or(source.read_action);
+    derived.fields = r.fields.clone().or_else(|| source.fields.clone());
+    derived
+}
+
+fn description(out: &mut String, level: usize, description: Option<&String>) {
+    if let Some(description) = description {
+        property(out, level, "desc", &string(description));
+    }
+}
+
+/// SystemRDL identifier of the name, without the `%s` placeholder
+fn ident(name: &str) -> String {
+    let mut ident: String = strip(name)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    if KEYWORDS.contains(&ident.as_str()) {
+        ident.insert(0, '\\');
+    }
+    ident
+}
+
+/// Quoted SystemRDL string on a single line
+fn string(text: &str) -> String {
+    format!(
+        "\"{}\"",
+        one_line(text).replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+/// Remove the `%s` placeholder from the name of an array
+fn strip(name: &str) -> String {
+    name.replace("[%s]", "").replace("%s", "")
+}
+
+fn instance(name: &str, index: &str) -> String {
+    name.replace("[%s]", index).replace("%s", index)
+}
+
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
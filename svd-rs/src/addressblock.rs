@@ -63,6 +63,11 @@ impl AddressBlockUsage {
 }
 
 /// Builder for [`AddressBlock`]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(default, rename_all = "camelCase")
+)]
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct AddressBlockBuilder {
     offset: Option<u32>,
@@ -0,0 +1,519 @@
+//! Reverse lookup of bus addresses.
+//!
+//! [`AddressIndex`] expands all peripheral, cluster and register arrays of a
+//! [`Device`] and stores the address ranges of the register instances and of the
+//! address blocks in interval trees, so each lookup takes `O(log n)` time plus
+//! the number of overlapping elements.
+//!
+//! Peripherals which are derived from another peripheral and don't define their
+//! own registers or address blocks use the ones of the peripheral they are derived from.
+//! In the same way, derived clusters without registers use the registers of their
+//! source, and derived registers without fields use the fields of their source.
+//! Register properties which are not set are inherited from the source.
+//! Registers without a size are assumed to be 32 bits wide.
+
+use super::{
+    AddressBlock, BitRange, Cluster, Device, DimElement, Field, Peripheral, Register,
+    RegisterCluster, RegisterProperties,
+};
+
+/// An expanded peripheral instance
+#[derive(Clone, Debug, PartialEq)]
+pub struct PeripheralInstance<'a> {
+    /// The peripheral or peripheral array
+    pub peripheral: &'a Peripheral,
+    /// Name of the instance
+    pub name: String,
+    /// Index of the array instance, `None` for single peripherals
+    pub index: Option<String>,
+    /// Base address of the instance
+    pub base_address: u64,
+}
+
+/// An address block of an expanded peripheral instance
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockInstance<'a> {
+    /// The address block
+    pub block: &'a AddressBlock,
+    /// Index of the peripheral instance in [`AddressIndex::peripherals`]
+    pub peripheral: usize,
+    /// Absolute address of the block
+    pub address: u64,
+}
+
+/// An expanded register instance
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegisterInstance<'a> {
+    /// The register or register array
+    pub register: &'a Register,
+    /// Register the fields are taken from: `register` itself, or the register it
+    /// is derived from if it has no fields of its own
+    pub source: &'a Register,
+    /// Index of the peripheral instance in [`AddressIndex::peripherals`]
+    pub peripheral: usize,
+    /// Path of the instance, using instance names for all array instances
+    pub path: String,
+    /// Index of the register array instance, `None` for single registers
+    pub index: Option<String>,
+    /// Absolute address of the instance
+    pub address: u64,
+    /// Size of the register in bits
+    pub size: u32,
+    /// Register properties of the register, filled with the inherited ones
+    pub properties: RegisterProperties,
+}
+
+/// An expanded field instance
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldInstance<'a> {
+    /// The field or field array
+    pub field: &'a Field,
+    /// Name of the instance
+    pub name: String,
+    /// Index of the field array instance, `None` for single fields
+    pub index: Option<String>,
+    /// Position of the instance in the register
+    pub bit_range: BitRange,
+}
+
+impl<'a> RegisterInstance<'a> {
+    /// Get all field instances of the register
+    pub fn fields(&self) -> Vec<FieldInstance<'a>> {
+        field_instances(self.source.fields.as_deref().unwrap_or(&[]))
+    }
+
+    /// Return `true` if the register is an alternate of another register
+    pub fn is_alternate(&self) -> bool {
+        self.register.alternate_register.is_some() || self.register.alternate_group.is_some()
+    }
+}
+
+/// Result of [`AddressIndex::register_at`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Location<'a> {
+    /// Peripheral instance containing the register
+    pub peripheral: &'a PeripheralInstance<'a>,
+    /// Address block containing the address, if any
+    pub block: Option<&'a BlockInstance<'a>>,
+    /// The register at the address.
+    ///
+    /// Registers which are not alternates of another register
+    /// and peripherals which are not alternates of another peripheral are preferred.
+    pub register: &'a RegisterInstance<'a>,
+    /// Other registers at the address
+    pub alternates: Vec<&'a RegisterInstance<'a>>,
+    /// Offset of the address from the start of the register in bytes
+    pub offset: u32,
+    /// Fields with bits in the byte at the address
+    pub fields: Vec<FieldInstance<'a>>,
+}
+
+struct RegisterEntry<'a> {
+    /// Register or its peripheral is an alternate
+    alternate: bool,
+    register: RegisterInstance<'a>,
+}
+
+/// Interval tree index of the register and address block instances of a [`Device`]
+pub struct AddressIndex<'a> {
+    peripherals: Vec<PeripheralInstance<'a>>,
+    registers: IntervalTree<RegisterEntry<'a>>,
+    blocks: IntervalTree<BlockInstance<'a>>,
+}
+
+impl<'a> AddressIndex<'a> {
+    /// Build the index of the device
+    pub fn new(device: &'a Device) -> Self {
+        let mut peripherals = Vec::new();
+        let mut registers = Vec::new();
+        let mut blocks = Vec::new();
+        for p in &device.peripherals {
+            let source = derived_source(device, p);
+            let properties = p
+                .default_register_properties
+                .inherit(&source.default_register_properties)
+                .inherit(&device.default_register_properties);
+            let children = p
+                .registers
+                .as_deref()
+                .or_else(|| source.registers.as_deref())
+                .unwrap_or(&[]);
+            let address_block = p
+                .address_block
+                .as_deref()
+                .or_else(|| source.address_block.as_deref())
+                .unwrap_or(&[]);
+            let dim = match p {
+                Peripheral::Single(_) => None,
+                Peripheral::Array(_, dim) => Some(dim),
+            };
+            for (name, index, offset) in instances(&p.name, dim) {
+                let base_address = p.base_address + offset;
+                let id = peripherals.len();
+                let mut builder = Builder {
+                    device,
+                    peripheral: id,
+                    alternate: p.alternate_peripheral.is_some(),
+                    registers: &mut registers,
+                };
+                builder.children(children, &name, base_address, &properties);
+                for block in address_block {
+                    let start = base_address + block.offset as u64;
+                    blocks.push((
+                        start,
+                        start + block.size as u64,
+                        BlockInstance {
+                            block,
+                            peripheral: id,
+                            address: start,
+                        },
+                    ));
+                }
+                peripherals.push(PeripheralInstance {
+                    peripheral: p,
+                    name,
+                    index,
+                    base_address,
+                });
+            }
+        }
+        Self {
+            peripherals,
+            registers: IntervalTree::new(registers),
+            blocks: IntervalTree::new(blocks),
+        }
+    }
+
+    /// Get all expanded peripheral instances
+    pub fn peripherals(&self) -> &[PeripheralInstance<'a>] {
+        &self.peripherals
+    }
+
+    /// Iterate over all expanded register instances, ordered by address
+    pub fn registers(&self) -> impl Iterator<Item = &RegisterInstance<'a>> {
+        self.registers.items.iter().map(|(_, _, e)| &e.register)
+    }
+
+    /// Find the register containing the address
+    pub fn register_at(&self, address: u64) -> Option<Location> {
+        let mut found = Vec::new();
+        self.registers.find(address, &mut found);
+        found.sort_by_key(|&i| (self.registers.get(i).alternate, i));
+        let mut found = found.into_iter().map(|i| &self.registers.get(i).register);
+        let register = found.next()?;
+        let offset = (address - register.address) as u32;
+        let (lsb, msb) = (offset * 8, offset * 8 + 7);
+        let fields = register
+            .fields()
+            .into_iter()
+            .filter(|f| f.bit_range.lsb() <= msb && f.bit_range.msb() >= lsb)
+            .collect();
+        Some(Location {
+            peripheral: &self.peripherals[register.peripheral],
+            block: self.block_at(address),
+            register,
+            alternates: found.collect(),
+            offset,
+            fields,
+        })
+    }
+
+    /// Find the address block containing the address.
+    ///
+    /// If blocks overlap, the one with the lowest address is returned.
+    pub fn block_at(&self, address: u64) -> Option<&BlockInstance> {
+        let mut found = Vec::new();
+        self.blocks.find(address, &mut found);
+        found.into_iter().min().map(|i| self.blocks.get(i))
+    }
+}
+
+impl Device {
+    /// Build an address index of the device. See [`addressindex`](crate::addressindex).
+    pub fn address_index(&self) -> AddressIndex {
+        AddressIndex::new(self)
+    }
+}
+
+struct Builder<'a, 'b> {
+    device: &'a Device,
+    peripheral: usize,
+    alternate: bool,
+    registers: &'b mut Vec<(u64, u64, RegisterEntry<'a>)>,
+}
+
+impl<'a, 'b> Builder<'a, 'b> {
+    fn children(
+        &mut self,
+        children: &'a [RegisterCluster],
+        parent: &str,
+        base: u64,
+        properties: &RegisterProperties,
+    ) {
+        for rc in children {
+            match rc {
+                RegisterCluster::Cluster(c) => {
+                    let (source, own) = self.cluster_source(children, c);
+                    let properties = own.inherit(properties);
+                    let dim = match c {
+                        Cluster::Single(_) => None,
+                        Cluster::Array(_, dim) => Some(dim),
+                    };
+                    for (name, _, offset) in instances(&c.name, dim) {
+                        let address = base + c.address_offset as u64 + offset;
+                        let path = format!("{}.{}", parent, name);
+                        self.children(&source.children, &path, address, &properties);
+                    }
+                }
+                RegisterCluster::Register(r) => {
+                    let (source, own) = self.register_source(children, r);
+                    let properties = own.inherit(properties);
+                    let size = properties.size.unwrap_or(32);
+                    let dim = match r {
+                        Register::Single(_) => None,
+                        Register::Array(_, dim) => Some(dim),
+                    };
+                    for (name, index, offset) in instances(&r.name, dim) {
+                        let address = base + r.address_offset as u64 + offset;
+                        let register = RegisterInstance {
+                            register: r,
+                            source,
+                            peripheral: self.peripheral,
+                            path: format!("{}.{}", parent, name),
+                            index,
+                            address,
+                            size,
+                            properties,
+                        };
+                        let end = address + (size.max(1) as u64 - 1) / 8 + 1;
+                        let alternate = self.alternate || register.is_alternate();
+                        self.registers.push((
+                            address,
+                            end,
+                            RegisterEntry {
+                                alternate,
+                                register,
+                            },
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Cluster defining the registers of `c`, and the default register properties
+    /// of `c` filled with the ones of the clusters it is derived from
+    fn cluster_source(
+        &self,
+        siblings: &'a [RegisterCluster],
+        c: &'a Cluster,
+    ) -> (&'a Cluster, RegisterProperties) {
+        let mut source = c;
+        let mut properties = c.default_register_properties;
+        let mut current = c;
+        // Limit the depth in case of circular references
+        for _ in 0..=siblings.len() {
+            match current
+                .derived_from
+                .as_ref()
+                .and_then(|path| self.find(siblings, path))
+            {
+                Some(RegisterCluster::Cluster(parent)) => {
+                    properties = properties.inherit(&parent.default_register_properties);
+                    if source.children.is_empty() {
+                        source = parent;
+                    }
+                    current = parent;
+                }
+                _ => break,
+            }
+        }
+        (source, properties)
+    }
+
+    /// Register defining the fields of `r`, and the properties of `r` filled with
+    /// the ones of the registers it is derived from
+    fn register_source(
+        &self,
+        siblings: &'a [RegisterCluster],
+        r: &'a Register,
+    ) -> (&'a Register, RegisterProperties) {
+        let mut source = r;
+        let mut properties = r.properties;
+        let mut current = r;
+        for _ in 0..=siblings.len() {
+            match current
+                .derived_from
+                .as_ref()
+                .and_then(|path| self.find(siblings, path))
+            {
+                Some(RegisterCluster::Register(parent)) => {
+                    properties = properties.inherit(&parent.properties);
+                    if source.fields.is_none() {
+                        source = parent;
+                    }
+                    current = parent;
+                }
+                _ => break,
+            }
+        }
+        (source, properties)
+    }
+
+    /// Find the register or cluster of a `derivedFrom`, a name of one of the `siblings`
+    /// or a path starting with the name of a peripheral
+    fn find(&self, siblings: &'a [RegisterCluster], path: &str) -> Option<&'a RegisterCluster> {
+        let find = |children: &'a [RegisterCluster], name: &str| {
+            children.iter().find(|rc| match rc {
+                RegisterCluster::Register(r) => r.name == name,
+                RegisterCluster::Cluster(c) => c.name == name,
+            })
+        };
+        let mut names = path.split('.');
+        let first = names.next()?;
+        let peripheral = match self.device.peripherals.iter().find(|p| p.name == first) {
+            Some(p) if path.contains('.') => p,
+            _ => return find(siblings, path),
+        };
+        let mut children = peripheral
+            .registers
+            .as_deref()
+            .or_else(|| derived_source(self.device, peripheral).registers.as_deref())?;
+        let mut found = None;
+        for name in names {
+            let rc = find(children, name)?;
+            children = match rc {
+                RegisterCluster::Cluster(c) => &c.children,
+                RegisterCluster::Register(_) => &[],
+            };
+            found = Some(rc);
+        }
+        found
+    }
+}
+
+/// Peripheral the registers and address blocks are taken from, if `p` has none
+fn derived_source<'a>(device: &'a Device, p: &'a Peripheral) -> &'a Peripheral {
+    let mut source = p;
+    // Limit the depth in case of circular references
+    for _ in 0..device.peripherals.len() {
+        if source.registers.is_some() && source.address_block.is_some() {
+            break;
+        }
+        match source
+            .derived_from
+            .as_ref()
+            .and_then(|name| device.peripherals.iter().find(|p| &p.name == name))
+        {
+            Some(parent) => source = parent,
+            None => break,
+        }
+    }
+    source
+}
+
+/// Names, indexes and address offsets of the instances of an element
+fn instances(name: &str, dim: Option<&DimElement>) -> Vec<(String, Option<String>, u64)> {
+    match dim {
+        None => vec![(name.to_string(), None, 0)],
+        Some(dim) => dim
+            .indexes()
+            .enumerate()
+            .map(|(i, idx)| {
+                (
+                    instance_name(name, &idx),
+                    Some(idx.into_owned()),
+                    i as u64 * dim.dim_increment as u64,
+                )
+            })
+            .collect(),
+    }
+}
+
+/// Expand the field arrays
+pub(crate) fn field_instances(fields: &[Field]) -> Vec<FieldInstance> {
+    let mut instances = Vec::new();
+    for f in fields {
+        match f {
+            Field::Single(info) => instances.push(FieldInstance {
+                field: f,
+                name: info.name.clone(),
+                index: None,
+                bit_range: info.bit_range,
+            }),
+            Field::Array(info, dim) => {
+                for (i, idx) in dim.indexes().enumerate() {
+                    let offset = info.bit_range.offset + i as u32 * dim.dim_increment;
+                    instances.push(FieldInstance {
+                        field: f,
+                        name: instance_name(&info.name, &idx),
+                        index: Some(idx.into_owned()),
+                        bit_range: BitRange::from_offset_width(offset, info.bit_range.width),
+                    });
+                }
+            }
+        }
+    }
+    instances
+}
+
+fn instance_name(name: &str, index: &str) -> String {
+    name.replace("[%s]", index).replace("%s", index)
+}
+
+/// Static interval tree, stored as an implicit binary search tree over the
+/// intervals sorted by their start, where each node keeps the maximum end of its subtree
+struct IntervalTree<T> {
+    /// Half-open intervals `start..end` with their values
+    items: Vec<(u64, u64, T)>,
+    /// Maximum end of the subtree with the root at the same index
+    max_end: Vec<u64>,
+}
+
+impl<T> IntervalTree<T> {
+    fn new(mut items: Vec<(u64, u64, T)>) -> Self {
+        // Stable sort keeps the device order for intervals with the same start
+        items.sort_by_key(|item| item.0);
+        let mut max_end = vec![0; items.len()];
+        Self::build(&items, &mut max_end, 0, items.len());
+        Self { items, max_end }
+    }
+
+    fn build(items: &[(u64, u64, T)], max_end: &mut [u64], lo: usize, hi: usize) -> u64 {
+        if lo >= hi {
+            return 0;
+        }
+        let mid = lo + (hi - lo) / 2;
+        let left = Self::build(items, max_end, lo, mid);
+        let right = Self::build(items, max_end, mid + 1, hi);
+        max_end[mid] = items[mid].1.max(left).max(right);
+        max_end[mid]
+    }
+
+    fn get(&self, i: usize) -> &T {
+        &self.items[i].2
+    }
+
+    /// Collect the indexes of the intervals containing `point`
+    fn find(&self, point: u64, found: &mut Vec<usize>) {
+        self.find_in(point, 0, self.items.len(), found)
+    }
+
+    fn find_in(&self, point: u64, lo: usize, hi: usize, found: &mut Vec<usize>) {
+        if lo >= hi {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        if self.max_end[mid] <= point {
+            return;
+        }
+        self.find_in(point, lo, mid, found);
+        let (start, end, _) = &self.items[mid];
+        if *start <= point {
+            if point < *end {
+                found.push(mid);
+            }
+            self.find_in(point, mid + 1, hi, found);
+        }
+    }
+}
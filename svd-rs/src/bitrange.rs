@@ -135,7 +135,7 @@ mod ser_de {
         where
             M: MapAccess<'de>,
         {
-            match map.next_key::<&str>()? {
+            match map.next_key::<String>()? {
                 Some(k) if k == "bitRange" => {
                     let s: String = map.next_value()?;
                     BitRange::from_bit_range(&s)
@@ -146,13 +146,13 @@ mod ser_de {
                     let width;
                     if k == "bitOffset" {
                         offset = map.next_value()?;
-                        width = match map.next_key::<&str>()? {
+                        width = match map.next_key::<String>()? {
                             Some(k) if k == "bitWidth" => map.next_value()?,
                             _ => return Err(serde::de::Error::custom("Missing bitWidth")),
                         };
                     } else {
                         width = map.next_value()?;
-                        offset = match map.next_key::<&str>()? {
+                        offset = match map.next_key::<String>()? {
                             Some(k) if k == "bitOffset" => map.next_value()?,
                             _ => return Err(serde::de::Error::custom("Missing bitOffset")),
                         };
@@ -164,13 +164,13 @@ mod ser_de {
                     let lsb;
                     if k == "msb" {
                         msb = map.next_value()?;
-                        lsb = match map.next_key::<&str>()? {
+                        lsb = match map.next_key::<String>()? {
                             Some(k) if k == "lsb" => map.next_value()?,
                             _ => return Err(serde::de::Error::custom("Missing lsb")),
                         };
                     } else {
                         lsb = map.next_value()?;
-                        msb = match map.next_key::<&str>()? {
+                        msb = match map.next_key::<String>()? {
                             Some(k) if k == "msb" => map.next_value()?,
                             _ => return Err(serde::de::Error::custom("Missing msb")),
                         };
@@ -66,6 +66,11 @@ pub struct ClusterInfo {
 }
 
 /// Builder for [`ClusterInfo`]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(default, rename_all = "camelCase")
+)]
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct ClusterInfoBuilder {
     name: Option<String>,
@@ -73,6 +78,7 @@ pub struct ClusterInfoBuilder {
     alternate_cluster: Option<String>,
     header_struct_name: Option<String>,
     address_offset: Option<u32>,
+    #[cfg_attr(feature = "serde", serde(flatten))]
     default_register_properties: RegisterProperties,
     children: Option<Vec<RegisterCluster>>,
     derived_from: Option<String>,
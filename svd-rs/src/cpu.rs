@@ -64,6 +64,11 @@ pub struct Cpu {
 }
 
 /// Builder for [`Cpu`]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(default, rename_all = "camelCase")
+)]
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct CpuBuilder {
     name: Option<String>,
@@ -71,6 +76,7 @@ pub struct CpuBuilder {
     endian: Option<Endian>,
     mpu_present: Option<bool>,
     fpu_present: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(rename = "fpuDP"))]
     fpu_double_precision: Option<bool>,
     dsp_present: Option<bool>,
     icache_present: Option<bool>,
@@ -78,7 +84,9 @@ pub struct CpuBuilder {
     itcm_present: Option<bool>,
     dtcm_present: Option<bool>,
     vtor_present: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(rename = "nvicPrioBits"))]
     nvic_priority_bits: Option<u32>,
+    #[cfg_attr(feature = "serde", serde(rename = "vendorSystickConfig"))]
     has_vendor_systick: Option<bool>,
     device_num_interrupts: Option<u32>,
     sau_num_regions: Option<u32>,
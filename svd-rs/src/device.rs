@@ -78,6 +78,11 @@ pub struct Device {
 }
 
 /// Builder for [`Device`]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(default, rename_all = "camelCase")
+)]
 #[derive(Clone, Debug, Default)]
 pub struct DeviceBuilder {
     name: Option<String>,
@@ -86,6 +91,7 @@ pub struct DeviceBuilder {
     cpu: Option<Cpu>,
     address_unit_bits: Option<u32>,
     width: Option<u32>,
+    #[cfg_attr(feature = "serde", serde(flatten))]
     default_register_properties: RegisterProperties,
     peripherals: Option<Vec<Peripheral>>,
     schema_version: Option<String>,
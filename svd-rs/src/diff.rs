@@ -0,0 +1,669 @@
+//! Semantic comparison of two [`Device`]s.
+//!
+//! Elements are matched by their path (`PERIPHERAL.CLUSTER.REGISTER.FIELD.USAGE.VALUE`),
+//! not by their position in the file, so reordering elements is not reported.
+//! Enumerated values are identified by the usage of their container, like `read-write`,
+//! so read and write values with the same name are distinct.
+//! Register and field properties are compared after inheritance from the enclosing
+//! elements is applied, so moving a `<size>` from a register to its peripheral is not a change.
+//!
+//! Every [`Change`] is classified by its [`Impact`] on the code generated from the device.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use super::{
+    Access, BitRange, Cluster, Device, DimElement, EnumeratedValue, Field, ModifiedWriteValues,
+    Peripheral, ReadAction, Register, RegisterCluster, RegisterProperties, Usage, WriteConstraint,
+};
+
+/// Kind of the element a [`Change`] refers to
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ElementKind {
+    /// Peripheral
+    Peripheral,
+    /// Cluster
+    Cluster,
+    /// Register
+    Register,
+    /// Field
+    Field,
+    /// Enumerated value
+    EnumeratedValue,
+}
+
+/// A property of an element which differs between the devices.
+/// Each variant contains the old and the new value.
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Property {
+    /// Base address of a peripheral
+    BaseAddress(u64, u64),
+    /// Address offset of a cluster or register
+    AddressOffset(u32, u32),
+    /// Bit position of a field
+    BitRange(BitRange, BitRange),
+    /// Effective size of a register
+    Size(Option<u32>, Option<u32>),
+    /// Effective access of a register or field
+    Access(Option<Access>, Option<Access>),
+    /// Effective reset value of a register
+    ResetValue(Option<u64>, Option<u64>),
+    /// Effective reset mask of a register
+    ResetMask(Option<u64>, Option<u64>),
+    /// Write side effects of a register or field
+    ModifiedWriteValues(Option<ModifiedWriteValues>, Option<ModifiedWriteValues>),
+    /// Write constraint of a register or field
+    WriteConstraint(Option<WriteConstraint>, Option<WriteConstraint>),
+    /// Read side effects of a register or field
+    ReadAction(Option<ReadAction>, Option<ReadAction>),
+    /// Array description of an element
+    Dim(Option<Box<DimElement>>, Option<Box<DimElement>>),
+    /// Element the element is derived from
+    DerivedFrom(Option<String>, Option<String>),
+    /// Alternate register
+    AlternateRegister(Option<String>, Option<String>),
+    /// Description of an element
+    Description(Option<String>, Option<String>),
+    /// Value of an enumerated value
+    Value(Option<u64>, Option<u64>),
+    /// Default flag of an enumerated value
+    IsDefault(Option<bool>, Option<bool>),
+}
+
+/// What happened to an element
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChangeKind {
+    /// Element exists only in the new device
+    Added,
+    /// Element exists only in the old device
+    Removed,
+    /// Element exists in both devices, but the property differs
+    Modified(Property),
+}
+
+/// Impact of a change on the code generated from the device.
+///
+/// Variants are ordered by severity, so the impact of a set of changes is the maximum
+/// of their impacts.
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(rename_all = "lowercase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Impact {
+    /// Generated code is the same except for documentation
+    Cosmetic,
+    /// Generated code gets new items, existing code keeps working
+    Additive,
+    /// Code using the old device may stop compiling or behave differently
+    Breaking,
+}
+
+impl Impact {
+    /// Parse a string into an [`Impact`] value, returning [`Option::None`] if the string is not valid.
+    pub fn parse_str(s: &str) -> Option<Self> {
+        match s {
+            "cosmetic" => Some(Self::Cosmetic),
+            "additive" => Some(Self::Additive),
+            "breaking" => Some(Self::Breaking),
+            _ => None,
+        }
+    }
+
+    /// Convert this [`Impact`] into a static string.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Cosmetic => "cosmetic",
+            Self::Additive => "additive",
+            Self::Breaking => "breaking",
+        }
+    }
+}
+
+/// A single difference between two devices
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Change {
+    /// Kind of the changed element
+    pub element: ElementKind,
+    /// Path of the element, like `PERIPHERAL.REGISTER.FIELD`
+    pub path: String,
+    /// What has changed
+    pub kind: ChangeKind,
+    /// Impact of the change on the generated code
+    pub impact: Impact,
+}
+
+/// Return the highest impact of `changes`, `None` if there are no changes.
+pub fn impact(changes: &[Change]) -> Option<Impact> {
+    changes.iter().map(|c| c.impact).max()
+}
+
+impl ChangeKind {
+    /// Classify the change by its impact on the generated code.
+    ///
+    /// Removed elements are breaking, added elements are additive.
+    pub fn impact(&self) -> Impact {
+        match self {
+            Self::Added => Impact::Additive,
+            Self::Removed => Impact::Breaking,
+            Self::Modified(property) => property.impact(),
+        }
+    }
+}
+
+impl Property {
+    /// Classify the property change by its impact on the generated code.
+    pub fn impact(&self) -> Impact {
+        match self {
+            Self::Description(_, _) => Impact::Cosmetic,
+            Self::Access(old, new) => {
+                access_impact(old.unwrap_or_default(), new.unwrap_or_default())
+            }
+            Self::WriteConstraint(_, None) => Impact::Additive,
+            Self::Dim(Some(old), Some(new))
+                if new.dim > old.dim
+                    && new.dim_increment == old.dim_increment
+                    && old.dim_index.is_none()
+                    && new.dim_index.is_none() =>
+            {
+                Impact::Additive
+            }
+            Self::BaseAddress(_, _)
+            | Self::AddressOffset(_, _)
+            | Self::BitRange(_, _)
+            | Self::Size(_, _)
+            | Self::ResetValue(_, _)
+            | Self::ResetMask(_, _)
+            | Self::ModifiedWriteValues(_, _)
+            | Self::WriteConstraint(_, Some(_))
+            | Self::ReadAction(_, _)
+            | Self::Dim(_, _)
+            | Self::DerivedFrom(_, _)
+            | Self::AlternateRegister(_, _)
+            | Self::Value(_, _)
+            | Self::IsDefault(_, _) => Impact::Breaking,
+        }
+    }
+}
+
+/// Access change is breaking if any right is lost, additive if any right is gained
+fn access_impact(old: Access, new: Access) -> Impact {
+    fn write_once(a: Access) -> bool {
+        matches!(a, Access::WriteOnce | Access::ReadWriteOnce)
+    }
+    let narrowed = (old.can_read() && !new.can_read())
+        || (old.can_write() && !new.can_write())
+        || (!write_once(old) && write_once(new) && old.can_write());
+    if narrowed {
+        Impact::Breaking
+    } else if old == new {
+        Impact::Cosmetic
+    } else {
+        Impact::Additive
+    }
+}
+
+/// Compare two devices and return the list of changes needed to get `new` from `old`.
+///
+/// Changes are listed in the order of the old device, new elements follow
+/// the elements existing in both devices at the same level.
+pub fn diff(old: &Device, new: &Device) -> Vec<Change> {
+    let mut differ = Differ::default();
+    differ.peripherals(old, new);
+    differ.changes
+}
+
+impl Device {
+    /// Compare with a newer revision of the device. See [`diff`](crate::diff::diff).
+    pub fn diff(&self, new: &Device) -> Vec<Change> {
+        diff(self, new)
+    }
+}
+
+enum Pair<'a, T> {
+    Both(&'a T, &'a T),
+    Removed(&'a T),
+    Added(&'a T),
+}
+
+/// Match elements of two lists by key
+fn pair<'a, T, K, F>(old: &'a [T], new: &'a [T], key: F) -> Vec<Pair<'a, T>>
+where
+    K: Eq + Hash,
+    F: Fn(&'a T) -> K,
+{
+    let mut new_map: HashMap<K, &T> = new.iter().map(|n| (key(n), n)).collect();
+    let mut pairs = Vec::with_capacity(old.len().max(new.len()));
+    for o in old {
+        match new_map.remove(&key(o)) {
+            Some(n) => pairs.push(Pair::Both(o, n)),
+            None => pairs.push(Pair::Removed(o)),
+        }
+    }
+    for n in new {
+        if new_map.remove(&key(n)).is_some() {
+            pairs.push(Pair::Added(n));
+        }
+    }
+    pairs
+}
+
+fn join(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", parent, name)
+    }
+}
+
+#[derive(Default)]
+struct Differ {
+    changes: Vec<Change>,
+}
+
+impl Differ {
+    fn push(&mut self, element: ElementKind, path: String, kind: ChangeKind) {
+        self.changes.push(Change {
+            element,
+            path,
+            impact: kind.impact(),
+            kind,
+        });
+    }
+
+    fn cmp<T: PartialEq + Clone>(
+        &mut self,
+        element: ElementKind,
+        path: &str,
+        old: &T,
+        new: &T,
+        property: fn(T, T) -> Property,
+    ) {
+        if old != new {
+            self.push(
+                element,
+                path.to_string(),
+                ChangeKind::Modified(property(old.clone(), new.clone())),
+            );
+        }
+    }
+
+    fn peripherals(&mut self, old: &Device, new: &Device) {
+        use ElementKind::Peripheral as P;
+        for p in pair(&old.peripherals, &new.peripherals, |p| p.name.as_str()) {
+            match p {
+                Pair::Removed(o) => self.push(P, o.name.clone(), ChangeKind::Removed),
+                Pair::Added(n) => self.push(P, n.name.clone(), ChangeKind::Added),
+                Pair::Both(o, n) => {
+                    let path = o.name.as_str();
+                    self.cmp(
+                        P,
+                        path,
+                        &o.base_address,
+                        &n.base_address,
+                        Property::BaseAddress,
+                    );
+                    self.cmp(
+                        P,
+                        path,
+                        &peripheral_dim(o),
+                        &peripheral_dim(n),
+                        Property::Dim,
+                    );
+                    self.cmp(
+                        P,
+                        path,
+                        &o.derived_from,
+                        &n.derived_from,
+                        Property::DerivedFrom,
+                    );
+                    self.cmp(
+                        P,
+                        path,
+                        &o.description,
+                        &n.description,
+                        Property::Description,
+                    );
+                    let old_props = o
+                        .default_register_properties
+                        .inherit(&old.default_register_properties);
+                    let new_props = n
+                        .default_register_properties
+                        .inherit(&new.default_register_properties);
+                    self.children(
+                        path,
+                        o.registers.as_deref().unwrap_or(&[]),
+                        n.registers.as_deref().unwrap_or(&[]),
+                        &old_props,
+                        &new_props,
+                    );
+                }
+            }
+        }
+    }
+
+    fn children(
+        &mut self,
+        parent: &str,
+        old: &[RegisterCluster],
+        new: &[RegisterCluster],
+        old_props: &RegisterProperties,
+        new_props: &RegisterProperties,
+    ) {
+        fn key(rc: &RegisterCluster) -> (bool, &str) {
+            match rc {
+                RegisterCluster::Register(r) => (false, r.name.as_str()),
+                RegisterCluster::Cluster(c) => (true, c.name.as_str()),
+            }
+        }
+        fn kind_name(rc: &RegisterCluster) -> (ElementKind, &str) {
+            match rc {
+                RegisterCluster::Register(r) => (ElementKind::Register, r.name.as_str()),
+                RegisterCluster::Cluster(c) => (ElementKind::Cluster, c.name.as_str()),
+            }
+        }
+        for p in pair(old, new, key) {
+            match p {
+                Pair::Removed(o) => {
+                    let (kind, name) = kind_name(o);
+                    self.push(kind, join(parent, name), ChangeKind::Removed);
+                }
+                Pair::Added(n) => {
+                    let (kind, name) = kind_name(n);
+                    self.push(kind, join(parent, name), ChangeKind::Added);
+                }
+                Pair::Both(RegisterCluster::Register(o), RegisterCluster::Register(n)) => {
+                    self.register(parent, o, n, old_props, new_props)
+                }
+                Pair::Both(RegisterCluster::Cluster(o), RegisterCluster::Cluster(n)) => {
+                    self.cluster(parent, o, n, old_props, new_props)
+                }
+                Pair::Both(_, _) => unreachable!(),
+            }
+        }
+    }
+
+    fn cluster(
+        &mut self,
+        parent: &str,
+        o: &Cluster,
+        n: &Cluster,
+        old_props: &RegisterProperties,
+        new_props: &RegisterProperties,
+    ) {
+        use ElementKind::Cluster as C;
+        let path = join(parent, &o.name);
+        self.cmp(
+            C,
+            &path,
+            &o.address_offset,
+            &n.address_offset,
+            Property::AddressOffset,
+        );
+        self.cmp(C, &path, &cluster_dim(o), &cluster_dim(n), Property::Dim);
+        self.cmp(
+            C,
+            &path,
+            &o.derived_from,
+            &n.derived_from,
+            Property::DerivedFrom,
+        );
+        self.cmp(
+            C,
+            &path,
+            &o.description,
+            &n.description,
+            Property::Description,
+        );
+        let old_props = o.default_register_properties.inherit(old_props);
+        let new_props = n.default_register_properties.inherit(new_props);
+        self.children(&path, &o.children, &n.children, &old_props, &new_props);
+    }
+
+    fn register(
+        &mut self,
+        parent: &str,
+        o: &Register,
+        n: &Register,
+        old_props: &RegisterProperties,
+        new_props: &RegisterProperties,
+    ) {
+        use ElementKind::Register as R;
+        let path = join(parent, &o.name);
+        let old_props = o.properties.inherit(old_props);
+        let new_props = n.properties.inherit(new_props);
+        self.cmp(
+            R,
+            &path,
+            &o.address_offset,
+            &n.address_offset,
+            Property::AddressOffset,
+        );
+        self.cmp(R, &path, &register_dim(o), &register_dim(n), Property::Dim);
+        self.cmp(R, &path, &old_props.size, &new_props.size, Property::Size);
+        self.cmp(
+            R,
+            &path,
+            &old_props.access,
+            &new_props.access,
+            Property::Access,
+        );
+        self.cmp(
+            R,
+            &path,
+            &old_props.reset_value,
+            &new_props.reset_value,
+            Property::ResetValue,
+        );
+        self.cmp(
+            R,
+            &path,
+            &old_props.reset_mask,
+            &new_props.reset_mask,
+            Property::ResetMask,
+        );
+        self.cmp(
+            R,
+            &path,
+            &o.modified_write_values,
+            &n.modified_write_values,
+            Property::ModifiedWriteValues,
+        );
+        self.cmp(
+            R,
+            &path,
+            &o.write_constraint,
+            &n.write_constraint,
+            Property::WriteConstraint,
+        );
+        self.cmp(
+            R,
+            &path,
+            &o.read_action,
+            &n.read_action,
+            Property::ReadAction,
+        );
+        self.cmp(
+            R,
+            &path,
+            &o.alternate_register,
+            &n.alternate_register,
+            Property::AlternateRegister,
+        );
+        self.cmp(
+            R,
+            &path,
+            &o.derived_from,
+            &n.derived_from,
+            Property::DerivedFrom,
+        );
+        self.cmp(
+            R,
+            &path,
+            &o.description,
+            &n.description,
+            Property::Description,
+        );
+        let old_fields = o.fields.as_deref().unwrap_or(&[]);
+        let new_fields = n.fields.as_deref().unwrap_or(&[]);
+        for p in pair(old_fields, new_fields, |f| f.name.as_str()) {
+            match p {
+                Pair::Removed(o) => self.push(
+                    ElementKind::Field,
+                    join(&path, &o.name),
+                    ChangeKind::Removed,
+                ),
+                Pair::Added(n) => {
+                    self.push(ElementKind::Field, join(&path, &n.name), ChangeKind::Added)
+                }
+                Pair::Both(o, n) => self.field(&path, o, n, old_props.access, new_props.access),
+            }
+        }
+    }
+
+    fn field(
+        &mut self,
+        parent: &str,
+        o: &Field,
+        n: &Field,
+        old_access: Option<Access>,
+        new_access: Option<Access>,
+    ) {
+        use ElementKind::Field as F;
+        let path = join(parent, &o.name);
+        let old_range = (o.bit_range.offset, o.bit_range.width);
+        let new_range = (n.bit_range.offset, n.bit_range.width);
+        if old_range != new_range {
+            self.push(
+                F,
+                path.clone(),
+                ChangeKind::Modified(Property::BitRange(o.bit_range, n.bit_range)),
+            );
+        }
+        self.cmp(F, &path, &field_dim(o), &field_dim(n), Property::Dim);
+        self.cmp(
+            F,
+            &path,
+            &o.access.or(old_access),
+            &n.access.or(new_access),
+            Property::Access,
+        );
+        self.cmp(
+            F,
+            &path,
+            &o.modified_write_values,
+            &n.modified_write_values,
+            Property::ModifiedWriteValues,
+        );
+        self.cmp(
+            F,
+            &path,
+            &o.write_constraint,
+            &n.write_constraint,
+            Property::WriteConstraint,
+        );
+        self.cmp(
+            F,
+            &path,
+            &o.read_action,
+            &n.read_action,
+            Property::ReadAction,
+        );
+        self.cmp(
+            F,
+            &path,
+            &o.derived_from,
+            &n.derived_from,
+            Property::DerivedFrom,
+        );
+        self.cmp(
+            F,
+            &path,
+            &o.description,
+            &n.description,
+            Property::Description,
+        );
+
+        let old_values = enumerated_values(o);
+        let new_values = enumerated_values(n);
+        for p in pair(&old_values, &new_values, |(u, ev)| {
+            (u.as_str(), ev.name.as_str())
+        }) {
+            use ElementKind::EnumeratedValue as E;
+            let value_path =
+                |usage: &Usage, ev: &EnumeratedValue| join(&join(&path, usage.as_str()), &ev.name);
+            match p {
+                Pair::Removed((u, o)) => self.push(E, value_path(u, o), ChangeKind::Removed),
+                Pair::Added((u, n)) => self.push(E, value_path(u, n), ChangeKind::Added),
+                Pair::Both((u, o), (_, n)) => {
+                    let path = value_path(u, o);
+                    self.cmp(E, &path, &o.value, &n.value, Property::Value);
+                    self.cmp(E, &path, &o.is_default, &n.is_default, Property::IsDefault);
+                    self.cmp(
+                        E,
+                        &path,
+                        &o.description,
+                        &n.description,
+                        Property::Description,
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn enumerated_values(f: &Field) -> Vec<(Usage, &EnumeratedValue)> {
+    f.enumerated_values
+        .iter()
+        .flat_map(|evs| evs.values.iter().map(move |ev| (evs.usage(), ev)))
+        .collect()
+}
+
+fn peripheral_dim(p: &Peripheral) -> Option<Box<DimElement>> {
+    match p {
+        Peripheral::Single(_) => None,
+        Peripheral::Array(_, dim) => Some(Box::new(dim.clone())),
+    }
+}
+
+fn cluster_dim(c: &Cluster) -> Option<Box<DimElement>> {
+    match c {
+        Cluster::Single(_) => None,
+        Cluster::Array(_, dim) => Some(Box::new(dim.clone())),
+    }
+}
+
+fn register_dim(r: &Register) -> Option<Box<DimElement>> {
+    match r {
+        Register::Single(_) => None,
+        Register::Array(_, dim) => Some(Box::new(dim.clone())),
+    }
+}
+
+fn field_dim(f: &Field) -> Option<Box<DimElement>> {
+    match f {
+        Field::Single(_) => None,
+        Field::Array(_, dim) => Some(Box::new(dim.clone())),
+    }
+}
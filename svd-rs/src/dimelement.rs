@@ -64,6 +64,11 @@ pub struct DimArrayIndex {
 }
 
 /// Builder for [`DimElement`]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(default, rename_all = "camelCase")
+)]
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct DimElementBuilder {
     dim: Option<u32>,
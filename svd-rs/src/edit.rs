@@ -0,0 +1,337 @@
+//! Reference-aware editing of a [`Device`].
+//!
+//! Elements are addressed by their path, like `PERIPHERAL.CLUSTER.REGISTER.FIELD`,
+//! with array elements using their template names (`GPIO%s`). Renaming or moving an
+//! element rewrites every `derivedFrom`, `alternatePeripheral`, `alternateCluster` and
+//! `alternateRegister` pointing at the element or at one of its children.
+//!
+//! References without a `.` are resolved relative to the parent of the referencing
+//! element, references with a `.` are full paths. Relative references are kept relative
+//! when the target stays a sibling, otherwise they are replaced with the full path.
+
+use super::{
+    ClusterInfo, Device, EnumeratedValues, FieldInfo, PeripheralInfo, RegisterCluster,
+    RegisterInfo, SvdError,
+};
+
+/// Errors for [`Device::rename`] and [`Device::move_to`]
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    /// No element with the path
+    #[error("Element `{0}` not found")]
+    NotFound(String),
+    /// Element with the same path already exists
+    #[error("Element `{0}` already exists")]
+    AlreadyExists(String),
+    /// Element can't be moved to the parent
+    #[error("Element `{0}` can't be moved into `{1}`")]
+    CannotMove(String, String),
+    /// Moved element would start before its new parent
+    #[error("Element `{0}` is located before `{1}`")]
+    InvalidOffset(String, String),
+    /// Alternate reference of an element can't point to a non-sibling element
+    #[error("Reference `{1}` of `{0}` can't point to `{2}`")]
+    BrokenReference(String, String, String),
+}
+
+impl Device {
+    /// Rename the element at `path` and update all references to it.
+    ///
+    /// On error the device is left unchanged.
+    pub fn rename(&mut self, path: &str, name: &str) -> Result<(), SvdError> {
+        let new_path = join(parent_of(path), name);
+        let mut device = self.clone();
+        if find_mut(&mut device, path).is_none() {
+            return Err(Error::NotFound(path.to_string()).into());
+        }
+        if new_path == path {
+            return Ok(());
+        }
+        if find_mut(&mut device, &new_path).is_some() {
+            return Err(Error::AlreadyExists(new_path).into());
+        }
+        rewrite_references(&mut device, path, &new_path)?;
+        match find_mut(&mut device, path) {
+            Some(Node::Peripheral(p)) => {
+                super::check_dimable_name(name, "name")?;
+                p.name = name.to_string();
+            }
+            Some(Node::Cluster(c)) => {
+                super::check_dimable_name(name, "name")?;
+                c.name = name.to_string();
+            }
+            Some(Node::Register(r)) => {
+                super::check_dimable_name(name, "name")?;
+                r.name = name.to_string();
+            }
+            Some(Node::Field(f)) => {
+                super::check_dimable_name(name, "name")?;
+                f.name = name.to_string();
+            }
+            Some(Node::EnumeratedValues(e)) => {
+                super::check_name(name, "name")?;
+                e.name = Some(name.to_string());
+            }
+            None => unreachable!(),
+        }
+        *self = device;
+        Ok(())
+    }
+
+    /// Move the register or cluster at `path` into the peripheral or cluster at `parent`
+    /// and update all references to it.
+    ///
+    /// Both must belong to the same peripheral. The address offset of the element is
+    /// adjusted, so its address doesn't change. Elements can't be moved out of or into
+    /// cluster arrays, as this would change the number of instances.
+    ///
+    /// On error the device is left unchanged.
+    pub fn move_to(&mut self, path: &str, parent: &str) -> Result<(), SvdError> {
+        let cannot_move = || Error::CannotMove(path.to_string(), parent.to_string());
+        if !path.contains('.') {
+            return Err(cannot_move().into());
+        }
+        let name = last(path);
+        let new_path = join(parent, name);
+        let mut device = self.clone();
+
+        match find_mut(&mut device, path) {
+            Some(Node::Register(_)) | Some(Node::Cluster(_)) => {}
+            Some(_) => return Err(cannot_move().into()),
+            None => return Err(Error::NotFound(path.to_string()).into()),
+        }
+        match find_mut(&mut device, parent) {
+            Some(Node::Peripheral(_)) | Some(Node::Cluster(_)) => {}
+            Some(_) => return Err(cannot_move().into()),
+            None => return Err(Error::NotFound(parent.to_string()).into()),
+        }
+        if first(path) != first(parent) || is_under(parent, path) {
+            return Err(cannot_move().into());
+        }
+        if new_path == path {
+            return Ok(());
+        }
+        if find_mut(&mut device, &new_path).is_some() {
+            return Err(Error::AlreadyExists(new_path).into());
+        }
+
+        let old_base = cluster_offset(&device, parent_of(path)).ok_or_else(cannot_move)?;
+        let new_base = cluster_offset(&device, parent).ok_or_else(cannot_move)?;
+
+        rewrite_references(&mut device, path, &new_path)?;
+
+        let children = children_mut(&mut device, parent_of(path)).unwrap();
+        let idx = children.iter().position(|rc| rc_name(rc) == name).unwrap();
+        let mut element = children.remove(idx);
+        let offset = match &mut element {
+            RegisterCluster::Register(r) => &mut r.address_offset,
+            RegisterCluster::Cluster(c) => &mut c.address_offset,
+        };
+        let address = old_base + *offset as u64;
+        *offset = address
+            .checked_sub(new_base)
+            .ok_or_else(|| Error::InvalidOffset(path.to_string(), parent.to_string()))?
+            as u32;
+        children_mut(&mut device, parent).unwrap().push(element);
+
+        *self = device;
+        Ok(())
+    }
+}
+
+/// Replace `old` path or its prefix in all references of the device with `new`
+pub(crate) fn rewrite_references(device: &mut Device, old: &str, new: &str) -> Result<(), Error> {
+    let r = Rewrite { old, new };
+    for p in device.peripherals.iter_mut() {
+        let path = p.name.clone();
+        let info: &mut PeripheralInfo = p;
+        r.fix(&mut info.derived_from, &path, false)?;
+        r.fix(&mut info.alternate_peripheral, &path, true)?;
+        if let Some(children) = info.registers.as_mut() {
+            r.children(children, &path)?;
+        }
+    }
+    Ok(())
+}
+
+struct Rewrite<'a> {
+    old: &'a str,
+    new: &'a str,
+}
+
+impl<'a> Rewrite<'a> {
+    /// New path of the element at `path`
+    fn map(&self, path: &str) -> String {
+        if path == self.old {
+            self.new.to_string()
+        } else if is_under(path, self.old) {
+            format!("{}{}", self.new, &path[self.old.len()..])
+        } else {
+            path.to_string()
+        }
+    }
+
+    /// Rewrite the reference of the element at `path`.
+    /// `sibling` references can only point to elements with the same parent.
+    fn fix(&self, reference: &mut Option<String>, path: &str, sibling: bool) -> Result<(), Error> {
+        let r = match reference {
+            Some(r) => r,
+            None => return Ok(()),
+        };
+        let old_scope = parent_of(path);
+        let new_path = self.map(path);
+        let new_scope = parent_of(&new_path);
+        let relative = !r.contains('.');
+        let old_target = if relative {
+            join(old_scope, r)
+        } else {
+            r.clone()
+        };
+        let new_target = self.map(&old_target);
+        if new_target == old_target && new_scope == old_scope {
+            return Ok(());
+        }
+        if relative && parent_of(&new_target) == new_scope {
+            *r = last(&new_target).to_string();
+        } else if sibling {
+            return Err(Error::BrokenReference(new_path, r.clone(), new_target));
+        } else {
+            *r = new_target;
+        }
+        Ok(())
+    }
+
+    fn children(&self, children: &mut [RegisterCluster], parent: &str) -> Result<(), Error> {
+        for rc in children {
+            match rc {
+                RegisterCluster::Cluster(c) => {
+                    let path = join(parent, &c.name);
+                    self.fix(&mut c.derived_from, &path, false)?;
+                    self.fix(&mut c.alternate_cluster, &path, true)?;
+                    self.children(&mut c.children, &path)?;
+                }
+                RegisterCluster::Register(r) => {
+                    let path = join(parent, &r.name);
+                    self.fix(&mut r.derived_from, &path, false)?;
+                    self.fix(&mut r.alternate_register, &path, true)?;
+                    for f in r.fields.iter_mut().flatten() {
+                        let path = join(&path, &f.name);
+                        self.fix(&mut f.derived_from, &path, false)?;
+                        for e in f.enumerated_values.iter_mut() {
+                            let path = join(&path, e.name.as_deref().unwrap_or(""));
+                            self.fix(&mut e.derived_from, &path, false)?;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+enum Node<'a> {
+    Peripheral(&'a mut PeripheralInfo),
+    Cluster(&'a mut ClusterInfo),
+    Register(&'a mut RegisterInfo),
+    Field(&'a mut FieldInfo),
+    EnumeratedValues(&'a mut EnumeratedValues),
+}
+
+fn find_mut<'a>(device: &'a mut Device, path: &str) -> Option<Node<'a>> {
+    let mut segments = path.split('.');
+    let first = segments.next()?;
+    let p = device.peripherals.iter_mut().find(|p| p.name == first)?;
+    let mut node = Node::Peripheral(p);
+    for name in segments {
+        node = match node {
+            Node::Peripheral(p) => child(p.registers.as_mut()?, name)?,
+            Node::Cluster(c) => child(&mut c.children, name)?,
+            Node::Register(r) => {
+                Node::Field(r.fields.as_mut()?.iter_mut().find(|f| f.name == name)?)
+            }
+            Node::Field(f) => Node::EnumeratedValues(
+                f.enumerated_values
+                    .iter_mut()
+                    .find(|e| e.name.as_deref() == Some(name))?,
+            ),
+            Node::EnumeratedValues(_) => return None,
+        };
+    }
+    Some(node)
+}
+
+fn child<'a>(children: &'a mut [RegisterCluster], name: &str) -> Option<Node<'a>> {
+    children
+        .iter_mut()
+        .find(|rc| rc_name(rc) == name)
+        .map(|rc| match rc {
+            RegisterCluster::Cluster(c) => Node::Cluster(c),
+            RegisterCluster::Register(r) => Node::Register(r),
+        })
+}
+
+/// Children of the peripheral or cluster at `path`
+fn children_mut<'a>(device: &'a mut Device, path: &str) -> Option<&'a mut Vec<RegisterCluster>> {
+    match find_mut(device, path)? {
+        Node::Peripheral(p) => Some(p.registers.get_or_insert_with(Vec::new)),
+        Node::Cluster(c) => Some(&mut c.children),
+        _ => None,
+    }
+}
+
+/// Offset of the peripheral or cluster at `path` from the peripheral base address,
+/// `None` if any of the clusters is an array
+fn cluster_offset(device: &Device, path: &str) -> Option<u64> {
+    let mut names = path.split('.');
+    let first = names.next()?;
+    let p = device.peripherals.iter().find(|p| p.name == first)?;
+    let mut children = p.registers.as_deref().unwrap_or(&[]);
+    let mut offset = 0;
+    for name in names {
+        match children.iter().find(|rc| rc_name(rc) == name)? {
+            RegisterCluster::Cluster(c) if c.is_single() => {
+                offset += c.address_offset as u64;
+                children = &c.children;
+            }
+            _ => return None,
+        }
+    }
+    Some(offset)
+}
+
+fn rc_name(rc: &RegisterCluster) -> &str {
+    match rc {
+        RegisterCluster::Register(r) => &r.name,
+        RegisterCluster::Cluster(c) => &c.name,
+    }
+}
+
+fn join(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", parent, name)
+    }
+}
+
+fn parent_of(path: &str) -> &str {
+    match path.rfind('.') {
+        Some(idx) => &path[..idx],
+        None => "",
+    }
+}
+
+fn first(path: &str) -> &str {
+    path.split('.').next().unwrap_or("")
+}
+
+fn last(path: &str) -> &str {
+    path.rsplit('.').next().unwrap_or("")
+}
+
+/// Return `true` if `path` is a child of `ancestor` at any depth
+fn is_under(path: &str, ancestor: &str) -> bool {
+    path.len() > ancestor.len()
+        && path.starts_with(ancestor)
+        && path.as_bytes()[ancestor.len()] == b'.'
+}
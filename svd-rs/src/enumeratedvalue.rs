@@ -18,7 +18,11 @@ pub struct EnumeratedValue {
     /// Defines the constant for the bit-field as decimal, hexadecimal or binary number
     #[cfg_attr(
         feature = "serde",
-        serde(default, skip_serializing_if = "Option::is_none")
+        serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "crate::integer::deserialize_option"
+        )
     )]
     pub value: Option<u64>,
 
@@ -42,10 +46,19 @@ pub enum Error {
 }
 
 /// Builder for [`EnumeratedValue`]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(default, rename_all = "camelCase")
+)]
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct EnumeratedValueBuilder {
     name: Option<String>,
     description: Option<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "crate::integer::deserialize_option")
+    )]
     value: Option<u64>,
     is_default: Option<bool>,
 }
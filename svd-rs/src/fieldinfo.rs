@@ -1,6 +1,7 @@
 use super::{
-    bitrange, Access, BitRange, BuildError, DimElement, EmptyToNone, EnumeratedValues, Field,
-    ModifiedWriteValues, ReadAction, SvdError, Usage, ValidateLevel, WriteConstraint,
+    bitrange, writeconstraint, Access, BitRange, BuildError, DimElement, EmptyToNone,
+    EnumeratedValues, Field, ModifiedWriteValues, ReadAction, SvdError, Usage, ValidateLevel,
+    WriteConstraint,
 };
 
 /// Errors for [`FieldInfo::validate`]
@@ -79,11 +80,19 @@ pub struct FieldInfo {
 }
 
 /// Builder for [`FieldInfo`]
-
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(default, rename_all = "camelCase")
+)]
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct FieldInfoBuilder {
     name: Option<String>,
     description: Option<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(flatten, deserialize_with = "ser_de::deserialize_bit_range")
+    )]
     bit_range: Option<BitRange>,
     bit_offset: Option<u32>,
     bit_width: Option<u32>,
@@ -293,6 +302,17 @@ impl FieldInfo {
         }
 
         if lvl.is_strict() {
+            if let Some(WriteConstraint::Range(range)) = self.write_constraint {
+                let width = self.bit_range.width;
+                if range.min > range.max {
+                    return Err(writeconstraint::Error::EmptyRange(range.min, range.max).into());
+                }
+                if width < 64 && range.max >> width != 0 {
+                    return Err(
+                        writeconstraint::Error::RangeTooWide(range.min, range.max, width).into(),
+                    );
+                }
+            }
             match self.enumerated_values.as_slice() {
                 [] | [_] => {}
                 [ev1, ev2]
@@ -303,4 +323,97 @@ impl FieldInfo {
         }
         Ok(())
     }
+
+    /// Check if `value` can be written to the field.
+    ///
+    /// `access` is the effective access of the register, used if the field has none.
+    /// The value must fit in the field, and the field must not be read-only.
+    /// `WriteAsRead` constraint rejects all values, as the last read value is unknown.
+    pub fn is_write_allowed(
+        &self,
+        value: u64,
+        access: Option<Access>,
+    ) -> Result<(), writeconstraint::Error> {
+        if !self.access.or(access).unwrap_or_default().can_write() {
+            return Err(writeconstraint::Error::ReadOnly);
+        }
+        self.check_write(value, self.write_constraint)
+    }
+
+    /// Check `value` against the width of the field and `constraint`
+    pub(crate) fn check_write(
+        &self,
+        value: u64,
+        constraint: Option<WriteConstraint>,
+    ) -> Result<(), writeconstraint::Error> {
+        use writeconstraint::Error;
+        let width = self.bit_range.width;
+        if width < 64 && value >> width != 0 {
+            return Err(Error::TooWide(value, width));
+        }
+        match constraint {
+            Some(WriteConstraint::WriteAsRead(true)) => Err(Error::WriteAsRead),
+            Some(WriteConstraint::UseEnumeratedValues(true)) => {
+                if self
+                    .enumerated_values
+                    .iter()
+                    .filter(|e| e.usage().can_write())
+                    .flat_map(|e| e.values.iter())
+                    .any(|e| e.value == Some(value))
+                {
+                    Ok(())
+                } else {
+                    Err(Error::NotEnumerated(value))
+                }
+            }
+            Some(WriteConstraint::Range(range)) if value < range.min || value > range.max => {
+                Err(Error::OutOfRange(value, range.min, range.max))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+mod ser_de {
+    use super::*;
+    use serde::{Deserialize, Deserializer};
+
+    /// Bit range keys of a [`FieldInfoBuilder`], `bitOffset` and `bitWidth` are separate fields
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct BitRangeKeys {
+        #[serde(default)]
+        bit_range: Option<String>,
+        #[serde(default)]
+        lsb: Option<u32>,
+        #[serde(default)]
+        msb: Option<u32>,
+    }
+
+    pub(super) fn deserialize_bit_range<'de, D>(
+        deserializer: D,
+    ) -> Result<Option<BitRange>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match BitRangeKeys::deserialize(deserializer)? {
+            BitRangeKeys {
+                bit_range: Some(s), ..
+            } => BitRange::from_bit_range(&s)
+                .map(Some)
+                .ok_or_else(|| serde::de::Error::custom("Can't parse bitRange")),
+            BitRangeKeys {
+                lsb: Some(lsb),
+                msb: Some(msb),
+                ..
+            } => Ok(Some(BitRange::from_msb_lsb(msb, lsb))),
+            BitRangeKeys {
+                lsb: None,
+                msb: None,
+                ..
+            } => Ok(None),
+            _ => Err(serde::de::Error::custom("Both lsb and msb are required")),
+        }
+    }
 }
@@ -0,0 +1,101 @@
+//! YAML and TOML representations of [`Device`]s.
+//!
+//! They use the serde model of the `serde` feature, like JSON: elements are maps
+//! with the camelCase names of the SVD tags, arrays have their `dim` properties
+//! next to the properties of the element, and bit ranges are written as in the
+//! SVD, with `bitRange`, `bitOffset` and `bitWidth`, or `lsb` and `msb`.
+//!
+//! ```yaml
+//! name: DEV
+//! peripherals:
+//!   - name: UART0
+//!     baseAddress: 1073741824
+//!     registers:
+//!       - register:
+//!           name: CR
+//!           addressOffset: 0
+//!           fields:
+//!             - name: EN
+//!               bitOffset: 0
+//!               bitWidth: 1
+//! ```
+//!
+//! Integers may also be written as decimal, `0x` hexadecimal or `0b` binary
+//! strings. TOML can't represent integers above `i64::MAX`, so these values, like
+//! a 64-bit `resetMask` of all ones, are saved as hexadecimal strings.
+
+use super::{Device, SvdError};
+
+/// Errors of the conversions
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    /// YAML can't be converted
+    #[cfg(feature = "yaml")]
+    #[error("YAML error: {0}")]
+    Yaml(String),
+    /// TOML can't be converted
+    #[cfg(feature = "toml")]
+    #[error("TOML error: {0}")]
+    Toml(String),
+}
+
+impl Device {
+    /// Load a device from YAML
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml(yaml: &str) -> Result<Self, SvdError> {
+        serde_yaml::from_str(yaml).map_err(|e| Error::Yaml(e.to_string()).into())
+    }
+
+    /// Save the device as YAML
+    #[cfg(feature = "yaml")]
+    pub fn to_yaml(&self) -> Result<String, SvdError> {
+        serde_yaml::to_string(self).map_err(|e| Error::Yaml(e.to_string()).into())
+    }
+
+    /// Load a device from TOML
+    #[cfg(feature = "toml")]
+    pub fn from_toml(toml: &str) -> Result<Self, SvdError> {
+        toml_crate::from_str(toml).map_err(|e| Error::Toml(e.to_string()).into())
+    }
+
+    /// Save the device as TOML
+    #[cfg(feature = "toml")]
+    pub fn to_toml(&self) -> Result<String, SvdError> {
+        use serde::Deserialize;
+        // The TOML serializer doesn't support enums with data, like `RegisterCluster`,
+        // so the device is converted through a JSON value, whose enums are maps
+        // with a single key. Serializing a TOML `Value` puts the tables after
+        // the plain values of each table, as TOML requires.
+        let json = serde_json::to_value(self).map_err(|e| Error::Toml(e.to_string()))?;
+        let value = toml_crate::Value::deserialize(for_toml(json))
+            .map_err(|e| Error::Toml(e.to_string()))?;
+        toml_crate::to_string(&value).map_err(|e| Error::Toml(e.to_string()).into())
+    }
+}
+
+/// Remove the `null` values and write the integers above `i64::MAX` as hexadecimal
+/// strings, as TOML can't represent them
+#[cfg(feature = "toml")]
+fn for_toml(value: serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+    match value {
+        Value::Array(values) => Value::Array(
+            values
+                .into_iter()
+                .filter(|v| !v.is_null())
+                .map(for_toml)
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(k, v)| (k, for_toml(v)))
+                .collect(),
+        ),
+        Value::Number(n) if n.as_i64().is_none() => match n.as_u64() {
+            Some(n) => Value::String(format!("{:#x}", n)),
+            None => Value::Number(n),
+        },
+        value => value,
+    }
+}
@@ -20,6 +20,11 @@ pub struct Interrupt {
 }
 
 /// Builder for [`Interrupt`]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(default, rename_all = "camelCase")
+)]
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct InterruptBuilder {
     name: Option<String>,
@@ -0,0 +1,437 @@
+//! Struct layout of peripherals and clusters.
+//!
+//! [`PeripheralInfo::layout`] and [`ClusterInfo::layout`] order the registers and
+//! clusters by offset, as needed to generate `#[repr(C)]` or C structs:
+//!
+//! - gaps between members, and the padding at the end up to the alignment of the
+//!   struct, become [`MemberKind::Reserved`] members,
+//! - members at the same offset, like alternate registers, are grouped in a
+//!   [`MemberKind::Union`],
+//! - clusters are nested layouts, padded to `dimIncrement` for arrays,
+//! - register arrays are a single member with a count if `dimIncrement` is the
+//!   register size, and are expanded to one member per element otherwise.
+//!
+//! Registers are aligned to their size, rounded up to a power of two bytes, and
+//! clusters to the largest alignment of their members. Members which partially
+//! overlap and arrays with a `dimIncrement` smaller than their elements are errors,
+//! misalignments and undeclared alternates are reported as [`Diagnostic`]s.
+
+use super::{
+    Cluster, ClusterInfo, PeripheralInfo, Register, RegisterCluster, RegisterProperties, SvdError,
+};
+
+/// Errors of a layout computation
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    /// A member starts inside of the previous member
+    #[error("`{1}` overlaps `{0}`")]
+    Overlap(String, String),
+    /// Elements of an array are larger than `dimIncrement`
+    #[error("`{0}` has a dimIncrement of {1} bytes but elements of {2} bytes")]
+    IncrementTooSmall(String, u32, u64),
+}
+
+/// Issues of a layout which don't prevent its computation
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Diagnostic {
+    /// Offset of the member is not a multiple of its alignment
+    Misaligned {
+        /// Path of the member, relative to the layout
+        path: String,
+        /// Offset of the member in its parent
+        offset: u64,
+        /// Alignment of the member
+        alignment: u64,
+    },
+    /// `dimIncrement` of an array is not a multiple of the alignment of its elements
+    UnalignedIncrement {
+        /// Path of the array, relative to the layout
+        path: String,
+        /// `dimIncrement` of the array
+        increment: u32,
+        /// Alignment of the elements
+        alignment: u64,
+    },
+    /// Members at the same offset which don't declare an alternate
+    UndeclaredAlternate {
+        /// Path of the member, relative to the layout
+        path: String,
+        /// Name of the first member at the offset
+        other: String,
+    },
+}
+
+/// Ordered members of a struct
+#[derive(Clone, Debug, PartialEq)]
+pub struct Layout<'a> {
+    /// Members by offset, including reserved gaps
+    pub members: Vec<Member<'a>>,
+    /// Size in bytes, up to the end of the last member rounded up to `alignment`,
+    /// or to `dimIncrement` of cluster arrays
+    pub size: u64,
+    /// Largest alignment of the members, 1 if there are none
+    pub alignment: u64,
+    /// Diagnostics of the members, including the members of nested clusters
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Member of a [`Layout`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Member<'a> {
+    /// Offset in bytes from the start of the struct
+    pub offset: u64,
+    /// Size in bytes, of all elements for arrays
+    pub size: u64,
+    /// The content of the member
+    pub kind: MemberKind<'a>,
+}
+
+/// Content of a [`Member`]
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum MemberKind<'a> {
+    /// A register, an array of registers, or an element of an expanded register array
+    Register {
+        /// Member name, without the `%s` placeholder of arrays
+        name: String,
+        /// The register
+        register: &'a Register,
+        /// Effective properties, including the inherited ones
+        properties: RegisterProperties,
+        /// Number of elements if the member is an array
+        count: Option<u32>,
+    },
+    /// A cluster or an array of clusters
+    Cluster {
+        /// Member name, without the `%s` placeholder of arrays
+        name: String,
+        /// The cluster
+        cluster: &'a Cluster,
+        /// Layout of an element
+        layout: Layout<'a>,
+        /// Number of elements if the member is an array
+        count: Option<u32>,
+    },
+    /// Members at the same offset
+    Union(Vec<Member<'a>>),
+    /// Gap between members
+    Reserved,
+}
+
+impl<'a> Member<'a> {
+    /// Name of a register or cluster member
+    pub fn name(&self) -> Option<&str> {
+        match &self.kind {
+            MemberKind::Register { name, .. } | MemberKind::Cluster { name, .. } => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Alignment of the member
+    pub fn alignment(&self) -> u64 {
+        match &self.kind {
+            MemberKind::Register { properties, .. } => alignment(properties),
+            MemberKind::Cluster { layout, .. } => layout.alignment,
+            MemberKind::Union(members) => members.iter().map(Member::alignment).max().unwrap_or(1),
+            MemberKind::Reserved => 1,
+        }
+    }
+
+    fn declares_alternate(&self) -> bool {
+        match &self.kind {
+            MemberKind::Register { register, .. } => {
+                register.alternate_group.is_some() || register.alternate_register.is_some()
+            }
+            MemberKind::Cluster { cluster, .. } => cluster.alternate_cluster.is_some(),
+            _ => false,
+        }
+    }
+}
+
+impl PeripheralInfo {
+    /// Compute the struct layout of the registers and clusters of the peripheral.
+    ///
+    /// `defaults` are the default register properties of the device.
+    pub fn layout(&self, defaults: &RegisterProperties) -> Result<Layout, SvdError> {
+        let properties = self.default_register_properties.inherit(defaults);
+        let children = self.registers.as_deref().unwrap_or(&[]);
+        Ok(layout(children, &properties, None)?)
+    }
+}
+
+impl ClusterInfo {
+    /// Compute the struct layout of an element of the cluster.
+    ///
+    /// `defaults` are the default register properties of the parent.
+    pub fn layout(&self, defaults: &RegisterProperties) -> Result<Layout, SvdError> {
+        let properties = self.default_register_properties.inherit(defaults);
+        Ok(layout(&self.children, &properties, None)?)
+    }
+}
+
+/// Size in bytes of a register
+fn bytes(properties: &RegisterProperties) -> u64 {
+    (properties.size.unwrap_or(32).max(1) as u64 - 1) / 8 + 1
+}
+
+fn alignment(properties: &RegisterProperties) -> u64 {
+    bytes(properties).next_power_of_two()
+}
+
+/// Return `true` if `value` is a multiple of the power of two `alignment`
+fn aligned(value: u64, alignment: u64) -> bool {
+    value & (alignment - 1) == 0
+}
+
+fn strip(name: &str) -> String {
+    name.replace("[%s]", "").replace("%s", "")
+}
+
+fn layout<'a>(
+    children: &'a [RegisterCluster],
+    properties: &RegisterProperties,
+    pad_to: Option<u64>,
+) -> Result<Layout<'a>, Error> {
+    let mut diagnostics = Vec::new();
+    let mut items = Vec::new();
+    for rc in children {
+        match rc {
+            RegisterCluster::Register(r) => {
+                register(&mut items, &mut diagnostics, r, properties)?;
+            }
+            RegisterCluster::Cluster(c) => {
+                cluster(&mut items, &mut diagnostics, c, properties)?;
+            }
+        }
+    }
+    items.sort_by_key(|m: &Member| m.offset);
+
+    let mut members = Vec::new();
+    let mut pos = 0;
+    let mut previous = String::new();
+    let mut items = items.into_iter().peekable();
+    while let Some(first) = items.next() {
+        let offset = first.offset;
+        let name = first.name().unwrap_or_default().to_string();
+        if offset < pos {
+            return Err(Error::Overlap(previous, name));
+        }
+        if offset > pos {
+            members.push(Member {
+                offset: pos,
+                size: offset - pos,
+                kind: MemberKind::Reserved,
+            });
+        }
+        let mut group = vec![first];
+        while items.peek().map(|m| m.offset) == Some(offset) {
+            let m = items.next().unwrap();
+            if !group[0].declares_alternate() && !m.declares_alternate() {
+                diagnostics.push(Diagnostic::UndeclaredAlternate {
+                    path: m.name().unwrap_or_default().to_string(),
+                    other: name.clone(),
+                });
+            }
+            group.push(m);
+        }
+        let size = group.iter().map(|m| m.size).max().unwrap_or(0);
+        pos = offset + size;
+        previous = name;
+        if group.len() == 1 {
+            members.extend(group);
+        } else {
+            members.push(Member {
+                offset,
+                size,
+                kind: MemberKind::Union(group),
+            });
+        }
+    }
+    if let Some(pad_to) = pad_to {
+        if pad_to > pos {
+            members.push(Member {
+                offset: pos,
+                size: pad_to - pos,
+                kind: MemberKind::Reserved,
+            });
+            pos = pad_to;
+        }
+    }
+    let alignment = members.iter().map(Member::alignment).max().unwrap_or(1);
+    // Tail padding, like the size of a C struct
+    if pad_to.is_none() && !aligned(pos, alignment) {
+        let end = (pos | (alignment - 1)) + 1;
+        members.push(Member {
+            offset: pos,
+            size: end - pos,
+            kind: MemberKind::Reserved,
+        });
+        pos = end;
+    }
+    Ok(Layout {
+        members,
+        size: pos,
+        alignment,
+        diagnostics,
+    })
+}
+
+fn check_alignment(diagnostics: &mut Vec<Diagnostic>, path: &str, offset: u64, alignment: u64) {
+    if !aligned(offset, alignment) {
+        diagnostics.push(Diagnostic::Misaligned {
+            path: path.to_string(),
+            offset,
+            alignment,
+        });
+    }
+}
+
+fn register<'a>(
+    items: &mut Vec<Member<'a>>,
+    diagnostics: &mut Vec<Diagnostic>,
+    r: &'a Register,
+    properties: &RegisterProperties,
+) -> Result<(), Error> {
+    let properties = r.properties.inherit(properties);
+    let size = bytes(&properties);
+    let alignment = alignment(&properties);
+    let offset = r.address_offset as u64;
+    let name = strip(&r.name);
+    check_alignment(diagnostics, &name, offset, alignment);
+    match r {
+        Register::Single(_) => items.push(Member {
+            offset,
+            size,
+            kind: MemberKind::Register {
+                name,
+                register: r,
+                properties,
+                count: None,
+            },
+        }),
+        Register::Array(_, dim) => {
+            let increment = dim.dim_increment as u64;
+            if increment < size {
+                return Err(Error::IncrementTooSmall(name, dim.dim_increment, size));
+            }
+            if !aligned(increment, alignment) {
+                diagnostics.push(Diagnostic::UnalignedIncrement {
+                    path: name.clone(),
+                    increment: dim.dim_increment,
+                    alignment,
+                });
+            }
+            if increment == size {
+                items.push(Member {
+                    offset,
+                    size: size * dim.dim as u64,
+                    kind: MemberKind::Register {
+                        name,
+                        register: r,
+                        properties,
+                        count: Some(dim.dim),
+                    },
+                });
+            } else {
+                for (i, idx) in dim.indexes().enumerate() {
+                    items.push(Member {
+                        offset: offset + i as u64 * increment,
+                        size,
+                        kind: MemberKind::Register {
+                            name: r.name.replace("[%s]", &idx).replace("%s", &idx),
+                            register: r,
+                            properties,
+                            count: None,
+                        },
+                    });
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn cluster<'a>(
+    items: &mut Vec<Member<'a>>,
+    diagnostics: &mut Vec<Diagnostic>,
+    c: &'a Cluster,
+    properties: &RegisterProperties,
+) -> Result<(), Error> {
+    let properties = c.default_register_properties.inherit(properties);
+    let name = strip(&c.name);
+    let offset = c.address_offset as u64;
+    let nested = |e| match e {
+        Error::Overlap(a, b) => {
+            Error::Overlap(format!("{}.{}", name, a), format!("{}.{}", name, b))
+        }
+        Error::IncrementTooSmall(a, i, s) => {
+            Error::IncrementTooSmall(format!("{}.{}", name, a), i, s)
+        }
+    };
+    let (layout, count) = match c {
+        Cluster::Single(_) => (
+            layout(&c.children, &properties, None).map_err(nested)?,
+            None,
+        ),
+        Cluster::Array(_, dim) => {
+            let layout =
+                layout(&c.children, &properties, Some(dim.dim_increment as u64)).map_err(nested)?;
+            if layout.size > dim.dim_increment as u64 {
+                return Err(Error::IncrementTooSmall(
+                    name,
+                    dim.dim_increment,
+                    layout.size,
+                ));
+            }
+            if !aligned(dim.dim_increment as u64, layout.alignment) {
+                diagnostics.push(Diagnostic::UnalignedIncrement {
+                    path: name.clone(),
+                    increment: dim.dim_increment,
+                    alignment: layout.alignment,
+                });
+            }
+            (layout, Some(dim.dim))
+        }
+    };
+    check_alignment(diagnostics, &name, offset, layout.alignment);
+    diagnostics.extend(layout.diagnostics.iter().cloned().map(|d| match d {
+        Diagnostic::Misaligned {
+            path,
+            offset,
+            alignment,
+        } => Diagnostic::Misaligned {
+            path: format!("{}.{}", name, path),
+            offset,
+            alignment,
+        },
+        Diagnostic::UnalignedIncrement {
+            path,
+            increment,
+            alignment,
+        } => Diagnostic::UnalignedIncrement {
+            path: format!("{}.{}", name, path),
+            increment,
+            alignment,
+        },
+        Diagnostic::UndeclaredAlternate { path, other } => Diagnostic::UndeclaredAlternate {
+            path: format!("{}.{}", name, path),
+            other,
+        },
+    }));
+    let size = match count {
+        Some(count) => layout.size * count as u64,
+        None => layout.size,
+    };
+    items.push(Member {
+        offset,
+        size,
+        kind: MemberKind::Cluster {
+            name,
+            cluster: c,
+            layout,
+            count,
+        },
+    });
+    Ok(())
+}
@@ -102,6 +102,48 @@ pub use self::readaction::ReadAction;
 pub mod protection;
 pub use self::protection::Protection;
 
+/// Device comparison
+pub mod diff;
+
+/// Device merging
+pub mod merge;
+
+/// Device traversal
+pub mod visit;
+
+/// Glob queries
+pub mod query;
+
+/// Reference-aware editing
+pub mod edit;
+
+/// Indexed device view
+pub mod view;
+
+/// Reverse address lookup
+pub mod addressindex;
+
+/// Register values
+pub mod value;
+
+/// Read-modify-write masks
+pub mod rmw;
+
+/// Struct layout
+pub mod layout;
+
+/// Device patches
+#[cfg(feature = "serde")]
+pub mod patch;
+
+/// YAML and TOML formats
+#[cfg(any(feature = "yaml", feature = "toml"))]
+pub mod format;
+
+/// JSON Schema of the serde format
+#[cfg(feature = "schema")]
+pub mod schema;
+
 /// Level of validation
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ValidateLevel {
@@ -169,6 +211,9 @@ pub enum SvdError {
     /// BitRange error
     #[error("`BitRange error: {0}")]
     BitRange(#[from] bitrange::Error),
+    /// WriteConstraint error
+    #[error("`WriteConstraint error: {0}")]
+    WriteConstraint(#[from] writeconstraint::Error),
     /// EnumeratedValue error
     #[error("`EnumeratedValue error: {0}")]
     EnumeratedValue(#[from] enumeratedvalue::Error),
@@ -178,6 +223,29 @@ pub enum SvdError {
     /// RegisterProperties error
     #[error("`RegisterProperties error: {0}")]
     RegisterProperties(#[from] registerproperties::Error),
+    /// Merge error
+    #[error("`Merge error: {0}")]
+    Merge(#[from] merge::Error),
+    /// Query error
+    #[error("`Query error: {0}")]
+    Query(#[from] query::Error),
+    /// Edit error
+    #[error("`Edit error: {0}")]
+    Edit(#[from] edit::Error),
+    /// Value error
+    #[error("`Value error: {0}")]
+    Value(#[from] value::Error),
+    /// Layout error
+    #[error("`Layout error: {0}")]
+    Layout(#[from] layout::Error),
+    /// Patch error
+    #[cfg(feature = "serde")]
+    #[error("`Patch error: {0}")]
+    Patch(#[from] patch::Error),
+    /// Format error
+    #[cfg(any(feature = "yaml", feature = "toml"))]
+    #[error("`Format error: {0}")]
+    Format(#[from] format::Error),
 }
 
 /// Errors from a builder
@@ -196,6 +264,17 @@ pub enum NameError {
     Invalid(String, String),
 }
 
+/// Shift `value` left by `shift` bits, giving 0 for shifts of 64 bits or more,
+/// like the bit offset of a field beyond a 64-bit register
+pub fn checked_shl(value: u64, shift: u32) -> u64 {
+    value.checked_shl(shift).unwrap_or(0)
+}
+
+/// Shift `value` right by `shift` bits, giving 0 for shifts of 64 bits or more
+pub fn checked_shr(value: u64, shift: u32) -> u64 {
+    value.checked_shr(shift).unwrap_or(0)
+}
+
 pub(crate) fn check_name(name: &str, tag: &str) -> Result<(), NameError> {
     static PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new("^[_A-Za-z0-9]*$").unwrap());
     if PATTERN.is_match(name) {
@@ -239,6 +318,70 @@ impl<T> EmptyToNone for Option<Vec<T>> {
     }
 }
 
+/// Integers of the serde model may also be given as decimal, `0x` hexadecimal
+/// or `0b` binary strings, as formats like TOML can't represent all `u64` values.
+#[cfg(feature = "serde")]
+mod integer {
+    use serde::de::{Error, Unexpected, Visitor};
+    use serde::{Deserialize, Deserializer};
+    use std::fmt;
+
+    struct Integer(u64);
+
+    impl<'de> Deserialize<'de> for Integer {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_any(IntegerVisitor)
+        }
+    }
+
+    struct IntegerVisitor;
+
+    impl<'de> Visitor<'de> for IntegerVisitor {
+        type Value = Integer;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("an unsigned integer or a string with one")
+        }
+
+        fn visit_u64<E: Error>(self, value: u64) -> Result<Integer, E> {
+            Ok(Integer(value))
+        }
+
+        fn visit_i64<E: Error>(self, value: i64) -> Result<Integer, E> {
+            if value < 0 {
+                Err(E::invalid_value(Unexpected::Signed(value), &self))
+            } else {
+                Ok(Integer(value as u64))
+            }
+        }
+
+        fn visit_str<E: Error>(self, value: &str) -> Result<Integer, E> {
+            let text = value.trim();
+            let lower = text.to_lowercase();
+            let parsed = if lower.starts_with("0x") {
+                u64::from_str_radix(&text[2..], 16)
+            } else if lower.starts_with("0b") {
+                u64::from_str_radix(&text[2..], 2)
+            } else {
+                text.parse()
+            };
+            parsed
+                .map(Integer)
+                .map_err(|_| E::invalid_value(Unexpected::Str(value), &self))
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        Integer::deserialize(deserializer).map(|i| i.0)
+    }
+
+    pub(crate) fn deserialize_option<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<u64>, D::Error> {
+        Option::<Integer>::deserialize(deserializer).map(|i| i.map(|i| i.0))
+    }
+}
+
 #[cfg(feature = "serde")]
 #[derive(serde::Serialize)]
 struct SerArray<'a, T> {
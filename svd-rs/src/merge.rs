@@ -0,0 +1,295 @@
+//! Merging of several [`Device`]s into one.
+//!
+//! Typical uses are combining a CMSIS core description (NVIC, SCB, SysTick)
+//! with a vendor device description, or stitching together descriptions of
+//! separate subsystems.
+
+use core::ops::Range;
+
+use super::{Device, Interrupt, Peripheral, RegisterProperties, SvdError};
+
+/// Errors for [`Device::merge`]
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    /// Both devices contain a peripheral with the same name
+    #[error("Peripheral `{0}` is present in both devices")]
+    DuplicatePeripheral(String),
+    /// Address blocks of two peripherals overlap
+    #[error("Peripheral `{0}` at 0x{1:x} overlaps with peripheral `{2}` at 0x{3:x}")]
+    AddressOverlap(String, u64, String, u64),
+    /// Interrupts have the same number but different names, or the same name but different numbers
+    #[error("Interrupt `{0}` ({1}) clashes with interrupt `{2}` ({3})")]
+    InterruptClash(String, u32, String, u32),
+}
+
+/// What to do with a peripheral whose name is already used in the device
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PeripheralPolicy {
+    /// Return an error
+    Error,
+    /// Keep the existing peripheral, merge the interrupts of the new one into it
+    KeepExisting,
+    /// Replace the existing peripheral, merge its interrupts into the new one
+    Replace,
+}
+
+/// What to do with a peripheral whose address blocks overlap with an existing peripheral
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressPolicy {
+    /// Return an error
+    Error,
+    /// Add the peripheral anyway
+    Allow,
+}
+
+/// What to do with an interrupt which clashes with an existing one
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InterruptPolicy {
+    /// Return an error
+    Error,
+    /// Drop the new interrupt
+    KeepExisting,
+    /// Remove the existing interrupt
+    Replace,
+    /// Keep both interrupts
+    Allow,
+}
+
+/// Conflict policies for [`Device::merge`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Config {
+    /// Policy for peripherals with duplicate names
+    pub peripheral: PeripheralPolicy,
+    /// Policy for peripherals with overlapping address blocks
+    pub address: AddressPolicy,
+    /// Policy for interrupt number clashes
+    pub interrupt: InterruptPolicy,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            peripheral: PeripheralPolicy::Error,
+            address: AddressPolicy::Error,
+            interrupt: InterruptPolicy::Error,
+        }
+    }
+}
+
+impl Config {
+    /// Set the policy for peripherals with duplicate names
+    pub fn peripheral(mut self, value: PeripheralPolicy) -> Self {
+        self.peripheral = value;
+        self
+    }
+    /// Set the policy for peripherals with overlapping address blocks
+    pub fn address(mut self, value: AddressPolicy) -> Self {
+        self.address = value;
+        self
+    }
+    /// Set the policy for interrupt number clashes
+    pub fn interrupt(mut self, value: InterruptPolicy) -> Self {
+        self.interrupt = value;
+        self
+    }
+}
+
+impl Device {
+    /// Merge peripherals of `other` into the device.
+    ///
+    /// Name, version, description and default register properties of the device are kept,
+    /// `cpu`, `address_unit_bits` and `width` are taken from `other` if missing.
+    /// Default register properties of `other` which differ from the device ones are moved
+    /// to its peripherals, and device defaults which `other` doesn't set are moved to the
+    /// peripherals of the device, so registers keep their effective properties.
+    ///
+    /// On error the device is left unchanged.
+    pub fn merge(&mut self, other: Device, config: &Config) -> Result<(), SvdError> {
+        let (defaults, own, inherited) = split_properties(
+            &self.default_register_properties,
+            &other.default_register_properties,
+        );
+        let mut peripherals = self.peripherals.clone();
+        for p in &mut peripherals {
+            p.default_register_properties = p.default_register_properties.inherit(&own);
+        }
+
+        for mut p in other.peripherals {
+            p.default_register_properties = p.default_register_properties.inherit(&inherited);
+            let interrupts = core::mem::take(&mut p.interrupt);
+            let target = match peripherals.iter().position(|e| e.name == p.name) {
+                Some(idx) => match config.peripheral {
+                    PeripheralPolicy::Error => {
+                        return Err(Error::DuplicatePeripheral(p.name.clone()).into())
+                    }
+                    PeripheralPolicy::KeepExisting => idx,
+                    PeripheralPolicy::Replace => {
+                        p.interrupt = core::mem::take(&mut peripherals[idx].interrupt);
+                        peripherals[idx] = p;
+                        check_overlap(&peripherals, idx, config)?;
+                        idx
+                    }
+                },
+                None => {
+                    peripherals.push(p);
+                    let idx = peripherals.len() - 1;
+                    check_overlap(&peripherals, idx, config)?;
+                    idx
+                }
+            };
+            add_interrupts(&mut peripherals, target, interrupts, config.interrupt)?;
+        }
+
+        self.peripherals = peripherals;
+        self.default_register_properties = defaults;
+        self.cpu = self.cpu.take().or(other.cpu);
+        self.address_unit_bits = self.address_unit_bits.or(other.address_unit_bits);
+        self.width = self.width.or(other.width);
+        Ok(())
+    }
+}
+
+/// Split the default properties of the devices into the defaults of the merged device,
+/// the properties to set on the peripherals of the device and the ones to set on
+/// the peripherals of `other`
+fn split_properties(
+    own: &RegisterProperties,
+    other: &RegisterProperties,
+) -> (RegisterProperties, RegisterProperties, RegisterProperties) {
+    fn split<T: PartialEq + Copy>(
+        own: Option<T>,
+        other: Option<T>,
+    ) -> (Option<T>, Option<T>, Option<T>) {
+        match (own, other) {
+            (own, other) if own == other => (own, None, None),
+            // Peripherals of `other` must not inherit the default of the device
+            (Some(own), None) => (None, Some(own), None),
+            (own, other) => (own, None, other),
+        }
+    }
+    let size = split(own.size, other.size);
+    let access = split(own.access, other.access);
+    let protection = split(own.protection, other.protection);
+    let reset_value = split(own.reset_value, other.reset_value);
+    let reset_mask = split(own.reset_mask, other.reset_mask);
+    (
+        RegisterProperties::new()
+            .size(size.0)
+            .access(access.0)
+            .protection(protection.0)
+            .reset_value(reset_value.0)
+            .reset_mask(reset_mask.0),
+        RegisterProperties::new()
+            .size(size.1)
+            .access(access.1)
+            .protection(protection.1)
+            .reset_value(reset_value.1)
+            .reset_mask(reset_mask.1),
+        RegisterProperties::new()
+            .size(size.2)
+            .access(access.2)
+            .protection(protection.2)
+            .reset_value(reset_value.2)
+            .reset_mask(reset_mask.2),
+    )
+}
+
+/// Address ranges of all instances of the peripheral
+fn address_ranges(p: &Peripheral, peripherals: &[Peripheral]) -> Vec<Range<u64>> {
+    let blocks = p.address_block.as_ref().or_else(|| {
+        p.derived_from.as_ref().and_then(|base| {
+            peripherals
+                .iter()
+                .find(|d| &d.name == base)
+                .and_then(|d| d.address_block.as_ref())
+        })
+    });
+    let blocks = match blocks {
+        Some(blocks) => blocks,
+        None => return Vec::new(),
+    };
+    let bases: Vec<u64> = match p {
+        Peripheral::Single(info) => vec![info.base_address],
+        Peripheral::Array(info, dim) => (0..dim.dim as u64)
+            .map(|i| info.base_address + i * dim.dim_increment as u64)
+            .collect(),
+    };
+    bases
+        .iter()
+        .flat_map(|base| {
+            blocks.iter().map(move |b| {
+                let start = base + b.offset as u64;
+                start..start + b.size as u64
+            })
+        })
+        .collect()
+}
+
+fn are_alternates(a: &Peripheral, b: &Peripheral) -> bool {
+    a.alternate_peripheral.as_deref() == Some(b.name.as_str())
+        || b.alternate_peripheral.as_deref() == Some(a.name.as_str())
+}
+
+/// Check the peripheral at `idx` against the other peripherals, including the ones
+/// merged before it
+fn check_overlap(peripherals: &[Peripheral], idx: usize, config: &Config) -> Result<(), Error> {
+    if config.address == AddressPolicy::Allow {
+        return Ok(());
+    }
+    let p = &peripherals[idx];
+    let ranges = address_ranges(p, peripherals);
+    for (i, e) in peripherals.iter().enumerate() {
+        if i == idx || are_alternates(p, e) {
+            continue;
+        }
+        for r in address_ranges(e, peripherals) {
+            if ranges.iter().any(|n| n.start < r.end && r.start < n.end) {
+                return Err(Error::AddressOverlap(
+                    p.name.clone(),
+                    p.base_address,
+                    e.name.clone(),
+                    e.base_address,
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn clashes(a: &Interrupt, b: &Interrupt) -> bool {
+    (a.name == b.name) != (a.value == b.value)
+}
+
+/// Add interrupts to the peripheral at `target`, resolving clashes with the whole device
+fn add_interrupts(
+    peripherals: &mut [Peripheral],
+    target: usize,
+    interrupts: Vec<Interrupt>,
+    policy: InterruptPolicy,
+) -> Result<(), Error> {
+    'incoming: for i in interrupts {
+        if peripherals[target].interrupt.contains(&i) {
+            continue;
+        }
+        for p in peripherals.iter_mut() {
+            if let Some(e) = p.interrupt.iter().find(|e| clashes(e, &i)) {
+                match policy {
+                    InterruptPolicy::Error => {
+                        return Err(Error::InterruptClash(
+                            i.name,
+                            i.value,
+                            e.name.clone(),
+                            e.value,
+                        ))
+                    }
+                    InterruptPolicy::KeepExisting => continue 'incoming,
+                    InterruptPolicy::Replace => p.interrupt.retain(|e| !clashes(e, &i)),
+                    InterruptPolicy::Allow => {}
+                }
+            }
+        }
+        peripherals[target].interrupt.push(i);
+    }
+    Ok(())
+}
@@ -0,0 +1,901 @@
+//! Declarative patches for [`Device`]s.
+//!
+//! A patch is a list of [`Operation`]s, usually deserialized from JSON or YAML:
+//!
+//! ```json
+//! [
+//!   { "modify": { "register": "GPIO*.MODER", "with": { "access": "read-write" } } },
+//!   { "rename": { "field": "TIM[1-8].CR1.CEN", "to": "EN" } },
+//!   { "delete": { "register": "TIM1.{ISR,ICR}" } },
+//!   { "copy": { "peripheral": "UART0", "name": "UART2" } },
+//!   { "derive": { "peripheral": "UART2", "from": "UART0" } },
+//!   { "add": { "parent": "TIM1.CR1.MODE", "enumeratedValues": { "values": [] } } },
+//!   { "add": { "parent": "TIM1.CR1.DIR.read-write", "enumeratedValue": { "name": "UP", "value": 0 } } },
+//!   { "modify": { "enumeratedValue": "TIM1.CR1.DIR.*.DOWN", "with": { "value": 1 } } }
+//! ]
+//! ```
+//!
+//! Targets are selected with [`query`](crate::query) glob patterns, matched against
+//! element names as written in the device (`GPIO%s` for arrays, without expansion).
+//! The last segment selects the elements, the other segments select their parents.
+//! Unnamed enumerated values containers have an empty name. Single enumerated values
+//! are selected through the usage of their container, like `FIELD.read-write.VALUE`,
+//! so a value can be added to an existing list without restating it.
+//!
+//! Integers may also be given as decimal, `0x` hexadecimal or `0b` binary strings,
+//! like in the YAML and TOML formats, for values above `i64::MAX` in TOML.
+//!
+//! Modifications are applied through the `modify_from` method of each element.
+//! Renaming updates references to the renamed elements, see [`edit`](crate::edit).
+
+use super::{
+    diff::ElementKind, edit, query::Query, Access, Cluster, ClusterInfo, Device, EnumeratedValue,
+    EnumeratedValues, Field, ModifiedWriteValues, Peripheral, PeripheralInfo, Protection,
+    ReadAction, Register, RegisterCluster, RegisterInfo, RegisterProperties, SvdError, Usage,
+    ValidateLevel, WriteConstraint,
+};
+
+/// Errors for [`Device::patch`]
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    /// Pattern doesn't select any element
+    #[error("Pattern `{0}` doesn't match anything")]
+    NoMatch(String),
+    /// Element with the same name already exists
+    #[error("Element `{0}` already exists")]
+    AlreadyExists(String),
+    /// Property can't be modified on the kind of element
+    #[error("Property `{0}` can't be modified on `{1}`")]
+    NotApplicable(String, String),
+}
+
+/// Kind and pattern of the elements an operation applies to
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Target {
+    /// Peripherals, like `GPIO*`
+    Peripheral(String),
+    /// Clusters, like `TIM1.CH*`
+    Cluster(String),
+    /// Registers, like `GPIO*.MODER`
+    Register(String),
+    /// Fields, like `TIM1.CR1.*EN`
+    Field(String),
+    /// Enumerated values containers, like `TIM1.CR1.MODE.*`
+    EnumeratedValues(String),
+    /// Enumerated values, by the usage of their container, like `TIM1.CR1.MODE.read-write.*`
+    EnumeratedValue(String),
+}
+
+impl Target {
+    /// Glob pattern of the target
+    pub fn pattern(&self) -> &str {
+        match self {
+            Self::Peripheral(p)
+            | Self::Cluster(p)
+            | Self::Register(p)
+            | Self::Field(p)
+            | Self::EnumeratedValues(p)
+            | Self::EnumeratedValue(p) => p,
+        }
+    }
+
+    /// Kind of the target elements
+    pub fn kind(&self) -> ElementKind {
+        match self {
+            Self::Peripheral(_) => ElementKind::Peripheral,
+            Self::Cluster(_) => ElementKind::Cluster,
+            Self::Register(_) => ElementKind::Register,
+            Self::Field(_) => ElementKind::Field,
+            Self::EnumeratedValues(_) | Self::EnumeratedValue(_) => ElementKind::EnumeratedValue,
+        }
+    }
+}
+
+/// A new element
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Element {
+    /// Peripheral
+    Peripheral(Peripheral),
+    /// Cluster
+    Cluster(Cluster),
+    /// Register
+    Register(Register),
+    /// Field
+    Field(Field),
+    /// Enumerated values container
+    EnumeratedValues(EnumeratedValues),
+    /// Enumerated value
+    EnumeratedValue(EnumeratedValue),
+}
+
+/// Properties to change. Properties which are not set are left unchanged.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[non_exhaustive]
+pub struct Modification {
+    /// Display name of a peripheral or register
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// Description of a peripheral, cluster, register or field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Base address of a peripheral
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::integer::deserialize_option"
+    )]
+    pub base_address: Option<u64>,
+    /// Address offset of a cluster or register
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address_offset: Option<u32>,
+    /// Size of a register, default size for a peripheral or cluster
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u32>,
+    /// Access of a register or field, default access for a peripheral or cluster
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access: Option<Access>,
+    /// Protection of a register, default protection for a peripheral or cluster
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protection: Option<Protection>,
+    /// Reset value of a register, default reset value for a peripheral or cluster
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::integer::deserialize_option"
+    )]
+    pub reset_value: Option<u64>,
+    /// Reset mask of a register, default reset mask for a peripheral or cluster
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::integer::deserialize_option"
+    )]
+    pub reset_mask: Option<u64>,
+    /// Write side effects of a register or field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_write_values: Option<ModifiedWriteValues>,
+    /// Write constraint of a register or field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_constraint: Option<WriteConstraint>,
+    /// Read side effects of a register or field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_action: Option<ReadAction>,
+    /// Bit offset of a field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bit_offset: Option<u32>,
+    /// Bit width of a field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bit_width: Option<u32>,
+    /// Usage of an enumerated values container
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Usage>,
+    /// Value of an enumerated value
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::integer::deserialize_option"
+    )]
+    pub value: Option<u64>,
+    /// Default flag of an enumerated value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_default: Option<bool>,
+}
+
+/// Add a new element
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Add {
+    /// Pattern of the parents, `None` for peripherals
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    /// Element added to each parent
+    #[serde(flatten)]
+    pub element: Element,
+}
+
+/// Change properties of elements
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Modify {
+    /// Elements to modify
+    #[serde(flatten)]
+    pub target: Target,
+    /// New properties
+    pub with: Modification,
+}
+
+/// Rename elements
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rename {
+    /// Elements to rename
+    #[serde(flatten)]
+    pub target: Target,
+    /// New name
+    pub to: String,
+}
+
+/// Copy elements
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Copy {
+    /// Elements to copy
+    #[serde(flatten)]
+    pub target: Target,
+    /// Pattern of the new parents, `None` to copy next to the original
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    /// Name of the copy, `None` to keep the name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// Make elements derived from another one
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Derive {
+    /// Elements to derive
+    #[serde(flatten)]
+    pub target: Target,
+    /// Path of the element to derive from
+    pub from: String,
+}
+
+/// A single patch operation
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Operation {
+    /// Add a new element
+    Add(Box<Add>),
+    /// Delete elements
+    Delete(Target),
+    /// Change properties of elements
+    Modify(Modify),
+    /// Rename elements
+    Rename(Rename),
+    /// Copy elements
+    Copy(Copy),
+    /// Make elements derived from another one
+    Derive(Derive),
+}
+
+impl Device {
+    /// Apply patch operations in order.
+    ///
+    /// Every operation must match at least one element. On error the device is left unchanged.
+    pub fn patch(&mut self, operations: &[Operation], lvl: ValidateLevel) -> Result<(), SvdError> {
+        let mut device = self.clone();
+        for op in operations {
+            op.apply(&mut device, lvl)?;
+        }
+        *self = device;
+        Ok(())
+    }
+}
+
+impl Operation {
+    /// Apply the operation to the device.
+    ///
+    /// On error the device may be partially modified, use [`Device::patch`] to avoid this.
+    pub fn apply(&self, device: &mut Device, lvl: ValidateLevel) -> Result<(), SvdError> {
+        match self {
+            Self::Add(add) => {
+                let query = add.parent.as_deref().map(Query::new).transpose()?;
+                let depth = query.as_ref().map(Query::len).unwrap_or(0);
+                let mut added = 0;
+                for (path, mut c) in containers(device, query.as_ref(), depth) {
+                    if c.insert(add.element.clone(), &path)? {
+                        added += 1;
+                    }
+                }
+                if added == 0 {
+                    return Err(no_match(add.parent.as_deref().unwrap_or("")));
+                }
+            }
+            Self::Delete(target) => {
+                let query = Query::new(target.pattern())?;
+                let depth = query.len() - 1;
+                let mut removed = 0;
+                for (_, mut c) in containers(device, Some(&query), depth) {
+                    removed += c.retain(target, |name| !query.matches(depth, name));
+                }
+                if removed == 0 {
+                    return Err(no_match(target.pattern()));
+                }
+            }
+            Self::Modify(m) => {
+                for_each(device, &m.target, |e, path| e.modify(&m.with, path, lvl))?;
+            }
+            Self::Rename(r) => {
+                let query = Query::new(r.target.pattern())?;
+                let depth = query.len() - 1;
+                let mut renamed = Vec::new();
+                for (path, mut c) in containers(device, Some(&query), depth) {
+                    let mut elements = c.elements(&r.target);
+                    let exists = elements.iter().any(|e| e.name() == r.to);
+                    elements.retain(|e| query.matches(depth, e.name()));
+                    if !elements.is_empty() && (exists || elements.len() > 1) {
+                        return Err(Error::AlreadyExists(join(&path, &r.to)).into());
+                    }
+                    for e in elements {
+                        renamed.push((join(&path, e.name()), join(&path, &r.to)));
+                        e.rename(&r.to, lvl)?;
+                    }
+                }
+                if renamed.is_empty() {
+                    return Err(no_match(r.target.pattern()));
+                }
+                for (old, new) in renamed {
+                    edit::rewrite_references(device, &old, &new)?;
+                }
+            }
+            Self::Derive(d) => {
+                for_each(device, &d.target, |e, path| e.derive(&d.from, path, lvl))?;
+            }
+            Self::Copy(c) => copy(device, c, lvl)?,
+        }
+        Ok(())
+    }
+}
+
+fn no_match(pattern: &str) -> SvdError {
+    Error::NoMatch(pattern.to_string()).into()
+}
+
+fn join(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", parent, name)
+    }
+}
+
+/// Call `f` for each element selected by `target`
+fn for_each<F>(device: &mut Device, target: &Target, mut f: F) -> Result<(), SvdError>
+where
+    F: FnMut(ElementMut, &str) -> Result<(), SvdError>,
+{
+    let query = Query::new(target.pattern())?;
+    let depth = query.len() - 1;
+    let mut found = 0;
+    for (path, mut c) in containers(device, Some(&query), depth) {
+        for e in c.elements(target) {
+            if query.matches(depth, e.name()) {
+                let path = join(&path, e.name());
+                f(e, &path)?;
+                found += 1;
+            }
+        }
+    }
+    if found == 0 {
+        return Err(no_match(target.pattern()));
+    }
+    Ok(())
+}
+
+fn copy(device: &mut Device, c: &Copy, lvl: ValidateLevel) -> Result<(), SvdError> {
+    let query = Query::new(c.target.pattern())?;
+    let depth = query.len() - 1;
+    let copies = |container: &mut Container| -> Result<Vec<Element>, SvdError> {
+        let mut copies = Vec::new();
+        for e in container.elements(&c.target) {
+            if query.matches(depth, e.name()) {
+                let mut copy = e.to_element();
+                if let Some(name) = &c.name {
+                    copy.as_mut().rename(name, lvl)?;
+                }
+                copies.push(copy);
+            }
+        }
+        Ok(copies)
+    };
+
+    let mut copied = 0;
+    match &c.to {
+        None => {
+            for (path, mut container) in containers(device, Some(&query), depth) {
+                for copy in copies(&mut container)? {
+                    container.insert(copy, &path)?;
+                    copied += 1;
+                }
+            }
+        }
+        Some(to) => {
+            let mut all = Vec::new();
+            for (_, mut container) in containers(device, Some(&query), depth) {
+                all.extend(copies(&mut container)?);
+            }
+            copied = all.len();
+            let to = Query::new(to)?;
+            let mut inserted = 0;
+            for (path, mut container) in containers(device, Some(&to), to.len()) {
+                for copy in &all {
+                    if container.insert(copy.clone(), &path)? {
+                        inserted += 1;
+                    }
+                }
+            }
+            if copied > 0 && inserted == 0 {
+                return Err(no_match(to.as_str()));
+            }
+        }
+    }
+    if copied == 0 {
+        return Err(no_match(c.target.pattern()));
+    }
+    Ok(())
+}
+
+/// A list of elements of the device, with the path of its owner
+enum Container<'a> {
+    Peripherals(&'a mut Vec<Peripheral>),
+    Registers(&'a mut Option<Vec<RegisterCluster>>),
+    Children(&'a mut Vec<RegisterCluster>),
+    Fields(&'a mut Option<Vec<Field>>),
+    EnumeratedValues(&'a mut Vec<EnumeratedValues>),
+    Values(&'a mut Vec<EnumeratedValue>),
+}
+
+impl<'a> Container<'a> {
+    fn children(&mut self) -> Option<&mut Vec<RegisterCluster>> {
+        match self {
+            Self::Registers(r) => r.as_mut(),
+            Self::Children(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// Elements of the kind of the `target`
+    fn elements(&mut self, target: &Target) -> Vec<ElementMut> {
+        match (target, self) {
+            (Target::Peripheral(_), Self::Peripherals(p)) => {
+                p.iter_mut().map(ElementMut::Peripheral).collect()
+            }
+            (Target::Field(_), Self::Fields(f)) => {
+                f.iter_mut().flatten().map(ElementMut::Field).collect()
+            }
+            (Target::EnumeratedValues(_), Self::EnumeratedValues(e)) => {
+                e.iter_mut().map(ElementMut::EnumeratedValues).collect()
+            }
+            (Target::EnumeratedValue(_), Self::Values(v)) => {
+                v.iter_mut().map(ElementMut::EnumeratedValue).collect()
+            }
+            (Target::Cluster(_), c) => c
+                .children()
+                .into_iter()
+                .flatten()
+                .filter_map(|rc| match rc {
+                    RegisterCluster::Cluster(c) => Some(ElementMut::Cluster(c)),
+                    RegisterCluster::Register(_) => None,
+                })
+                .collect(),
+            (Target::Register(_), c) => c
+                .children()
+                .into_iter()
+                .flatten()
+                .filter_map(|rc| match rc {
+                    RegisterCluster::Register(r) => Some(ElementMut::Register(r)),
+                    RegisterCluster::Cluster(_) => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Keep elements of the kind of the `target` for which `keep` returns `true`,
+    /// return the number of removed elements
+    fn retain<F: Fn(&str) -> bool>(&mut self, target: &Target, keep: F) -> usize {
+        fn retain<T, F: FnMut(&T) -> bool>(v: &mut Vec<T>, f: F) -> usize {
+            let len = v.len();
+            v.retain(f);
+            len - v.len()
+        }
+        match (target, self) {
+            (Target::Peripheral(_), Self::Peripherals(p)) => retain(p, |p| keep(&p.name)),
+            (Target::Field(_), Self::Fields(Some(f))) => retain(f, |f| keep(&f.name)),
+            (Target::EnumeratedValues(_), Self::EnumeratedValues(e)) => {
+                retain(e, |e| keep(e.name.as_deref().unwrap_or("")))
+            }
+            (Target::EnumeratedValue(_), Self::Values(v)) => retain(v, |v| keep(&v.name)),
+            (Target::Cluster(_), c) => match c.children() {
+                Some(children) => retain(children, |rc| match rc {
+                    RegisterCluster::Cluster(c) => keep(&c.name),
+                    RegisterCluster::Register(_) => true,
+                }),
+                None => 0,
+            },
+            (Target::Register(_), c) => match c.children() {
+                Some(children) => retain(children, |rc| match rc {
+                    RegisterCluster::Register(r) => keep(&r.name),
+                    RegisterCluster::Cluster(_) => true,
+                }),
+                None => 0,
+            },
+            _ => 0,
+        }
+    }
+
+    /// Add the element, return `false` if it can't be a child of the container
+    fn insert(&mut self, element: Element, path: &str) -> Result<bool, Error> {
+        let exists = |name: &str| Error::AlreadyExists(join(path, name));
+        match (element, self) {
+            (Element::Peripheral(p), Self::Peripherals(ps)) => {
+                if ps.iter().any(|e| e.name == p.name) {
+                    return Err(exists(&p.name));
+                }
+                ps.push(p);
+            }
+            (Element::Field(f), Self::Fields(fs)) => {
+                let fs = fs.get_or_insert_with(Vec::new);
+                if fs.iter().any(|e| e.name == f.name) {
+                    return Err(exists(&f.name));
+                }
+                fs.push(f);
+            }
+            (Element::EnumeratedValues(e), Self::EnumeratedValues(es)) => {
+                if e.name.is_some() && es.iter().any(|o| o.name == e.name) {
+                    return Err(exists(e.name.as_deref().unwrap_or("")));
+                }
+                es.push(e);
+            }
+            (Element::EnumeratedValue(v), Self::Values(vs)) => {
+                if vs.iter().any(|o| o.name == v.name) {
+                    return Err(exists(&v.name));
+                }
+                vs.push(v);
+            }
+            (Element::Register(r), c) => {
+                let children = match c {
+                    Self::Registers(r) => r.get_or_insert_with(Vec::new),
+                    Self::Children(c) => c,
+                    _ => return Ok(false),
+                };
+                if children.iter().any(|rc| rc_name(rc) == r.name) {
+                    return Err(exists(&r.name));
+                }
+                children.push(RegisterCluster::Register(r));
+            }
+            (Element::Cluster(cl), c) => {
+                let children = match c {
+                    Self::Registers(r) => r.get_or_insert_with(Vec::new),
+                    Self::Children(c) => c,
+                    _ => return Ok(false),
+                };
+                if children.iter().any(|rc| rc_name(rc) == cl.name) {
+                    return Err(exists(&cl.name));
+                }
+                children.push(RegisterCluster::Cluster(cl));
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+fn rc_name(rc: &RegisterCluster) -> &str {
+    match rc {
+        RegisterCluster::Register(r) => &r.name,
+        RegisterCluster::Cluster(c) => &c.name,
+    }
+}
+
+/// Containers whose owners match the first `depth` segments of the query
+fn containers<'a>(
+    device: &'a mut Device,
+    query: Option<&Query>,
+    depth: usize,
+) -> Vec<(String, Container<'a>)> {
+    let query = match query {
+        Some(query) if depth > 0 => query,
+        _ => {
+            return vec![(
+                String::new(),
+                Container::Peripherals(&mut device.peripherals),
+            )]
+        }
+    };
+    let mut out = Vec::new();
+    for p in device.peripherals.iter_mut() {
+        if !query.matches(0, &p.name) {
+            continue;
+        }
+        let path = p.name.clone();
+        let info: &'a mut PeripheralInfo = p;
+        if depth == 1 {
+            out.push((path, Container::Registers(&mut info.registers)));
+        } else if let Some(children) = info.registers.as_mut() {
+            child_containers(children, &path, 1, query, depth, &mut out);
+        }
+    }
+    out
+}
+
+fn child_containers<'a>(
+    children: &'a mut [RegisterCluster],
+    parent: &str,
+    level: usize,
+    query: &Query,
+    depth: usize,
+    out: &mut Vec<(String, Container<'a>)>,
+) {
+    for rc in children {
+        if !query.matches(level, rc_name(rc)) {
+            continue;
+        }
+        let path = join(parent, rc_name(rc));
+        match rc {
+            RegisterCluster::Cluster(c) => {
+                let info: &'a mut ClusterInfo = c;
+                if level + 1 == depth {
+                    out.push((path, Container::Children(&mut info.children)));
+                } else {
+                    child_containers(&mut info.children, &path, level + 1, query, depth, out);
+                }
+            }
+            RegisterCluster::Register(r) => {
+                let info: &'a mut RegisterInfo = r;
+                if level + 1 == depth {
+                    out.push((path, Container::Fields(&mut info.fields)));
+                } else if level + 2 == depth {
+                    for f in info.fields.iter_mut().flatten() {
+                        if query.matches(level + 1, &f.name) {
+                            let path = join(&path, &f.name);
+                            out.push((path, Container::EnumeratedValues(&mut f.enumerated_values)));
+                        }
+                    }
+                } else if level + 3 == depth {
+                    for f in info.fields.iter_mut().flatten() {
+                        if !query.matches(level + 1, &f.name) {
+                            continue;
+                        }
+                        let path = join(&path, &f.name);
+                        for e in f.enumerated_values.iter_mut() {
+                            let usage = e.usage().as_str();
+                            if query.matches(level + 2, usage) {
+                                let path = join(&path, usage);
+                                out.push((path, Container::Values(&mut e.values)));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Mutable reference to a selected element
+enum ElementMut<'a> {
+    Peripheral(&'a mut Peripheral),
+    Cluster(&'a mut Cluster),
+    Register(&'a mut Register),
+    Field(&'a mut Field),
+    EnumeratedValues(&'a mut EnumeratedValues),
+    EnumeratedValue(&'a mut EnumeratedValue),
+}
+
+impl Element {
+    fn as_mut(&mut self) -> ElementMut {
+        match self {
+            Self::Peripheral(p) => ElementMut::Peripheral(p),
+            Self::Cluster(c) => ElementMut::Cluster(c),
+            Self::Register(r) => ElementMut::Register(r),
+            Self::Field(f) => ElementMut::Field(f),
+            Self::EnumeratedValues(e) => ElementMut::EnumeratedValues(e),
+            Self::EnumeratedValue(v) => ElementMut::EnumeratedValue(v),
+        }
+    }
+}
+
+impl<'a> ElementMut<'a> {
+    fn name(&self) -> &str {
+        match self {
+            Self::Peripheral(p) => &p.name,
+            Self::Cluster(c) => &c.name,
+            Self::Register(r) => &r.name,
+            Self::Field(f) => &f.name,
+            Self::EnumeratedValues(e) => e.name.as_deref().unwrap_or(""),
+            Self::EnumeratedValue(v) => &v.name,
+        }
+    }
+
+    fn to_element(&self) -> Element {
+        match self {
+            Self::Peripheral(p) => Element::Peripheral((*p).clone()),
+            Self::Cluster(c) => Element::Cluster((*c).clone()),
+            Self::Register(r) => Element::Register((*r).clone()),
+            Self::Field(f) => Element::Field((*f).clone()),
+            Self::EnumeratedValues(e) => Element::EnumeratedValues((*e).clone()),
+            Self::EnumeratedValue(v) => Element::EnumeratedValue((*v).clone()),
+        }
+    }
+
+    fn rename(self, name: &str, lvl: ValidateLevel) -> Result<(), SvdError> {
+        let name = name.to_string();
+        match self {
+            Self::Peripheral(p) => p.modify_from(PeripheralInfo::builder().name(name), lvl),
+            Self::Cluster(c) => c.modify_from(ClusterInfo::builder().name(name), lvl),
+            Self::Register(r) => r.modify_from(RegisterInfo::builder().name(name), lvl),
+            Self::Field(f) => f.modify_from(super::FieldInfo::builder().name(name), lvl),
+            Self::EnumeratedValues(e) => {
+                e.modify_from(EnumeratedValues::builder().name(Some(name)), lvl)
+            }
+            Self::EnumeratedValue(v) => v.modify_from(EnumeratedValue::builder().name(name), lvl),
+        }
+    }
+
+    fn derive(self, from: &str, path: &str, lvl: ValidateLevel) -> Result<(), SvdError> {
+        let from = Some(from.to_string());
+        match self {
+            Self::Peripheral(p) => p.modify_from(PeripheralInfo::builder().derived_from(from), lvl),
+            Self::Cluster(c) => c.modify_from(ClusterInfo::builder().derived_from(from), lvl),
+            Self::Register(r) => r.modify_from(RegisterInfo::builder().derived_from(from), lvl),
+            Self::Field(f) => f.modify_from(super::FieldInfo::builder().derived_from(from), lvl),
+            Self::EnumeratedValues(e) => {
+                e.modify_from(EnumeratedValues::builder().derived_from(from), lvl)
+            }
+            Self::EnumeratedValue(_) => {
+                Err(Error::NotApplicable("derivedFrom".to_string(), path.to_string()).into())
+            }
+        }
+    }
+
+    fn modify(self, m: &Modification, path: &str, lvl: ValidateLevel) -> Result<(), SvdError> {
+        match self {
+            Self::Peripheral(p) => {
+                m.check(
+                    &[
+                        "displayName",
+                        "description",
+                        "baseAddress",
+                        "size",
+                        "access",
+                        "protection",
+                        "resetValue",
+                        "resetMask",
+                    ],
+                    path,
+                )?;
+                let mut builder = PeripheralInfo::builder()
+                    .display_name(m.display_name.clone())
+                    .description(m.description.clone())
+                    .default_register_properties(m.properties());
+                if let Some(base_address) = m.base_address {
+                    builder = builder.base_address(base_address);
+                }
+                p.modify_from(builder, lvl)
+            }
+            Self::Cluster(c) => {
+                m.check(
+                    &[
+                        "description",
+                        "addressOffset",
+                        "size",
+                        "access",
+                        "protection",
+                        "resetValue",
+                        "resetMask",
+                    ],
+                    path,
+                )?;
+                let mut builder = ClusterInfo::builder()
+                    .description(m.description.clone())
+                    .default_register_properties(m.properties());
+                if let Some(address_offset) = m.address_offset {
+                    builder = builder.address_offset(address_offset);
+                }
+                c.modify_from(builder, lvl)
+            }
+            Self::Register(r) => {
+                m.check(
+                    &[
+                        "displayName",
+                        "description",
+                        "addressOffset",
+                        "size",
+                        "access",
+                        "protection",
+                        "resetValue",
+                        "resetMask",
+                        "modifiedWriteValues",
+                        "writeConstraint",
+                        "readAction",
+                    ],
+                    path,
+                )?;
+                let mut builder = RegisterInfo::builder()
+                    .display_name(m.display_name.clone())
+                    .description(m.description.clone())
+                    .properties(m.properties())
+                    .modified_write_values(m.modified_write_values)
+                    .write_constraint(m.write_constraint)
+                    .read_action(m.read_action);
+                if let Some(address_offset) = m.address_offset {
+                    builder = builder.address_offset(address_offset);
+                }
+                r.modify_from(builder, lvl)
+            }
+            Self::Field(f) => {
+                m.check(
+                    &[
+                        "description",
+                        "access",
+                        "modifiedWriteValues",
+                        "writeConstraint",
+                        "readAction",
+                        "bitOffset",
+                        "bitWidth",
+                    ],
+                    path,
+                )?;
+                let mut builder = super::FieldInfo::builder()
+                    .description(m.description.clone())
+                    .access(m.access)
+                    .modified_write_values(m.modified_write_values)
+                    .write_constraint(m.write_constraint)
+                    .read_action(m.read_action);
+                if let Some(offset) = m.bit_offset {
+                    builder = builder.bit_offset(offset);
+                }
+                if let Some(width) = m.bit_width {
+                    builder = builder.bit_width(width);
+                }
+                f.modify_from(builder, lvl)
+            }
+            Self::EnumeratedValues(e) => {
+                m.check(&["usage"], path)?;
+                e.modify_from(EnumeratedValues::builder().usage(m.usage), lvl)
+            }
+            Self::EnumeratedValue(v) => {
+                m.check(&["description", "value", "isDefault"], path)?;
+                let builder = EnumeratedValue::builder()
+                    .description(m.description.clone())
+                    .value(m.value)
+                    .is_default(m.is_default);
+                v.modify_from(builder, lvl)
+            }
+        }
+    }
+}
+
+impl Modification {
+    /// Return an error if a property not in `allowed` is set
+    fn check(&self, allowed: &[&str], path: &str) -> Result<(), Error> {
+        let set = [
+            ("displayName", self.display_name.is_some()),
+            ("description", self.description.is_some()),
+            ("baseAddress", self.base_address.is_some()),
+            ("addressOffset", self.address_offset.is_some()),
+            ("size", self.size.is_some()),
+            ("access", self.access.is_some()),
+            ("protection", self.protection.is_some()),
+            ("resetValue", self.reset_value.is_some()),
+            ("resetMask", self.reset_mask.is_some()),
+            ("modifiedWriteValues", self.modified_write_values.is_some()),
+            ("writeConstraint", self.write_constraint.is_some()),
+            ("readAction", self.read_action.is_some()),
+            ("bitOffset", self.bit_offset.is_some()),
+            ("bitWidth", self.bit_width.is_some()),
+            ("usage", self.usage.is_some()),
+            ("value", self.value.is_some()),
+            ("isDefault", self.is_default.is_some()),
+        ];
+        for (name, is_set) in set.iter() {
+            if *is_set && !allowed.contains(name) {
+                return Err(Error::NotApplicable(name.to_string(), path.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    fn properties(&self) -> RegisterProperties {
+        RegisterProperties::new()
+            .size(self.size)
+            .access(self.access)
+            .protection(self.protection)
+            .reset_value(self.reset_value)
+            .reset_mask(self.reset_mask)
+    }
+}
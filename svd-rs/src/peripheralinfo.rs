@@ -81,6 +81,10 @@ pub struct PeripheralInfo {
     pub header_struct_name: Option<String>,
 
     /// Lowest address reserved or used by the peripheral
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "crate::integer::deserialize")
+    )]
     pub base_address: u64,
 
     /// Default properties for all registers
@@ -118,6 +122,11 @@ pub struct PeripheralInfo {
 }
 
 /// Builder for [`Peripheral`]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(default, rename_all = "camelCase")
+)]
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct PeripheralInfoBuilder {
     name: Option<String>,
@@ -129,7 +138,12 @@ pub struct PeripheralInfoBuilder {
     prepend_to_name: Option<String>,
     append_to_name: Option<String>,
     header_struct_name: Option<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "crate::integer::deserialize_option")
+    )]
     base_address: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(flatten))]
     default_register_properties: RegisterProperties,
     address_block: Option<Vec<AddressBlock>>,
     interrupt: Option<Vec<Interrupt>>,
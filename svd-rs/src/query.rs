@@ -0,0 +1,320 @@
+//! Selection of [`Device`] elements with glob patterns.
+//!
+//! A pattern is a list of segments separated by `.`, one for each level of
+//! `PERIPHERAL.CLUSTER.REGISTER.FIELD`. Each segment is a glob supporting
+//! `*` (any string), `?` (any character), `[1-8]`/`[!0]` (character classes)
+//! and `{ISR,ICR}` (alternatives). A pattern matches elements whose depth is
+//! equal to the number of its segments, e.g. `GPIO*.MODER` selects registers
+//! and `TIM[1-8].CR?.*EN` selects fields.
+//!
+//! Arrays are matched by their template name (`GPIO%s`) first. If the template
+//! doesn't match, the expanded instance names (`GPIOA`, `GPIOB`, ...) are tried,
+//! and each matching instance is returned separately.
+
+use regex::Regex;
+
+use super::{Cluster, Device, DimElement, Field, Peripheral, Register, RegisterCluster, SvdError};
+
+/// Errors for [`Query::new`]
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    /// Pattern is empty or has an empty segment
+    #[error("Pattern `{0}` has an empty segment")]
+    EmptySegment(String),
+    /// `[` or `{` is not closed
+    #[error("Pattern `{0}` has an unclosed `{1}`")]
+    Unclosed(String, char),
+    /// Pattern can't be converted to a regular expression
+    #[error("Pattern `{0}` is invalid: {1}")]
+    Invalid(String, String),
+}
+
+/// A compiled glob pattern
+#[derive(Clone, Debug)]
+pub struct Query {
+    pattern: String,
+    segments: Vec<Regex>,
+}
+
+/// An element selected by a [`Query`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Element<'a> {
+    /// Peripheral
+    Peripheral(&'a Peripheral),
+    /// Cluster
+    Cluster(&'a Cluster),
+    /// Register
+    Register(&'a Register),
+    /// Field
+    Field(&'a Field),
+}
+
+/// A match of a [`Query`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Match<'a> {
+    /// Path of the element, using instance names for array instances
+    pub path: String,
+    /// Index of the array instance, `None` if the element itself was matched
+    pub index: Option<String>,
+    /// The matched element
+    pub element: Element<'a>,
+}
+
+impl Query {
+    /// Compile a glob pattern.
+    pub fn new(pattern: &str) -> Result<Self, SvdError> {
+        let segments = split(pattern)?
+            .into_iter()
+            .map(|s| {
+                let re = format!("^{}$", glob_to_regex(pattern, s)?);
+                Regex::new(&re).map_err(|e| Error::Invalid(pattern.to_string(), e.to_string()))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            pattern: pattern.to_string(),
+            segments,
+        })
+    }
+
+    /// Get the source pattern
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Return `true` if the path like `PERIPHERAL.REGISTER` matches the pattern.
+    pub fn is_match(&self, path: &str) -> bool {
+        let names: Vec<&str> = path.split('.').collect();
+        names.len() == self.segments.len()
+            && names
+                .iter()
+                .zip(&self.segments)
+                .all(|(n, re)| re.is_match(n))
+    }
+
+    /// Number of segments in the pattern
+    pub(crate) fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Return `true` if the segment at `depth` matches `name` as written, without array expansion
+    pub(crate) fn matches(&self, depth: usize, name: &str) -> bool {
+        self.segments
+            .get(depth)
+            .map(|re| re.is_match(name))
+            .unwrap_or(false)
+    }
+
+    /// Find all matching elements of the device, in the order of the device.
+    pub fn find<'a>(&self, device: &'a Device) -> Vec<Match<'a>> {
+        let mut matches = Vec::new();
+        for p in &device.peripherals {
+            let dim = match p {
+                Peripheral::Single(_) => None,
+                Peripheral::Array(_, dim) => Some(dim),
+            };
+            for (path, index) in self.names("", 0, &p.name, dim) {
+                if self.segments.len() == 1 {
+                    matches.push(Match {
+                        path,
+                        index,
+                        element: Element::Peripheral(p),
+                    });
+                } else if let Some(children) = &p.registers {
+                    self.children(&mut matches, &path, 1, children);
+                }
+            }
+        }
+        matches
+    }
+
+    fn children<'a>(
+        &self,
+        matches: &mut Vec<Match<'a>>,
+        parent: &str,
+        depth: usize,
+        children: &'a [RegisterCluster],
+    ) {
+        let last = depth + 1 == self.segments.len();
+        for rc in children {
+            match rc {
+                RegisterCluster::Cluster(c) => {
+                    let dim = match c {
+                        Cluster::Single(_) => None,
+                        Cluster::Array(_, dim) => Some(dim),
+                    };
+                    for (path, index) in self.names(parent, depth, &c.name, dim) {
+                        if last {
+                            matches.push(Match {
+                                path,
+                                index,
+                                element: Element::Cluster(c),
+                            });
+                        } else {
+                            self.children(matches, &path, depth + 1, &c.children);
+                        }
+                    }
+                }
+                RegisterCluster::Register(r) => {
+                    let dim = match r {
+                        Register::Single(_) => None,
+                        Register::Array(_, dim) => Some(dim),
+                    };
+                    for (path, index) in self.names(parent, depth, &r.name, dim) {
+                        if last {
+                            matches.push(Match {
+                                path,
+                                index,
+                                element: Element::Register(r),
+                            });
+                        } else if depth + 2 == self.segments.len() {
+                            self.fields(matches, &path, depth + 1, r);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn fields<'a>(
+        &self,
+        matches: &mut Vec<Match<'a>>,
+        parent: &str,
+        depth: usize,
+        r: &'a Register,
+    ) {
+        for f in r.fields.iter().flatten() {
+            let dim = match f {
+                Field::Single(_) => None,
+                Field::Array(_, dim) => Some(dim),
+            };
+            for (path, index) in self.names(parent, depth, &f.name, dim) {
+                matches.push(Match {
+                    path,
+                    index,
+                    element: Element::Field(f),
+                });
+            }
+        }
+    }
+
+    /// Paths of the element or its array instances matching the segment at `depth`
+    fn names(
+        &self,
+        parent: &str,
+        depth: usize,
+        name: &str,
+        dim: Option<&DimElement>,
+    ) -> Vec<(String, Option<String>)> {
+        let re = match self.segments.get(depth) {
+            Some(re) => re,
+            None => return Vec::new(),
+        };
+        if re.is_match(name) {
+            return vec![(join(parent, name), None)];
+        }
+        match dim {
+            Some(dim) => dim
+                .indexes()
+                .filter_map(|idx| {
+                    let instance = name.replace("[%s]", &idx).replace("%s", &idx);
+                    if re.is_match(&instance) {
+                        Some((join(parent, &instance), Some(idx.into_owned())))
+                    } else {
+                        None
+                    }
+                })
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl Device {
+    /// Find elements matching the glob pattern. See [`query`](crate::query).
+    pub fn query(&self, pattern: &str) -> Result<Vec<Match>, SvdError> {
+        Ok(Query::new(pattern)?.find(self))
+    }
+}
+
+fn join(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", parent, name)
+    }
+}
+
+/// Split the pattern at `.` outside of brackets
+fn split(pattern: &str) -> Result<Vec<&str>, Error> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut braces = 0;
+    let mut in_class = false;
+    for (i, c) in pattern.char_indices() {
+        match c {
+            '[' if !in_class => in_class = true,
+            ']' if in_class => in_class = false,
+            '{' if !in_class => braces += 1,
+            '}' if !in_class && braces > 0 => braces -= 1,
+            '.' if !in_class && braces == 0 => {
+                segments.push(&pattern[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if in_class {
+        return Err(Error::Unclosed(pattern.to_string(), '['));
+    }
+    if braces > 0 {
+        return Err(Error::Unclosed(pattern.to_string(), '{'));
+    }
+    segments.push(&pattern[start..]);
+    if segments.iter().any(|s| s.is_empty()) {
+        return Err(Error::EmptySegment(pattern.to_string()));
+    }
+    Ok(segments)
+}
+
+/// Convert a glob segment to a regular expression
+fn glob_to_regex(pattern: &str, glob: &str) -> Result<String, Error> {
+    let mut re = String::new();
+    let mut chars = glob.chars().peekable();
+    let mut braces = 0;
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => re.push_str(".*"),
+            '?' => re.push('.'),
+            '[' => {
+                re.push('[');
+                if let Some('!') | Some('^') = chars.peek() {
+                    chars.next();
+                    re.push('^');
+                }
+                loop {
+                    match chars.next() {
+                        Some(']') => break,
+                        Some(c) if c == '\\' || c == '[' || c == '&' || c == '~' => {
+                            re.push('\\');
+                            re.push(c);
+                        }
+                        Some(c) => re.push(c),
+                        None => return Err(Error::Unclosed(pattern.to_string(), '[')),
+                    }
+                }
+                re.push(']');
+            }
+            '{' => {
+                braces += 1;
+                re.push_str("(?:");
+            }
+            ',' if braces > 0 => re.push('|'),
+            '}' if braces > 0 => {
+                braces -= 1;
+                re.push(')');
+            }
+            c => re.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    Ok(re)
+}
@@ -1,14 +1,21 @@
 use super::{
-    Access, BuildError, DimElement, EmptyToNone, Field, ModifiedWriteValues, ReadAction, Register,
+    addressindex::field_instances, checked_shr, value::bit_mask, writeconstraint, Access,
+    BuildError, DimElement, EmptyToNone, Field, ModifiedWriteValues, ReadAction, Register,
     RegisterProperties, SvdError, ValidateLevel, WriteConstraint,
 };
 
-/// Errors from [`RegisterInfo::validate`]
+/// Errors from [`RegisterInfo::validate`] and [`RegisterInfo::is_write_allowed`]
 #[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
 pub enum Error {
     /// Register had no fields, but specified a `<fields>` tag.
     #[error("Register have `fields` tag, but it is empty")]
     EmptyFields,
+    /// Write to the field is not allowed
+    #[error("Write to field `{0}` is not allowed: {1}")]
+    WriteNotAllowed(String, writeconstraint::Error),
+    /// The value doesn't fit in the register
+    #[error("Value {0:#x} doesn't fit in the {1} bits of the register")]
+    TooWide(u64, u32),
 }
 
 /// A register is a named, programmable resource that belongs to a [peripheral](crate::Peripheral).
@@ -99,6 +106,11 @@ pub struct RegisterInfo {
 }
 
 /// Builder for [`RegisterInfo`]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(default, rename_all = "camelCase")
+)]
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct RegisterInfoBuilder {
     name: Option<String>,
@@ -107,6 +119,7 @@ pub struct RegisterInfoBuilder {
     alternate_group: Option<String>,
     alternate_register: Option<String>,
     address_offset: Option<u32>,
+    #[cfg_attr(feature = "serde", serde(flatten))]
     properties: RegisterProperties,
     modified_write_values: Option<ModifiedWriteValues>,
     write_constraint: Option<WriteConstraint>,
@@ -328,4 +341,41 @@ impl RegisterInfo {
         }
         Ok(())
     }
+
+    /// Check if `value` can be written to the register.
+    ///
+    /// Each field instance is checked with [`FieldInfo::is_write_allowed`](crate::FieldInfo::is_write_allowed),
+    /// using the write constraint of the register for fields without one.
+    /// `properties` are the register properties inherited from the enclosing elements,
+    /// the ones of the register take precedence.
+    ///
+    /// The value must fit in the size of the register, 32 bits if it has none.
+    /// Read-only fields and fields with `WriteAsRead` constraint are skipped,
+    /// as their written values have no effect.
+    pub fn is_write_allowed(
+        &self,
+        value: u64,
+        properties: RegisterProperties,
+    ) -> Result<(), Error> {
+        let properties = self.properties.inherit(&properties);
+        let size = properties.size.unwrap_or(32);
+        if size < 64 && value >> size != 0 {
+            return Err(Error::TooWide(value, size));
+        }
+        for f in field_instances(self.fields.as_deref().unwrap_or(&[])) {
+            let access = f.field.access.or(properties.access).unwrap_or_default();
+            if !access.can_write() {
+                continue;
+            }
+            let constraint = f.field.write_constraint.or(self.write_constraint);
+            if let Some(WriteConstraint::WriteAsRead(true)) = constraint {
+                continue;
+            }
+            let v = checked_shr(value, f.bit_range.offset) & bit_mask(f.bit_range.width);
+            f.field
+                .check_write(v, constraint)
+                .map_err(|e| Error::WriteNotAllowed(f.name, e))?;
+        }
+        Ok(())
+    }
 }
@@ -47,14 +47,22 @@ pub struct RegisterProperties {
     /// Register value at RESET
     #[cfg_attr(
         feature = "serde",
-        serde(default, skip_serializing_if = "Option::is_none")
+        serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "crate::integer::deserialize_option"
+        )
     )]
     pub reset_value: Option<u64>,
 
     /// Define which register bits have a defined reset value
     #[cfg_attr(
         feature = "serde",
-        serde(default, skip_serializing_if = "Option::is_none")
+        serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "crate::integer::deserialize_option"
+        )
     )]
     pub reset_mask: Option<u64>,
 }
@@ -129,6 +137,15 @@ impl RegisterProperties {
         }
         Ok(self)
     }
+    /// Fill properties which are not set with the values inherited from `parent`.
+    pub fn inherit(mut self, parent: &RegisterProperties) -> Self {
+        self.size = self.size.or(parent.size);
+        self.access = self.access.or(parent.access);
+        self.protection = self.protection.or(parent.protection);
+        self.reset_value = self.reset_value.or(parent.reset_value);
+        self.reset_mask = self.reset_mask.or(parent.reset_mask);
+        self
+    }
 }
 
 pub(crate) fn check_reset_value(
@@ -0,0 +1,128 @@
+//! Read-modify-write masks.
+//!
+//! [`RmwMasks`] are computed from `access`, `modifiedWriteValues` and `readAction`.
+//! [`FieldInfo::rmw_masks`] uses the values of the field itself, while
+//! [`RegisterInfo::rmw_masks`] combines all field instances, inheriting the values
+//! of the register for fields which don't set them, and the access and size of the
+//! enclosing elements for registers which don't set them. A register without fields
+//! is treated as a single field covering its size, 32 bits if not set.
+
+use super::{
+    addressindex::field_instances, checked_shl, value::bit_mask, Access, FieldInfo,
+    ModifiedWriteValues, ReadAction, RegisterInfo, RegisterProperties,
+};
+
+/// Masks of the bits of a register with side effects
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RmwMasks {
+    /// Writing one clears the bit
+    pub one_to_clear: u64,
+    /// Writing zero clears the bit
+    pub zero_to_clear: u64,
+    /// Writing one sets the bit
+    pub one_to_set: u64,
+    /// Writing zero sets the bit
+    pub zero_to_set: u64,
+    /// Writing one (`oneToToggle`) or zero (`zeroToToggle`) inverts the bit
+    pub toggle: u64,
+    /// Reading the bit has side effects, including effects on other resources
+    pub read_side_effects: u64,
+    /// Bits which keep their value, and have no other effect,
+    /// when the value read from them is written back.
+    /// Bits with `read-writeOnce` or `writeOnce` access are never included.
+    ///
+    /// Read-modify-write sequences should write the other writable bits with
+    /// a neutral value, like zero for `oneToClear` bits.
+    pub write_back: u64,
+}
+
+impl RmwMasks {
+    /// Masks of `bits` with the given behavior
+    fn new(
+        bits: u64,
+        access: Access,
+        modified_write_values: ModifiedWriteValues,
+        read_action: Option<ReadAction>,
+    ) -> Self {
+        use ModifiedWriteValues::*;
+        let mut masks = Self::default();
+        if access.can_write() {
+            match modified_write_values {
+                OneToClear => masks.one_to_clear = bits,
+                ZeroToClear => masks.zero_to_clear = bits,
+                OneToSet => masks.one_to_set = bits,
+                ZeroToSet => masks.zero_to_set = bits,
+                OneToToggle | ZeroToToggle => masks.toggle = bits,
+                Clear | Set | Modify => {}
+            }
+        }
+        if read_action.is_some() {
+            masks.read_side_effects = bits;
+        }
+        // Writing back the read value doesn't change these bits:
+        // a read 1 is set again, a read 0 is cleared again.
+        // Bits which can be written once would lose their only write.
+        let neutral = matches!(modified_write_values, Modify | OneToSet | ZeroToClear);
+        let once = matches!(access, Access::ReadWriteOnce | Access::WriteOnce);
+        if !access.can_write() || (access.can_read() && !once && neutral && read_action.is_none()) {
+            masks.write_back = bits;
+        }
+        masks
+    }
+
+    fn union(self, other: Self) -> Self {
+        Self {
+            one_to_clear: self.one_to_clear | other.one_to_clear,
+            zero_to_clear: self.zero_to_clear | other.zero_to_clear,
+            one_to_set: self.one_to_set | other.one_to_set,
+            zero_to_set: self.zero_to_set | other.zero_to_set,
+            toggle: self.toggle | other.toggle,
+            read_side_effects: self.read_side_effects | other.read_side_effects,
+            write_back: self.write_back | other.write_back,
+        }
+    }
+}
+
+impl FieldInfo {
+    /// Get the masks of the field bits in the register, see [`rmw`](crate::rmw)
+    pub fn rmw_masks(&self) -> RmwMasks {
+        RmwMasks::new(
+            checked_shl(bit_mask(self.bit_range.width), self.bit_range.offset),
+            self.access.unwrap_or_default(),
+            self.modified_write_values.unwrap_or_default(),
+            self.read_action,
+        )
+    }
+}
+
+impl RegisterInfo {
+    /// Get the masks of the register, see [`rmw`](crate::rmw).
+    ///
+    /// `properties` are the register properties inherited from the enclosing elements,
+    /// the ones of the register take precedence.
+    pub fn rmw_masks(&self, properties: RegisterProperties) -> RmwMasks {
+        let properties = self.properties.inherit(&properties);
+        let access = properties.access;
+        let fields = field_instances(self.fields.as_deref().unwrap_or(&[]));
+        if fields.is_empty() {
+            return RmwMasks::new(
+                bit_mask(properties.size.unwrap_or(32)),
+                access.unwrap_or_default(),
+                self.modified_write_values.unwrap_or_default(),
+                self.read_action,
+            );
+        }
+        fields.iter().fold(RmwMasks::default(), |masks, f| {
+            masks.union(RmwMasks::new(
+                checked_shl(bit_mask(f.bit_range.width), f.bit_range.offset),
+                f.field.access.or(access).unwrap_or_default(),
+                f.field
+                    .modified_write_values
+                    .or(self.modified_write_values)
+                    .unwrap_or_default(),
+                f.field.read_action.or(self.read_action),
+            ))
+        })
+    }
+}
@@ -0,0 +1,440 @@
+//! JSON Schema of the serde representation.
+//!
+//! [`json_schema`] describes the documents made by serializing a [`Device`] with
+//! the `serde` feature, as JSON, or as YAML and TOML with the `yaml` and `toml`
+//! features. It follows the serde model:
+//! - elements are maps with the camelCase names of the SVD tags,
+//! - arrays have their `dim` properties next to the properties of the element,
+//! - bit ranges are written with `bitRange`, `bitOffset` and `bitWidth`, or `lsb` and `msb`,
+//! - registers and clusters are maps with a single `register` or `cluster` key,
+//! - 64-bit addresses and values are integers, or decimal, `0x` or `0b` strings,
+//!   which TOML uses above `i64::MAX`.
+//!
+//! The schema rejects keys which aren't part of the format, while deserialization
+//! ignores them, so that typos are caught by validation.
+//!
+//! The format is versioned by [`FORMAT_VERSION`], which changes with the serde model.
+//! [`versioned`] marks a serialized device with the `formatVersion` and the `$schema`
+//! it conforms to, so that tools can tell which version a file uses before
+//! migrating it. Deserialization ignores both keys.
+
+use serde_json::{json, Map, Value};
+
+use super::Device;
+
+/// Version of the serde format described by [`json_schema`]
+pub const FORMAT_VERSION: &str = "1.1";
+
+/// Identifier of the schema of [`FORMAT_VERSION`]
+pub const SCHEMA_ID: &str = "urn:svd-rs:format:1.1";
+
+/// JSON Schema of the serde representation of a [`Device`]
+pub fn json_schema() -> Value {
+    let mut device = object(
+        vec![
+            properties(&[
+                ("$schema", string()),
+                ("formatVersion", json!({ "const": FORMAT_VERSION })),
+                ("name", string()),
+                ("version", string()),
+                ("description", string()),
+                ("cpu", reference("Cpu")),
+                ("addressUnitBits", uint(u32::MAX as u64)),
+                ("width", uint(u32::MAX as u64)),
+                ("peripherals", array(reference("Peripheral"))),
+                ("schemaVersion", string()),
+            ]),
+            register_properties(),
+        ],
+        &["name", "peripherals"],
+    );
+    let root = device.as_object_mut().unwrap();
+    root.insert(
+        "$schema".into(),
+        "http://json-schema.org/draft-07/schema#".into(),
+    );
+    root.insert("$id".into(), SCHEMA_ID.into());
+    root.insert("title".into(), "SVD device".into());
+    root.insert("version".into(), FORMAT_VERSION.into());
+    root.insert("definitions".into(), definitions());
+    device
+}
+
+/// Serialize the device with its `formatVersion` and `$schema`
+pub fn versioned(device: &Device) -> Value {
+    let mut value = serde_json::to_value(device).expect("Device can be serialized");
+    let map = value.as_object_mut().unwrap();
+    map.insert("$schema".into(), SCHEMA_ID.into());
+    map.insert("formatVersion".into(), FORMAT_VERSION.into());
+    value
+}
+
+fn definitions() -> Value {
+    let mut definitions = Map::new();
+    let mut add = |name: &str, schema: Value| {
+        definitions.insert(name.into(), schema);
+    };
+
+    add(
+        "Cpu",
+        object(
+            vec![properties(&[
+                ("name", string()),
+                ("revision", string()),
+                ("endian", reference("Endian")),
+                ("mpuPresent", boolean()),
+                ("fpuPresent", boolean()),
+                ("fpuDP", nullable_boolean()),
+                ("dspPresent", nullable_boolean()),
+                ("icachePresent", nullable_boolean()),
+                ("dcachePresent", nullable_boolean()),
+                ("itcmPresent", nullable_boolean()),
+                ("dtcmPresent", nullable_boolean()),
+                ("vtorPresent", nullable_boolean()),
+                ("nvicPrioBits", uint(u32::MAX as u64)),
+                ("vendorSystickConfig", boolean()),
+                ("deviceNumInterrupts", nullable_uint(u32::MAX as u64)),
+                ("sauNumRegions", nullable_uint(u32::MAX as u64)),
+            ])],
+            &[
+                "name",
+                "revision",
+                "endian",
+                "mpuPresent",
+                "fpuPresent",
+                "nvicPrioBits",
+                "vendorSystickConfig",
+            ],
+        ),
+    );
+    add("Endian", strings(&["little", "big", "selectable", "other"]));
+    add(
+        "Peripheral",
+        dimmable(object(
+            vec![
+                properties(&[
+                    ("name", string()),
+                    ("displayName", string()),
+                    ("version", string()),
+                    ("description", string()),
+                    ("alternatePeripheral", string()),
+                    ("groupName", string()),
+                    ("prependToName", string()),
+                    ("appendToName", string()),
+                    ("headerStructName", string()),
+                    ("baseAddress", uint64()),
+                    ("addressBlock", array(reference("AddressBlock"))),
+                    ("interrupt", array(reference("Interrupt"))),
+                    ("registers", array(reference("RegisterCluster"))),
+                    ("derivedFrom", string()),
+                ]),
+                register_properties(),
+            ],
+            &["name", "baseAddress"],
+        )),
+    );
+    add(
+        "AddressBlock",
+        object(
+            vec![properties(&[
+                ("offset", uint(u32::MAX as u64)),
+                ("size", uint(u32::MAX as u64)),
+                ("usage", strings(&["registers", "buffer", "reserved"])),
+                ("protection", reference("Protection")),
+            ])],
+            &["offset", "size", "usage"],
+        ),
+    );
+    add(
+        "Interrupt",
+        object(
+            vec![properties(&[
+                ("name", string()),
+                ("description", string()),
+                ("value", uint(u32::MAX as u64)),
+            ])],
+            &["name", "value"],
+        ),
+    );
+    add(
+        "RegisterCluster",
+        json!({
+            "oneOf": [
+                object(vec![properties(&[("register", reference("Register"))])], &["register"]),
+                object(vec![properties(&[("cluster", reference("Cluster"))])], &["cluster"]),
+            ]
+        }),
+    );
+    add(
+        "Register",
+        dimmable(object(
+            vec![
+                properties(&[
+                    ("name", string()),
+                    ("displayName", string()),
+                    ("description", string()),
+                    ("alternateGroup", string()),
+                    ("alternateRegister", string()),
+                    ("addressOffset", uint(u32::MAX as u64)),
+                    ("modifiedWriteValues", reference("ModifiedWriteValues")),
+                    ("writeConstraint", reference("WriteConstraint")),
+                    ("readAction", reference("ReadAction")),
+                    ("fields", array(reference("Field"))),
+                    ("derivedFrom", string()),
+                ]),
+                register_properties(),
+            ],
+            &["name", "addressOffset"],
+        )),
+    );
+    add(
+        "Cluster",
+        dimmable(object(
+            vec![
+                properties(&[
+                    ("name", string()),
+                    ("description", string()),
+                    ("alternateCluster", string()),
+                    ("headerStructName", string()),
+                    ("addressOffset", uint(u32::MAX as u64)),
+                    ("children", array(reference("RegisterCluster"))),
+                    ("derivedFrom", string()),
+                ]),
+                register_properties(),
+            ],
+            &["name", "addressOffset", "children"],
+        )),
+    );
+    let mut field = dimmable(object(
+        vec![
+            properties(&[
+                ("name", string()),
+                ("description", string()),
+                ("access", reference("Access")),
+                ("modifiedWriteValues", reference("ModifiedWriteValues")),
+                ("writeConstraint", reference("WriteConstraint")),
+                ("readAction", reference("ReadAction")),
+                ("enumeratedValues", array(reference("EnumeratedValues"))),
+                ("derivedFrom", string()),
+            ]),
+            properties(&[
+                ("bitRange", bit_range()),
+                ("bitOffset", uint(u32::MAX as u64)),
+                ("bitWidth", uint(u32::MAX as u64)),
+                ("lsb", uint(u32::MAX as u64)),
+                ("msb", uint(u32::MAX as u64)),
+            ]),
+        ],
+        &["name"],
+    ));
+    // The bit range is written in one of the three forms of the SVD
+    let forms: &[&[&str]] = &[&["bitRange"], &["bitOffset", "bitWidth"], &["lsb", "msb"]];
+    let forms: Vec<Value> = forms
+        .iter()
+        .map(|form| {
+            let others: Vec<Value> = forms
+                .iter()
+                .filter(|other| other != &form)
+                .flat_map(|other| other.iter())
+                .map(|key| json!({ "required": [key] }))
+                .collect();
+            json!({ "required": form, "not": { "anyOf": others } })
+        })
+        .collect();
+    field
+        .as_object_mut()
+        .unwrap()
+        .insert("oneOf".into(), forms.into());
+    add("Field", field);
+    add(
+        "EnumeratedValues",
+        object(
+            vec![properties(&[
+                ("name", string()),
+                ("usage", strings(&["read", "write", "read-write"])),
+                ("derived_from", string()),
+                ("values", array(reference("EnumeratedValue"))),
+            ])],
+            &["values"],
+        ),
+    );
+    add(
+        "EnumeratedValue",
+        object(
+            vec![properties(&[
+                ("name", string()),
+                ("description", string()),
+                ("value", uint64()),
+                ("is_default", boolean()),
+            ])],
+            &["name"],
+        ),
+    );
+    add(
+        "DimArrayIndex",
+        object(
+            vec![properties(&[
+                ("headerEnumName", string()),
+                ("values", array(reference("EnumeratedValue"))),
+            ])],
+            &["values"],
+        ),
+    );
+    add(
+        "Access",
+        strings(&[
+            "read-only",
+            "read-write",
+            "read-writeOnce",
+            "writeOnce",
+            "write-only",
+        ]),
+    );
+    add("Protection", strings(&["s", "n", "p"]));
+    add(
+        "ModifiedWriteValues",
+        strings(&[
+            "oneToClear",
+            "oneToSet",
+            "oneToToggle",
+            "zeroToClear",
+            "zeroToSet",
+            "zeroToToggle",
+            "clear",
+            "set",
+            "modify",
+        ]),
+    );
+    add(
+        "ReadAction",
+        strings(&["clear", "set", "modify", "modifyExternal"]),
+    );
+    add(
+        "WriteConstraint",
+        json!({
+            "oneOf": [
+                object(vec![properties(&[("WriteAsRead", boolean())])], &["WriteAsRead"]),
+                object(
+                    vec![properties(&[("UseEnumeratedValues", boolean())])],
+                    &["UseEnumeratedValues"],
+                ),
+                object(
+                    vec![properties(&[(
+                        "Range",
+                        object(
+                            vec![properties(&[
+                                ("minimum", uint64()),
+                                ("maximum", uint64()),
+                            ])],
+                            &["minimum", "maximum"],
+                        ),
+                    )])],
+                    &["Range"],
+                ),
+            ]
+        }),
+    );
+    Value::Object(definitions)
+}
+
+/// Properties of [`RegisterProperties`](super::RegisterProperties), flattened in their element
+fn register_properties() -> Map<String, Value> {
+    properties(&[
+        ("size", uint(u32::MAX as u64)),
+        ("access", reference("Access")),
+        ("protection", reference("Protection")),
+        ("resetValue", uint64()),
+        ("resetMask", uint64()),
+    ])
+}
+
+/// Add the properties of [`DimElement`](super::DimElement) to an element which can be an array.
+/// `dim` and `dimIncrement` make the element an array, so the others need them.
+fn dimmable(mut element: Value) -> Value {
+    let map = element.as_object_mut().unwrap();
+    let dim = properties(&[
+        ("dim", uint(u32::MAX as u64)),
+        ("dimIncrement", uint(u32::MAX as u64)),
+        ("dimIndex", array(string())),
+        ("dimName", string()),
+        ("dimArrayIndex", reference("DimArrayIndex")),
+    ]);
+    map["properties"].as_object_mut().unwrap().extend(dim);
+    map.insert(
+        "dependencies".into(),
+        json!({
+            "dim": ["dimIncrement"],
+            "dimIncrement": ["dim"],
+            "dimIndex": ["dim", "dimIncrement"],
+            "dimName": ["dim", "dimIncrement"],
+            "dimArrayIndex": ["dim", "dimIncrement"],
+        }),
+    );
+    element
+}
+
+fn object(properties: Vec<Map<String, Value>>, required: &[&str]) -> Value {
+    let mut all = Map::new();
+    for p in properties {
+        all.extend(p);
+    }
+    json!({
+        "type": "object",
+        "properties": all,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+fn properties(properties: &[(&str, Value)]) -> Map<String, Value> {
+    properties
+        .iter()
+        .map(|(name, schema)| (name.to_string(), schema.clone()))
+        .collect()
+}
+
+fn reference(name: &str) -> Value {
+    json!({ "$ref": format!("#/definitions/{}", name) })
+}
+
+fn array(items: Value) -> Value {
+    json!({ "type": "array", "items": items })
+}
+
+fn string() -> Value {
+    json!({ "type": "string" })
+}
+
+fn strings(values: &[&str]) -> Value {
+    json!({ "type": "string", "enum": values })
+}
+
+fn boolean() -> Value {
+    json!({ "type": "boolean" })
+}
+
+fn nullable_boolean() -> Value {
+    json!({ "type": ["boolean", "null"] })
+}
+
+fn nullable_uint(max: u64) -> Value {
+    json!({ "type": ["integer", "null"], "minimum": 0, "maximum": max })
+}
+
+fn uint(max: u64) -> Value {
+    json!({ "type": "integer", "minimum": 0, "maximum": max })
+}
+
+/// 64-bit integer, or its decimal, hexadecimal or binary string
+fn uint64() -> Value {
+    json!({
+        "anyOf": [
+            uint(u64::MAX),
+            { "type": "string", "pattern": "^(0[xX][0-9a-fA-F]+|0[bB][01]+|[0-9]+)$" },
+        ]
+    })
+}
+
+fn bit_range() -> Value {
+    json!({ "type": "string", "pattern": r"^\[[0-9]+:[0-9]+\]$" })
+}
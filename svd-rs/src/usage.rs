@@ -22,6 +22,16 @@ impl Default for Usage {
 }
 
 impl Usage {
+    /// Whether the values can be used for reading.
+    pub fn can_read(self) -> bool {
+        self != Self::Write
+    }
+
+    /// Whether the values can be used for writing.
+    pub fn can_write(self) -> bool {
+        self != Self::Read
+    }
+
     /// Parse a string into an [`Usage`] value, returning [`Option::None`] if the string is not valid.
     pub fn parse_str(s: &str) -> Option<Self> {
         match s {
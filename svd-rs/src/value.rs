@@ -0,0 +1,220 @@
+//! Decoding and composition of raw register values.
+//!
+//! [`RegisterInfo::decode`] splits a raw value into the values of the fields,
+//! with field arrays expanded, and looks up the enumerated value of each field.
+//! Enumerated values containers with `usage` `write` are ignored while decoding.
+//! A value with `isDefault` matches any value which is not listed explicitly.
+//!
+//! [`ValueBuilder`] is the inverse: it starts from the reset value of the register
+//! and sets field instances by number or by the name of an enumerated value,
+//! using the containers with `usage` `write`.
+//!
+//! `derivedFrom` of enumerated values is not resolved.
+
+use super::{
+    addressindex::{field_instances, FieldInstance},
+    checked_shl, checked_shr, writeconstraint, EnumeratedValue, FieldInfo, RegisterInfo,
+    RegisterProperties, SvdError, Usage,
+};
+
+/// Errors for [`ValueBuilder::build`]
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    /// The register has no field with the name
+    #[error("Field `{0}` not found")]
+    UnknownField(String),
+    /// The field has no enumerated value with the name for writing
+    #[error("Field `{0}` has no enumerated value `{1}` for writing")]
+    UnknownEnumeratedValue(String, String),
+    /// The value can't be written to the field
+    #[error("Write to field `{0}` is not allowed: {1}")]
+    WriteNotAllowed(String, writeconstraint::Error),
+}
+
+/// A decoded register value
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegisterValue<'a> {
+    /// The raw value
+    pub value: u64,
+    /// Values of the field instances, in the order of the register
+    pub fields: Vec<FieldValue<'a>>,
+    /// Mask of the bits of the register which are not covered by any field
+    pub uncovered_mask: u64,
+}
+
+/// A decoded field value
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldValue<'a> {
+    /// The field instance
+    pub field: FieldInstance<'a>,
+    /// Value of the field, shifted to bit 0
+    pub value: u64,
+    /// The matching enumerated value, if any
+    pub enumerated_value: Option<&'a EnumeratedValue>,
+}
+
+impl<'a> RegisterValue<'a> {
+    /// Bits of the raw value which are not covered by any field
+    pub fn uncovered(&self) -> u64 {
+        self.value & self.uncovered_mask
+    }
+
+    /// Get the value of the field instance with `name`
+    pub fn field(&self, name: &str) -> Option<&FieldValue<'a>> {
+        self.fields.iter().find(|f| f.field.name == name)
+    }
+}
+
+impl RegisterInfo {
+    /// Decode the raw value of the register.
+    ///
+    /// `properties` are the register properties inherited from the enclosing elements,
+    /// the ones of the register take precedence.
+    /// Registers without a size are assumed to be 32 bits wide.
+    pub fn decode(&self, value: u64, properties: RegisterProperties) -> RegisterValue {
+        let size = self.properties.inherit(&properties).size.unwrap_or(32);
+        let mut uncovered_mask = bit_mask(size);
+        let fields = field_instances(self.fields.as_deref().unwrap_or(&[]))
+            .into_iter()
+            .map(|field| {
+                let mask = bit_mask(field.bit_range.width);
+                uncovered_mask &= !checked_shl(mask, field.bit_range.offset);
+                let value = checked_shr(value, field.bit_range.offset) & mask;
+                FieldValue {
+                    enumerated_value: field.field.enumerated_value(value, Usage::Read),
+                    field,
+                    value,
+                }
+            })
+            .collect();
+        RegisterValue {
+            value,
+            fields,
+            uncovered_mask,
+        }
+    }
+}
+
+/// Value of a field in a [`ValueBuilder`]
+#[derive(Clone, Debug, PartialEq)]
+enum Write {
+    Value(u64),
+    Enumerated(String),
+}
+
+/// Builder of a raw register value from field values
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValueBuilder<'a> {
+    register: &'a RegisterInfo,
+    properties: RegisterProperties,
+    writes: Vec<(String, Write)>,
+}
+
+impl<'a> ValueBuilder<'a> {
+    /// Create a builder for the register.
+    ///
+    /// `properties` are the effective register properties, including the inherited ones.
+    /// The initial value is `resetValue` with the bits outside of `resetMask` cleared.
+    pub fn new(register: &'a RegisterInfo, properties: RegisterProperties) -> Self {
+        Self {
+            register,
+            properties,
+            writes: Vec::new(),
+        }
+    }
+    /// Set the field instance with `name` to a number
+    pub fn field(mut self, name: &str, value: u64) -> Self {
+        self.writes.push((name.to_string(), Write::Value(value)));
+        self
+    }
+    /// Set the field instance with `name` to the value of an enumerated value
+    pub fn enumerated(mut self, name: &str, value: &str) -> Self {
+        self.writes
+            .push((name.to_string(), Write::Enumerated(value.to_string())));
+        self
+    }
+    /// Validate the field values and compose the raw value
+    pub fn build(self) -> Result<u64, SvdError> {
+        let reset = self.properties.reset_value.unwrap_or(0)
+            & self.properties.reset_mask.unwrap_or(u64::MAX);
+        let mut value = reset;
+        let fields = field_instances(self.register.fields.as_deref().unwrap_or(&[]));
+        for (name, write) in &self.writes {
+            let field = fields
+                .iter()
+                .find(|f| &f.name == name)
+                .ok_or_else(|| Error::UnknownField(name.clone()))?;
+            let info: &FieldInfo = field.field;
+            let v = match write {
+                Write::Value(v) => *v,
+                Write::Enumerated(e) => info
+                    .enumerated_value_by_name(e, Usage::Write)
+                    .and_then(|e| e.value)
+                    .ok_or_else(|| Error::UnknownEnumeratedValue(name.clone(), e.clone()))?,
+            };
+            let rejected = |e| Error::WriteNotAllowed(name.clone(), e);
+            let access = info.access.or(self.properties.access).unwrap_or_default();
+            if !access.can_write() {
+                return Err(rejected(writeconstraint::Error::ReadOnly).into());
+            }
+            let mask = bit_mask(field.bit_range.width);
+            let current = checked_shr(reset, field.bit_range.offset) & mask;
+            match info.check_write(v, info.write_constraint.or(self.register.write_constraint)) {
+                // The reset value is the last read value
+                Err(writeconstraint::Error::WriteAsRead) if v == current => {}
+                result => result.map_err(rejected)?,
+            }
+            value = (value & !checked_shl(mask, field.bit_range.offset))
+                | checked_shl(v, field.bit_range.offset);
+        }
+        Ok(value)
+    }
+}
+
+impl RegisterInfo {
+    /// Create a [`ValueBuilder`] with the register properties inherited from
+    /// the enclosing elements, the ones of the register take precedence
+    pub fn value_builder(&self, properties: RegisterProperties) -> ValueBuilder {
+        ValueBuilder::new(self, self.properties.inherit(&properties))
+    }
+}
+
+impl FieldInfo {
+    /// Find the enumerated value matching `value` in the containers usable for `usage`.
+    ///
+    /// Values listed explicitly take precedence over values with `isDefault`.
+    pub fn enumerated_value(&self, value: u64, usage: Usage) -> Option<&EnumeratedValue> {
+        let values = || {
+            self.enumerated_values
+                .iter()
+                .filter(move |e| matches_usage(e.usage(), usage))
+                .flat_map(|e| e.values.iter())
+        };
+        values()
+            .find(|v| v.value == Some(value))
+            .or_else(|| values().find(|v| v.is_default == Some(true)))
+    }
+
+    /// Find the enumerated value with `name` in the containers usable for `usage`
+    pub fn enumerated_value_by_name(&self, name: &str, usage: Usage) -> Option<&EnumeratedValue> {
+        self.enumerated_values
+            .iter()
+            .filter(|e| matches_usage(e.usage(), usage))
+            .flat_map(|e| e.values.iter())
+            .find(|v| v.name == name)
+    }
+}
+
+/// Return `true` if a container with `usage` can be used for an `access`
+fn matches_usage(usage: Usage, access: Usage) -> bool {
+    (access.can_read() && usage.can_read()) || (access.can_write() && usage.can_write())
+}
+
+/// Mask of `width` low bits
+pub(crate) fn bit_mask(width: u32) -> u64 {
+    if width >= 64 {
+        u64::MAX
+    } else {
+        (1 << width) - 1
+    }
+}
@@ -0,0 +1,332 @@
+//! Indexed, navigable view of a [`Device`].
+//!
+//! [`DeviceView`] borrows a device and gives every peripheral, cluster, register
+//! and field an [`ElementId`]. IDs are indexes in depth-first order, so they are
+//! stable for the same device. Each [`Node`] knows its parent, its children, its
+//! path, its absolute address and its resolved register properties.
+//!
+//! Properties are resolved like in the SVD: the element's own properties come first,
+//! then the ones of the element it is derived from, then the ones of the enclosing
+//! elements and the device. `derivedFrom` is looked up as a full path, then as
+//! a sibling of the element.
+//!
+//! Arrays are not expanded: an array has one node with the template name
+//! (`GPIO%s`), and the address of its first instance.
+//!
+//! ```
+//! use svd_rs::Device;
+//!
+//! fn registers_of(device: &Device, peripheral: &str) -> Vec<String> {
+//!     let view = device.view();
+//!     let id = match view.find(peripheral) {
+//!         Some(id) => id,
+//!         None => return Vec::new(),
+//!     };
+//!     view.children(id)
+//!         .iter()
+//!         .map(|&c| view[c].path.clone())
+//!         .collect()
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::ops::Index;
+use std::ptr;
+
+use super::{query::Element, Device, RegisterCluster, RegisterProperties};
+
+/// Identifier of an element in a [`DeviceView`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ElementId(usize);
+
+impl ElementId {
+    /// Index of the element in depth-first order
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// An element of a [`DeviceView`]
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Node<'a> {
+    /// The element
+    pub element: Element<'a>,
+    /// Enclosing element, `None` for peripherals
+    pub parent: Option<ElementId>,
+    /// Clusters and registers of a peripheral or cluster, fields of a register
+    pub children: Vec<ElementId>,
+    /// Path of the element, like `PERIPHERAL.CLUSTER.REGISTER.FIELD`
+    pub path: String,
+    /// Absolute address of the element, or of its first instance for arrays.
+    ///
+    /// Fields have the address of their register.
+    pub address: u64,
+    /// Register properties of the element, filled with the ones inherited from
+    /// the element it is derived from, the enclosing elements and the device.
+    ///
+    /// Fields have the properties of their register, with their own access.
+    pub properties: RegisterProperties,
+}
+
+impl<'a> Node<'a> {
+    /// Name of the element
+    pub fn name(&self) -> &'a str {
+        element_name(self.element)
+    }
+}
+
+/// Immutable index of a [`Device`] with parent links and lookup tables
+#[derive(Clone, Debug)]
+pub struct DeviceView<'a> {
+    device: &'a Device,
+    nodes: Vec<Node<'a>>,
+    peripherals: Vec<ElementId>,
+    paths: HashMap<String, ElementId>,
+    names: HashMap<&'a str, Vec<ElementId>>,
+}
+
+impl<'a> DeviceView<'a> {
+    /// Build the view of the device
+    pub fn new(device: &'a Device) -> Self {
+        let mut view = Self {
+            device,
+            nodes: Vec::new(),
+            peripherals: Vec::new(),
+            paths: HashMap::new(),
+            names: HashMap::new(),
+        };
+        for p in &device.peripherals {
+            let id = view.push(Element::Peripheral(p), None, p.base_address);
+            view.peripherals.push(id);
+            if let Some(children) = &p.registers {
+                view.add_children(id, children);
+            }
+        }
+        // Parents come before their children, so their properties are already resolved
+        for i in 0..view.nodes.len() {
+            let parent = match view.nodes[i].parent {
+                Some(parent) => view.nodes[parent.0].properties,
+                None => device.default_register_properties,
+            };
+            view.nodes[i].properties = view.own_properties(ElementId(i)).inherit(&parent);
+        }
+        view
+    }
+
+    /// Properties of the element and of the elements it is derived from
+    fn own_properties(&self, id: ElementId) -> RegisterProperties {
+        let mut properties = RegisterProperties::new();
+        let mut current = Some(id);
+        // Stop on cycles of `derivedFrom`
+        for _ in 0..self.nodes.len() {
+            let id = match current {
+                Some(id) => id,
+                None => break,
+            };
+            let own = match self[id].element {
+                Element::Peripheral(p) => p.default_register_properties,
+                Element::Cluster(c) => c.default_register_properties,
+                Element::Register(r) => r.properties,
+                Element::Field(f) => RegisterProperties::new().access(f.access),
+            };
+            properties = properties.inherit(&own);
+            current = self.derived_from(id);
+        }
+        properties
+    }
+
+    /// Element of the same kind the element is derived from
+    fn derived_from(&self, id: ElementId) -> Option<ElementId> {
+        let node = &self[id];
+        let from = match node.element {
+            Element::Peripheral(p) => p.derived_from.as_deref(),
+            Element::Cluster(c) => c.derived_from.as_deref(),
+            Element::Register(r) => r.derived_from.as_deref(),
+            Element::Field(f) => f.derived_from.as_deref(),
+        }?;
+        let sibling = node
+            .parent
+            .and_then(|parent| self.find(&format!("{}.{}", self.path(parent), from)));
+        self.find(from)
+            .or(sibling)
+            .filter(|&source| same_kind(self[source].element, node.element))
+    }
+
+    fn push(&mut self, element: Element<'a>, parent: Option<ElementId>, address: u64) -> ElementId {
+        let id = ElementId(self.nodes.len());
+        let name = element_name(element);
+        let path = match parent {
+            Some(parent) => format!("{}.{}", self.nodes[parent.0].path, name),
+            None => name.to_string(),
+        };
+        if let Some(parent) = parent {
+            self.nodes[parent.0].children.push(id);
+        }
+        self.paths.entry(path.clone()).or_insert(id);
+        self.names.entry(name).or_default().push(id);
+        self.nodes.push(Node {
+            element,
+            parent,
+            children: Vec::new(),
+            path,
+            address,
+            properties: RegisterProperties::new(),
+        });
+        id
+    }
+
+    fn add_children(&mut self, parent: ElementId, children: &'a [RegisterCluster]) {
+        let base = self.nodes[parent.0].address;
+        for rc in children {
+            match rc {
+                RegisterCluster::Cluster(c) => {
+                    let address = base + c.address_offset as u64;
+                    let id = self.push(Element::Cluster(c), Some(parent), address);
+                    self.add_children(id, &c.children);
+                }
+                RegisterCluster::Register(r) => {
+                    let address = base + r.address_offset as u64;
+                    let id = self.push(Element::Register(r), Some(parent), address);
+                    for f in r.fields.iter().flatten() {
+                        self.push(Element::Field(f), Some(id), address);
+                    }
+                }
+            }
+        }
+    }
+
+    /// The device of the view
+    pub fn device(&self) -> &'a Device {
+        self.device
+    }
+
+    /// Number of elements
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Return `true` if the device has no peripherals
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Get the node of the element, `None` if the ID doesn't belong to the view
+    pub fn get(&self, id: ElementId) -> Option<&Node<'a>> {
+        self.nodes.get(id.0)
+    }
+
+    /// Iterate over all elements in depth-first order
+    pub fn iter(&self) -> impl Iterator<Item = (ElementId, &Node<'a>)> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (ElementId(i), n))
+    }
+
+    /// IDs of the peripherals
+    pub fn peripherals(&self) -> &[ElementId] {
+        &self.peripherals
+    }
+
+    /// Enclosing element of the element
+    pub fn parent(&self, id: ElementId) -> Option<ElementId> {
+        self[id].parent
+    }
+
+    /// Children of the element
+    pub fn children(&self, id: ElementId) -> &[ElementId] {
+        &self[id].children
+    }
+
+    /// Child of the element with `name`
+    pub fn child(&self, id: ElementId, name: &str) -> Option<ElementId> {
+        self.children(id)
+            .iter()
+            .copied()
+            .find(|&c| self[c].name() == name)
+    }
+
+    /// Enclosing elements of the element, starting from its parent
+    pub fn ancestors(&self, id: ElementId) -> impl Iterator<Item = ElementId> + '_ {
+        let mut current = self.parent(id);
+        std::iter::from_fn(move || {
+            let id = current?;
+            current = self.parent(id);
+            Some(id)
+        })
+    }
+
+    /// Peripheral containing the element, or the element itself for peripherals
+    pub fn peripheral_of(&self, id: ElementId) -> ElementId {
+        self.ancestors(id).last().unwrap_or(id)
+    }
+
+    /// Path of the element
+    pub fn path(&self, id: ElementId) -> &str {
+        &self[id].path
+    }
+
+    /// Find the element with the path, like `PERIPHERAL.REGISTER`
+    pub fn find(&self, path: &str) -> Option<ElementId> {
+        self.paths.get(path).copied()
+    }
+
+    /// All elements with the name, in depth-first order
+    pub fn by_name(&self, name: &str) -> &[ElementId] {
+        self.names.get(name).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// ID of an element borrowed from the device of the view
+    pub fn id_of(&self, element: Element) -> Option<ElementId> {
+        self.by_name(element_name(element))
+            .iter()
+            .copied()
+            .find(|&id| same_element(self[id].element, element))
+    }
+}
+
+fn element_name(element: Element) -> &str {
+    match element {
+        Element::Peripheral(p) => &p.name,
+        Element::Cluster(c) => &c.name,
+        Element::Register(r) => &r.name,
+        Element::Field(f) => &f.name,
+    }
+}
+
+fn same_kind(a: Element, b: Element) -> bool {
+    matches!(
+        (a, b),
+        (Element::Peripheral(_), Element::Peripheral(_))
+            | (Element::Cluster(_), Element::Cluster(_))
+            | (Element::Register(_), Element::Register(_))
+            | (Element::Field(_), Element::Field(_))
+    )
+}
+
+/// Return `true` if both are the same element of the device, not just equal ones
+fn same_element(a: Element, b: Element) -> bool {
+    match (a, b) {
+        (Element::Peripheral(a), Element::Peripheral(b)) => ptr::eq(a, b),
+        (Element::Cluster(a), Element::Cluster(b)) => ptr::eq(a, b),
+        (Element::Register(a), Element::Register(b)) => ptr::eq(a, b),
+        (Element::Field(a), Element::Field(b)) => ptr::eq(a, b),
+        _ => false,
+    }
+}
+
+impl<'a> Index<ElementId> for DeviceView<'a> {
+    type Output = Node<'a>;
+
+    fn index(&self, id: ElementId) -> &Node<'a> {
+        &self.nodes[id.0]
+    }
+}
+
+impl Device {
+    /// Build an indexed view of the device. See [`view`](crate::view).
+    pub fn view(&self) -> DeviceView {
+        DeviceView::new(self)
+    }
+}
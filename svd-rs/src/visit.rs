@@ -0,0 +1,391 @@
+//! Traversal of the [`Device`] tree.
+//!
+//! [`Visit`], [`VisitMut`] and [`Fold`] have a method for each kind of element.
+//! The default implementations recurse into the children of the element through
+//! the `walk_*`, `walk_*_mut` and `fold_*` functions, so an implementation only
+//! needs to override the methods for the elements it is interested in, and call
+//! the corresponding function to continue the recursion.
+//!
+//! ```
+//! use svd_rs::visit::{self, Context, Visit};
+//! use svd_rs::{Device, Register};
+//!
+//! #[derive(Default)]
+//! struct Registers(Vec<String>);
+//!
+//! impl Visit for Registers {
+//!     fn visit_register(&mut self, r: &Register, ctx: &Context) {
+//!         self.0.push(ctx.path.clone());
+//!         visit::walk_register(self, r, ctx);
+//!     }
+//! }
+//!
+//! fn register_paths(device: &Device) -> Vec<String> {
+//!     let mut v = Registers::default();
+//!     v.visit_device(device, &Context::new());
+//!     v.0
+//! }
+//! ```
+
+use super::{
+    Cluster, Device, EnumeratedValue, EnumeratedValues, Field, Peripheral, Register,
+    RegisterCluster, RegisterProperties,
+};
+
+/// Position of the visited element in the device
+#[derive(Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct Context {
+    /// Path of the element, like `PERIPHERAL.CLUSTER.REGISTER.FIELD`.
+    ///
+    /// Enumerated values containers share the path of their field, their values
+    /// are below the usage of the container, like `FIELD.read-write.VALUE`, as in
+    /// [`diff`](crate::diff). The path of the device is empty.
+    pub path: String,
+    /// Register properties inherited from the enclosing elements,
+    /// not including the properties of the element itself
+    pub properties: RegisterProperties,
+}
+
+impl Context {
+    /// Create a context for the device root.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Context of a child element with `name`, inheriting `properties` of the parent
+    fn child(&self, name: &str, properties: &RegisterProperties) -> Self {
+        let path = if self.path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", self.path, name)
+        };
+        Self {
+            path,
+            properties: properties.inherit(&self.properties),
+        }
+    }
+}
+
+/// Read-only traversal of the device
+pub trait Visit {
+    /// Visit a device
+    fn visit_device(&mut self, d: &Device, ctx: &Context) {
+        walk_device(self, d, ctx)
+    }
+    /// Visit a peripheral
+    fn visit_peripheral(&mut self, p: &Peripheral, ctx: &Context) {
+        walk_peripheral(self, p, ctx)
+    }
+    /// Visit a cluster
+    fn visit_cluster(&mut self, c: &Cluster, ctx: &Context) {
+        walk_cluster(self, c, ctx)
+    }
+    /// Visit a register
+    fn visit_register(&mut self, r: &Register, ctx: &Context) {
+        walk_register(self, r, ctx)
+    }
+    /// Visit a field
+    fn visit_field(&mut self, f: &Field, ctx: &Context) {
+        walk_field(self, f, ctx)
+    }
+    /// Visit a container of enumerated values
+    fn visit_enumerated_values(&mut self, e: &EnumeratedValues, ctx: &Context) {
+        walk_enumerated_values(self, e, ctx)
+    }
+    /// Visit an enumerated value
+    fn visit_enumerated_value(&mut self, _e: &EnumeratedValue, _ctx: &Context) {}
+}
+
+/// Visit the peripherals of the device
+pub fn walk_device<V: Visit + ?Sized>(v: &mut V, d: &Device, ctx: &Context) {
+    let props = d.default_register_properties.inherit(&ctx.properties);
+    for p in &d.peripherals {
+        v.visit_peripheral(p, &ctx.child(&p.name, &props));
+    }
+}
+
+/// Visit the registers and clusters of the peripheral
+pub fn walk_peripheral<V: Visit + ?Sized>(v: &mut V, p: &Peripheral, ctx: &Context) {
+    if let Some(children) = &p.registers {
+        walk_children(v, children, ctx, &p.default_register_properties);
+    }
+}
+
+/// Visit the registers and clusters of the cluster
+pub fn walk_cluster<V: Visit + ?Sized>(v: &mut V, c: &Cluster, ctx: &Context) {
+    walk_children(v, &c.children, ctx, &c.default_register_properties);
+}
+
+fn walk_children<V: Visit + ?Sized>(
+    v: &mut V,
+    children: &[RegisterCluster],
+    ctx: &Context,
+    properties: &RegisterProperties,
+) {
+    for rc in children {
+        match rc {
+            RegisterCluster::Register(r) => v.visit_register(r, &ctx.child(&r.name, properties)),
+            RegisterCluster::Cluster(c) => v.visit_cluster(c, &ctx.child(&c.name, properties)),
+        }
+    }
+}
+
+/// Visit the fields of the register
+pub fn walk_register<V: Visit + ?Sized>(v: &mut V, r: &Register, ctx: &Context) {
+    if let Some(fields) = &r.fields {
+        for f in fields {
+            v.visit_field(f, &ctx.child(&f.name, &r.properties));
+        }
+    }
+}
+
+/// Visit the enumerated values containers of the field
+pub fn walk_field<V: Visit + ?Sized>(v: &mut V, f: &Field, ctx: &Context) {
+    for e in &f.enumerated_values {
+        v.visit_enumerated_values(e, ctx);
+    }
+}
+
+/// Visit the values of the enumerated values container
+pub fn walk_enumerated_values<V: Visit + ?Sized>(v: &mut V, e: &EnumeratedValues, ctx: &Context) {
+    let props = RegisterProperties::new();
+    let ctx = ctx.child(e.usage().as_str(), &props);
+    for value in &e.values {
+        v.visit_enumerated_value(value, &ctx.child(&value.name, &props));
+    }
+}
+
+/// Mutable traversal of the device
+pub trait VisitMut {
+    /// Visit a device
+    fn visit_device_mut(&mut self, d: &mut Device, ctx: &Context) {
+        walk_device_mut(self, d, ctx)
+    }
+    /// Visit a peripheral
+    fn visit_peripheral_mut(&mut self, p: &mut Peripheral, ctx: &Context) {
+        walk_peripheral_mut(self, p, ctx)
+    }
+    /// Visit a cluster
+    fn visit_cluster_mut(&mut self, c: &mut Cluster, ctx: &Context) {
+        walk_cluster_mut(self, c, ctx)
+    }
+    /// Visit a register
+    fn visit_register_mut(&mut self, r: &mut Register, ctx: &Context) {
+        walk_register_mut(self, r, ctx)
+    }
+    /// Visit a field
+    fn visit_field_mut(&mut self, f: &mut Field, ctx: &Context) {
+        walk_field_mut(self, f, ctx)
+    }
+    /// Visit a container of enumerated values
+    fn visit_enumerated_values_mut(&mut self, e: &mut EnumeratedValues, ctx: &Context) {
+        walk_enumerated_values_mut(self, e, ctx)
+    }
+    /// Visit an enumerated value
+    fn visit_enumerated_value_mut(&mut self, _e: &mut EnumeratedValue, _ctx: &Context) {}
+}
+
+/// Visit the peripherals of the device
+pub fn walk_device_mut<V: VisitMut + ?Sized>(v: &mut V, d: &mut Device, ctx: &Context) {
+    let props = d.default_register_properties.inherit(&ctx.properties);
+    for p in &mut d.peripherals {
+        let ctx = ctx.child(&p.name, &props);
+        v.visit_peripheral_mut(p, &ctx);
+    }
+}
+
+/// Visit the registers and clusters of the peripheral
+pub fn walk_peripheral_mut<V: VisitMut + ?Sized>(v: &mut V, p: &mut Peripheral, ctx: &Context) {
+    let props = p.default_register_properties;
+    if let Some(children) = &mut p.registers {
+        walk_children_mut(v, children, ctx, &props);
+    }
+}
+
+/// Visit the registers and clusters of the cluster
+pub fn walk_cluster_mut<V: VisitMut + ?Sized>(v: &mut V, c: &mut Cluster, ctx: &Context) {
+    let props = c.default_register_properties;
+    walk_children_mut(v, &mut c.children, ctx, &props);
+}
+
+fn walk_children_mut<V: VisitMut + ?Sized>(
+    v: &mut V,
+    children: &mut [RegisterCluster],
+    ctx: &Context,
+    properties: &RegisterProperties,
+) {
+    for rc in children {
+        match rc {
+            RegisterCluster::Register(r) => {
+                let ctx = ctx.child(&r.name, properties);
+                v.visit_register_mut(r, &ctx);
+            }
+            RegisterCluster::Cluster(c) => {
+                let ctx = ctx.child(&c.name, properties);
+                v.visit_cluster_mut(c, &ctx);
+            }
+        }
+    }
+}
+
+/// Visit the fields of the register
+pub fn walk_register_mut<V: VisitMut + ?Sized>(v: &mut V, r: &mut Register, ctx: &Context) {
+    let props = r.properties;
+    if let Some(fields) = &mut r.fields {
+        for f in fields {
+            let ctx = ctx.child(&f.name, &props);
+            v.visit_field_mut(f, &ctx);
+        }
+    }
+}
+
+/// Visit the enumerated values containers of the field
+pub fn walk_field_mut<V: VisitMut + ?Sized>(v: &mut V, f: &mut Field, ctx: &Context) {
+    for e in &mut f.enumerated_values {
+        v.visit_enumerated_values_mut(e, ctx);
+    }
+}
+
+/// Visit the values of the enumerated values container
+pub fn walk_enumerated_values_mut<V: VisitMut + ?Sized>(
+    v: &mut V,
+    e: &mut EnumeratedValues,
+    ctx: &Context,
+) {
+    let props = RegisterProperties::new();
+    let ctx = ctx.child(e.usage().as_str(), &props);
+    for value in &mut e.values {
+        let ctx = ctx.child(&value.name, &props);
+        v.visit_enumerated_value_mut(value, &ctx);
+    }
+}
+
+/// Owned transformation of the device
+pub trait Fold {
+    /// Fold a device
+    fn fold_device(&mut self, d: Device, ctx: &Context) -> Device {
+        fold_device(self, d, ctx)
+    }
+    /// Fold a peripheral
+    fn fold_peripheral(&mut self, p: Peripheral, ctx: &Context) -> Peripheral {
+        fold_peripheral(self, p, ctx)
+    }
+    /// Fold a cluster
+    fn fold_cluster(&mut self, c: Cluster, ctx: &Context) -> Cluster {
+        fold_cluster(self, c, ctx)
+    }
+    /// Fold a register
+    fn fold_register(&mut self, r: Register, ctx: &Context) -> Register {
+        fold_register(self, r, ctx)
+    }
+    /// Fold a field
+    fn fold_field(&mut self, f: Field, ctx: &Context) -> Field {
+        fold_field(self, f, ctx)
+    }
+    /// Fold a container of enumerated values
+    fn fold_enumerated_values(&mut self, e: EnumeratedValues, ctx: &Context) -> EnumeratedValues {
+        fold_enumerated_values(self, e, ctx)
+    }
+    /// Fold an enumerated value
+    fn fold_enumerated_value(&mut self, e: EnumeratedValue, _ctx: &Context) -> EnumeratedValue {
+        e
+    }
+}
+
+/// Fold the peripherals of the device
+pub fn fold_device<F: Fold + ?Sized>(f: &mut F, mut d: Device, ctx: &Context) -> Device {
+    let props = d.default_register_properties.inherit(&ctx.properties);
+    d.peripherals = core::mem::take(&mut d.peripherals)
+        .into_iter()
+        .map(|p| {
+            let ctx = ctx.child(&p.name, &props);
+            f.fold_peripheral(p, &ctx)
+        })
+        .collect();
+    d
+}
+
+/// Fold the registers and clusters of the peripheral
+pub fn fold_peripheral<F: Fold + ?Sized>(
+    f: &mut F,
+    mut p: Peripheral,
+    ctx: &Context,
+) -> Peripheral {
+    let props = p.default_register_properties;
+    p.registers = p
+        .registers
+        .take()
+        .map(|children| fold_children(f, children, ctx, &props));
+    p
+}
+
+/// Fold the registers and clusters of the cluster
+pub fn fold_cluster<F: Fold + ?Sized>(f: &mut F, mut c: Cluster, ctx: &Context) -> Cluster {
+    let props = c.default_register_properties;
+    c.children = fold_children(f, core::mem::take(&mut c.children), ctx, &props);
+    c
+}
+
+fn fold_children<F: Fold + ?Sized>(
+    f: &mut F,
+    children: Vec<RegisterCluster>,
+    ctx: &Context,
+    properties: &RegisterProperties,
+) -> Vec<RegisterCluster> {
+    children
+        .into_iter()
+        .map(|rc| match rc {
+            RegisterCluster::Register(r) => {
+                let ctx = ctx.child(&r.name, properties);
+                RegisterCluster::Register(f.fold_register(r, &ctx))
+            }
+            RegisterCluster::Cluster(c) => {
+                let ctx = ctx.child(&c.name, properties);
+                RegisterCluster::Cluster(f.fold_cluster(c, &ctx))
+            }
+        })
+        .collect()
+}
+
+/// Fold the fields of the register
+pub fn fold_register<F: Fold + ?Sized>(f: &mut F, mut r: Register, ctx: &Context) -> Register {
+    let props = r.properties;
+    r.fields = r.fields.take().map(|fields| {
+        fields
+            .into_iter()
+            .map(|field| {
+                let ctx = ctx.child(&field.name, &props);
+                f.fold_field(field, &ctx)
+            })
+            .collect()
+    });
+    r
+}
+
+/// Fold the enumerated values containers of the field
+pub fn fold_field<F: Fold + ?Sized>(f: &mut F, mut field: Field, ctx: &Context) -> Field {
+    field.enumerated_values = core::mem::take(&mut field.enumerated_values)
+        .into_iter()
+        .map(|e| f.fold_enumerated_values(e, ctx))
+        .collect();
+    field
+}
+
+/// Fold the values of the enumerated values container
+pub fn fold_enumerated_values<F: Fold + ?Sized>(
+    f: &mut F,
+    mut e: EnumeratedValues,
+    ctx: &Context,
+) -> EnumeratedValues {
+    let props = RegisterProperties::new();
+    let usage = ctx.child(e.usage().as_str(), &props);
+    e.values = core::mem::take(&mut e.values)
+        .into_iter()
+        .map(|value| {
+            let ctx = usage.child(&value.name, &props);
+            f.fold_enumerated_value(value, &ctx)
+        })
+        .collect();
+    e
+}
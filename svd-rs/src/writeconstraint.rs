@@ -15,9 +15,41 @@ pub enum WriteConstraint {
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct WriteConstraintRange {
     /// Specify the smallest number to be written to the field
-    #[cfg_attr(feature = "serde", serde(rename = "minimum"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "minimum", deserialize_with = "crate::integer::deserialize")
+    )]
     pub min: u64,
     /// Specify the largest number to be written to the field.
-    #[cfg_attr(feature = "serde", serde(rename = "maximum"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "maximum", deserialize_with = "crate::integer::deserialize")
+    )]
     pub max: u64,
 }
+
+/// Reasons for rejecting a write, see [`FieldInfo::is_write_allowed`](crate::FieldInfo::is_write_allowed)
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    /// The field is read-only
+    #[error("Field is read-only")]
+    ReadOnly,
+    /// The value doesn't fit in the field
+    #[error("Value {0} doesn't fit in {1} bits")]
+    TooWide(u64, u32),
+    /// Only the last read value can be written
+    #[error("Only the last read value can be written")]
+    WriteAsRead,
+    /// The value is not listed in the enumerated values
+    #[error("Value {0} is not one of the enumerated values")]
+    NotEnumerated(u64),
+    /// The value is outside of the range
+    #[error("Value {0} is out of range {1}..={2}")]
+    OutOfRange(u64, u64, u64),
+    /// The range of the constraint doesn't fit in the field
+    #[error("Range {0}..={1} doesn't fit in {2} bits")]
+    RangeTooWide(u64, u64, u32),
+    /// The minimum of the range is above its maximum
+    #[error("Range {0}..={1} is empty")]
+    EmptyRange(u64, u64),
+}
@@ -0,0 +1,312 @@
+//! Register-file simulator.
+//! This instantiates an in-memory model of the registers of a [`Device`].
+//!
+//! Registers start at their reset values, `resetValue` with the bits outside of
+//! `resetMask` cleared. Reads and writes address whole registers and apply the
+//! `access`, `modifiedWriteValues` and `readAction` of each field, inherited from
+//! the register when the field doesn't set them:
+//!
+//! - bits which can't be read read as zero,
+//! - writes to read-only bits are ignored, and `writeOnce` bits only take
+//!   the first write after a reset,
+//! - `readAction` `clear` and `set` are applied after the read,
+//!   `modify` and `modifyExternal` are left to [`Hook`]s.
+//!
+//! Alternate registers share the value of the register at the same address.
+//! Custom peripheral behavior is added with a [`Hook`] for a peripheral instance.
+//!
+//! ```
+//! use svd_rs::Device;
+//! use svd_sim::{Error, Simulator};
+//!
+//! fn enable(device: &Device) -> Result<u64, Error> {
+//!     let mut sim = Simulator::new(device);
+//!     let cr = sim.registers().address_of("UART0.CR").unwrap();
+//!     sim.write(cr, 1)?;
+//!     sim.read(cr)
+//! }
+//! ```
+
+use std::collections::{hash_map::Entry, HashMap};
+
+use svd_rs as svd;
+
+use crate::svd::{
+    addressindex::{AddressIndex, RegisterInstance},
+    checked_shl,
+    value::ValueBuilder,
+    Access, Device, ModifiedWriteValues, ReadAction,
+};
+
+/// Errors of register accesses
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    /// No register at the address
+    #[error("No register at address {0:#x}")]
+    NoRegister(u64),
+    /// The address is inside of a register, but not at its start
+    #[error("Address {0:#x} is not the start of register at {1:#x}")]
+    Misaligned(u64, u64),
+    /// No peripheral instance with the name
+    #[error("Peripheral `{0}` not found")]
+    NoPeripheral(String),
+}
+
+/// Custom behavior of a peripheral
+pub trait Hook {
+    /// Called on a read of a register of the peripheral, before the read action
+    /// is applied. `value` is the value read, the returned value is returned by
+    /// [`Simulator::read`].
+    fn read(&mut self, registers: &mut Registers, register: &RegisterInstance, value: u64) -> u64 {
+        let _ = (registers, register);
+        value
+    }
+    /// Called after a write to a register of the peripheral is applied.
+    /// `written` is the value written on the bus.
+    fn write(&mut self, registers: &mut Registers, register: &RegisterInstance, written: u64) {
+        let _ = (registers, register, written);
+    }
+}
+
+/// Current values of the registers, indexed by address
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Registers {
+    values: HashMap<u64, u64>,
+    paths: HashMap<String, u64>,
+}
+
+impl Registers {
+    /// Get the value of the register at the address, without side effects
+    pub fn get(&self, address: u64) -> Option<u64> {
+        self.values.get(&address).copied()
+    }
+    /// Set the value of the register at the address, without side effects.
+    ///
+    /// Return `false` if there is no register at the address.
+    pub fn set(&mut self, address: u64, value: u64) -> bool {
+        match self.values.get_mut(&address) {
+            Some(v) => {
+                *v = value;
+                true
+            }
+            None => false,
+        }
+    }
+    /// Get the address of the register instance with the path, like `UART0.CR`
+    pub fn address_of(&self, path: &str) -> Option<u64> {
+        self.paths.get(path).copied()
+    }
+}
+
+/// Bits of a register with the same behavior
+#[derive(Clone, Copy, Debug)]
+struct Bits {
+    mask: u64,
+    access: Access,
+    modified_write_values: ModifiedWriteValues,
+    read_action: Option<ReadAction>,
+}
+
+#[derive(Clone, Debug)]
+struct State {
+    reset: u64,
+    bits: Vec<Bits>,
+    /// `writeOnce` bits which were written after the reset
+    written: u64,
+}
+
+/// In-memory model of the registers of a device
+pub struct Simulator<'a> {
+    index: AddressIndex<'a>,
+    registers: Registers,
+    states: HashMap<u64, State>,
+    hooks: HashMap<usize, Box<dyn Hook + 'a>>,
+}
+
+impl<'a> Simulator<'a> {
+    /// Create a simulator of the device with all registers at their reset values
+    pub fn new(device: &'a Device) -> Self {
+        let index = device.address_index();
+        let mut registers = Registers::default();
+        let mut states = HashMap::new();
+        for r in index.registers() {
+            registers.paths.insert(r.path.clone(), r.address);
+            // The register preferred by the index defines the behavior of the address
+            let r = match index.register_at(r.address) {
+                Some(location) => location.register,
+                None => continue,
+            };
+            if let Entry::Vacant(entry) = states.entry(r.address) {
+                let reset = ValueBuilder::new(r.register, r.properties)
+                    .build()
+                    .unwrap_or(0);
+                registers.values.insert(r.address, reset);
+                entry.insert(State {
+                    reset,
+                    bits: bits(r),
+                    written: 0,
+                });
+            }
+        }
+        Self {
+            index,
+            registers,
+            states,
+            hooks: HashMap::new(),
+        }
+    }
+
+    /// Add custom behavior to the peripheral instance with `name`.
+    ///
+    /// Replaces the previous hook of the peripheral.
+    pub fn hook(&mut self, name: &str, hook: impl Hook + 'a) -> Result<(), Error> {
+        let id = self
+            .index
+            .peripherals()
+            .iter()
+            .position(|p| p.name == name)
+            .ok_or_else(|| Error::NoPeripheral(name.to_string()))?;
+        self.hooks.insert(id, Box::new(hook));
+        Ok(())
+    }
+
+    /// Set all registers to their reset values
+    pub fn reset(&mut self) {
+        for (address, state) in self.states.iter_mut() {
+            state.written = 0;
+            self.registers.values.insert(*address, state.reset);
+        }
+    }
+
+    /// Current register values, to inspect or modify them without side effects
+    pub fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    /// Mutable register values
+    pub fn registers_mut(&mut self) -> &mut Registers {
+        &mut self.registers
+    }
+
+    /// Read the register at the address
+    pub fn read(&mut self, address: u64) -> Result<u64, Error> {
+        let register = register_at(&self.index, address)?;
+        let state = &self.states[&register.address];
+        let current = self.registers.values[&register.address];
+        let mut value = 0;
+        for b in &state.bits {
+            if b.access.can_read() {
+                value |= current & b.mask;
+            }
+        }
+        if let Some(hook) = self.hooks.get_mut(&register.peripheral) {
+            value = hook.read(&mut self.registers, register, value);
+        }
+        let stored = self.registers.values.get_mut(&register.address).unwrap();
+        for b in &state.bits {
+            match b.read_action {
+                Some(ReadAction::Clear) => *stored &= !b.mask,
+                Some(ReadAction::Set) => *stored |= b.mask,
+                _ => {}
+            }
+        }
+        Ok(value)
+    }
+
+    /// Write the register at the address
+    pub fn write(&mut self, address: u64, value: u64) -> Result<(), Error> {
+        use ModifiedWriteValues::*;
+        let register = register_at(&self.index, address)?;
+        let state = self.states.get_mut(&register.address).unwrap();
+        let current = self.registers.values[&register.address];
+        let mut new = current;
+        for b in &state.bits {
+            if !b.access.can_write() {
+                continue;
+            }
+            if matches!(b.access, Access::WriteOnce | Access::ReadWriteOnce) {
+                if state.written & b.mask != 0 {
+                    continue;
+                }
+                state.written |= b.mask;
+            }
+            let bits = match b.modified_write_values {
+                OneToClear => current & !value,
+                OneToSet => current | value,
+                OneToToggle => current ^ value,
+                ZeroToClear => current & value,
+                ZeroToSet => current | !value,
+                ZeroToToggle => current ^ !value,
+                Clear => 0,
+                Set => u64::MAX,
+                Modify => value,
+            };
+            new = (new & !b.mask) | (bits & b.mask);
+        }
+        self.registers.values.insert(register.address, new);
+        if let Some(hook) = self.hooks.get_mut(&register.peripheral) {
+            hook.write(&mut self.registers, register, value);
+        }
+        Ok(())
+    }
+}
+
+/// Register instance starting at the address
+fn register_at<'i, 'a>(
+    index: &'i AddressIndex<'a>,
+    address: u64,
+) -> Result<&'i RegisterInstance<'i>, Error> {
+    let register = index
+        .register_at(address)
+        .ok_or(Error::NoRegister(address))?
+        .register;
+    if register.address != address {
+        return Err(Error::Misaligned(address, register.address));
+    }
+    Ok(register)
+}
+
+/// Behavior of the bits of the register instance
+fn bits(r: &RegisterInstance) -> Vec<Bits> {
+    let access = r.properties.access.unwrap_or_default();
+    let modified_write_values = r
+        .register
+        .modified_write_values
+        .or(r.source.modified_write_values)
+        .unwrap_or_default();
+    let read_action = r.register.read_action.or(r.source.read_action);
+    let fields = r.fields();
+    if fields.is_empty() {
+        let size = r.size.min(64);
+        return vec![Bits {
+            mask: if size == 64 {
+                u64::MAX
+            } else {
+                (1 << size) - 1
+            },
+            access,
+            modified_write_values,
+            read_action,
+        }];
+    }
+    fields
+        .iter()
+        .map(|f| {
+            let width = f.bit_range.width;
+            let mask = if width >= 64 {
+                u64::MAX
+            } else {
+                (1 << width) - 1
+            };
+            Bits {
+                mask: checked_shl(mask, f.bit_range.offset),
+                access: f.field.access.unwrap_or(access),
+                modified_write_values: f
+                    .field
+                    .modified_write_values
+                    .unwrap_or(modified_write_values),
+                read_action: f.field.read_action.or(read_action),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,158 @@
+use crate::svd::Access;
+use svd_parser as parser;
+
+const SVD: &str = r#"
+<device>
+  <name>DEV</name>
+  <size>32</size>
+  <access>read-write</access>
+  <peripherals>
+    <peripheral>
+      <dim>2</dim>
+      <dimIncrement>0x400</dimIncrement>
+      <dimIndex>A,B</dimIndex>
+      <name>GPIO%s</name>
+      <baseAddress>0x48000000</baseAddress>
+      <addressBlock><offset>0x0</offset><size>0x400</size><usage>registers</usage></addressBlock>
+      <registers>
+        <register>
+          <name>MODER</name>
+          <addressOffset>0x0</addressOffset>
+          <fields>
+            <field>
+              <dim>16</dim>
+              <dimIncrement>2</dimIncrement>
+              <name>MODE%s</name>
+              <bitRange>[1:0]</bitRange>
+            </field>
+          </fields>
+        </register>
+        <cluster>
+          <dim>2</dim>
+          <dimIncrement>0x10</dimIncrement>
+          <name>CH[%s]</name>
+          <addressOffset>0x20</addressOffset>
+          <register>
+            <name>CCR</name>
+            <addressOffset>0x4</addressOffset>
+            <size>16</size>
+          </register>
+        </cluster>
+        <register>
+          <dim>2</dim>
+          <dimIncrement>4</dimIncrement>
+          <name>AFR%s</name>
+          <addressOffset>0x40</addressOffset>
+        </register>
+        <register>
+          <name>AFR_ALT</name>
+          <addressOffset>0x44</addressOffset>
+          <alternateRegister>AFR1</alternateRegister>
+        </register>
+      </registers>
+    </peripheral>
+    <peripheral derivedFrom="GPIO%s">
+      <name>GPIOZ</name>
+      <baseAddress>0x48001000</baseAddress>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+#[test]
+fn register_at() {
+    let device = parser::parse(SVD).unwrap();
+    let index = device.address_index();
+    let names: Vec<_> = index
+        .peripherals()
+        .iter()
+        .map(|p| p.name.as_str())
+        .collect();
+    assert_eq!(names, ["GPIOA", "GPIOB", "GPIOZ"]);
+
+    // Byte 1 of GPIOB.MODER
+    let loc = index.register_at(0x4800_0401).unwrap();
+    assert_eq!(loc.peripheral.name, "GPIOB");
+    assert_eq!(loc.peripheral.index.as_deref(), Some("B"));
+    assert_eq!(loc.register.path, "GPIOB.MODER");
+    assert_eq!(loc.offset, 1);
+    let fields: Vec<_> = loc.fields.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(fields, ["MODE4", "MODE5", "MODE6", "MODE7"]);
+    assert_eq!(loc.fields[0].bit_range.offset, 8);
+    assert_eq!(loc.block.unwrap().address, 0x4800_0400);
+    assert!(loc.alternates.is_empty());
+
+    let loc = index.register_at(0x4800_0035).unwrap();
+    assert_eq!(loc.register.path, "GPIOA.CH1.CCR");
+    assert_eq!(loc.register.size, 16);
+    assert_eq!(loc.register.index, None);
+    assert!(index.register_at(0x4800_0036).is_none());
+
+    // Alternate registers share the address
+    let loc = index.register_at(0x4800_0447).unwrap();
+    assert_eq!(loc.register.path, "GPIOB.AFR1");
+    assert_eq!(loc.register.index.as_deref(), Some("1"));
+    assert_eq!(loc.offset, 3);
+    let alternates: Vec<_> = loc.alternates.iter().map(|r| r.path.as_str()).collect();
+    assert_eq!(alternates, ["GPIOB.AFR_ALT"]);
+
+    // Derived peripheral uses the registers of the original one
+    let loc = index.register_at(0x4800_1040).unwrap();
+    assert_eq!(loc.register.path, "GPIOZ.AFR0");
+    assert_eq!(index.block_at(0x4800_13ff).unwrap().peripheral, 2);
+
+    assert!(index.register_at(0x4800_0800).is_none());
+    assert!(index.block_at(0x4800_0800).is_none());
+}
+
+#[test]
+fn derived_clusters_and_registers() {
+    let svd = r#"
+<device>
+  <name>DEV</name>
+  <size>32</size>
+  <peripherals>
+    <peripheral>
+      <name>DMA</name>
+      <baseAddress>0x40000000</baseAddress>
+      <registers>
+        <cluster>
+          <name>CH0</name>
+          <addressOffset>0x0</addressOffset>
+          <register>
+            <name>CFG</name>
+            <addressOffset>0x0</addressOffset>
+            <size>16</size>
+            <access>read-only</access>
+            <resetValue>0x1</resetValue>
+            <fields>
+              <field><name>EN</name><bitRange>[0:0]</bitRange></field>
+            </fields>
+          </register>
+        </cluster>
+        <cluster derivedFrom="CH0">
+          <name>CH1</name>
+          <addressOffset>0x10</addressOffset>
+        </cluster>
+        <register derivedFrom="DMA.CH0.CFG">
+          <name>CTRL</name>
+          <addressOffset>0x20</addressOffset>
+          <access>read-write</access>
+        </register>
+      </registers>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+    let device = parser::parse(svd).unwrap();
+    let index = device.address_index();
+    let paths: Vec<_> = index.registers().map(|r| r.path.as_str()).collect();
+    assert_eq!(paths, ["DMA.CH0.CFG", "DMA.CH1.CFG", "DMA.CTRL"]);
+
+    let loc = index.register_at(0x4000_0020).unwrap();
+    assert_eq!(loc.register.size, 16);
+    assert_eq!(loc.register.properties.access, Some(Access::ReadWrite));
+    assert_eq!(loc.register.properties.reset_value, Some(0x1));
+    let fields: Vec<_> = loc.fields.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(fields, ["EN"]);
+}
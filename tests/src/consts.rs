@@ -0,0 +1,159 @@
+use svd_consts::{generate, Config, EnumStyle, Error};
+use svd_parser as parser;
+
+const SVD: &str = r#"
+<device>
+  <name>DEV</name>
+  <size>32</size>
+  <resetValue>0</resetValue>
+  <peripherals>
+    <peripheral>
+      <dim>2</dim>
+      <dimIncrement>0x1000</dimIncrement>
+      <name>UART%s</name>
+      <baseAddress>0x40000000</baseAddress>
+      <registers>
+        <register>
+          <name>SR</name>
+          <description>Status register</description>
+          <addressOffset>0x0</addressOffset>
+          <resetValue>0xc0</resetValue>
+          <fields>
+            <field>
+              <name>TXE</name>
+              <bitRange>[7:7]</bitRange>
+              <enumeratedValues>
+                <enumeratedValue><name>Full</name><value>0</value></enumeratedValue>
+                <enumeratedValue><name>Empty</name><value>1</value></enumeratedValue>
+              </enumeratedValues>
+            </field>
+            <field>
+              <dim>4</dim>
+              <dimIncrement>2</dimIncrement>
+              <name>ERR%s</name>
+              <bitRange>[9:8]</bitRange>
+            </field>
+          </fields>
+        </register>
+        <cluster>
+          <dim>2</dim>
+          <dimIncrement>0x10</dimIncrement>
+          <name>CH[%s]</name>
+          <addressOffset>0x20</addressOffset>
+          <register>
+            <name>CFG</name>
+            <addressOffset>0x4</addressOffset>
+            <size>16</size>
+          </register>
+        </cluster>
+      </registers>
+    </peripheral>
+    <peripheral>
+      <name>TIMER</name>
+      <baseAddress>0x40010000</baseAddress>
+      <registers>
+        <register>
+          <name>CNT</name>
+          <addressOffset>0x8</addressOffset>
+        </register>
+      </registers>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+fn lines(code: &str) -> Vec<String> {
+    code.lines().map(|l| l.trim().to_string()).collect()
+}
+
+#[test]
+fn generate_consts() {
+    let device = parser::parse(SVD).unwrap();
+    let code = generate(&device, &Config::default()).unwrap();
+    let generated = lines(&code);
+    for expected in &[
+        "pub mod uart {",
+        "pub const DIM: usize = 2;",
+        "pub const INCREMENT: usize = 0x1000;",
+        "pub const fn base(n: usize) -> usize {",
+        "/// Status register",
+        "pub mod sr {",
+        "0x40000000 + uart * 0x1000",
+        "pub const RESET_VALUE: u32 = 0xc0;",
+        "pub mod txe {",
+        "pub const MASK: u32 = 0x80;",
+        "#[repr(u8)]",
+        "pub enum Values {",
+        "Empty = 0x1,",
+        "pub mod err {",
+        "pub const fn mask(n: u32) -> u32 {",
+        "0x3 << offset(n)",
+        "pub mod ch {",
+        "0x40000020 + uart * 0x1000 + ch * 0x10",
+        "pub mod cfg {",
+        "pub const fn address(uart: usize, ch: usize) -> usize {",
+        "0x40000024 + uart * 0x1000 + ch * 0x10",
+        "pub const RESET_VALUE: u16 = 0x0;",
+        "pub mod timer {",
+        "pub const BASE: usize = 0x40010000;",
+        "pub const ADDRESS: usize = 0x40010008;",
+    ] {
+        assert!(
+            generated.iter().any(|l| l == expected),
+            "`{}` not in:\n{}",
+            expected,
+            code
+        );
+    }
+
+    let code = generate(&device, &Config::default().enum_style(EnumStyle::Consts)).unwrap();
+    let lines = lines(&code);
+    assert!(lines.iter().any(|l| l == "pub mod values {"));
+    assert!(lines.iter().any(|l| l == "pub const EMPTY: u8 = 0x1;"));
+}
+
+#[test]
+fn duplicate_name() {
+    let svd = SVD.replace("<name>CNT</name>", "<name>cnt</name>").replace(
+        "</registers>\n    </peripheral>\n  </peripherals>",
+        "<register><name>CNT</name><addressOffset>0</addressOffset></register>\n      </registers>\n    </peripheral>\n  </peripherals>",
+    );
+    let device = parser::parse(&svd).unwrap();
+    assert_eq!(
+        generate(&device, &Config::default()),
+        Err(Error::DuplicateName("DEV::timer".into(), "cnt".into()))
+    );
+}
+
+#[test]
+fn field_outside_of_register() {
+    let svd = SVD.replace(
+        "<name>CNT</name>",
+        "<name>CNT</name>\n          <fields><field><name>HIGH</name><bitRange>[71:64]</bitRange></field></fields>",
+    );
+    let device = parser::parse(&svd).unwrap();
+    let code = generate(&device, &Config::default()).unwrap();
+    assert!(lines(&code)
+        .iter()
+        .any(|l| l == "pub const MASK: u32 = 0x0;"));
+    assert_eq!(Config::default().enum_style, EnumStyle::Enums);
+}
+
+#[test]
+fn keywords() {
+    let svd = SVD
+        .replace("<name>CNT</name>", "<name>DO</name>")
+        .replace("<name>Full</name>", "<name>Self</name>")
+        .replace("<name>Empty</name>", "<name>yield</name>");
+    let device = parser::parse(&svd).unwrap();
+    let code = generate(&device, &Config::default()).unwrap();
+    let lines = lines(&code);
+    for expected in &["pub mod do_ {", "Self_ = 0x0,", "yield_ = 0x1,"] {
+        assert!(
+            lines.iter().any(|l| l == expected),
+            "`{}` not in:\n{}",
+            expected,
+            code
+        );
+    }
+}
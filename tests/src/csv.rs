@@ -0,0 +1,260 @@
+use super::svd::{Access, Register, RegisterCluster, ValidateLevel};
+use svd_csv::{export, import, Error};
+use svd_parser as parser;
+
+const SVD: &str = r#"
+<device>
+  <name>DEV</name>
+  <size>32</size>
+  <resetValue>0</resetValue>
+  <resetMask>0xFFFFFFFF</resetMask>
+  <peripherals>
+    <peripheral>
+      <name>UART0</name>
+      <description>Universal asynchronous receiver</description>
+      <baseAddress>0x40000000</baseAddress>
+      <registers>
+        <register>
+          <name>CR</name>
+          <description>Control, "main" register</description>
+          <addressOffset>0x0</addressOffset>
+          <access>read-write</access>
+          <resetValue>0x12</resetValue>
+          <resetMask>0xF0</resetMask>
+          <fields>
+            <field>
+              <name>MODE</name>
+              <description>Mode</description>
+              <bitRange>[1:0]</bitRange>
+              <enumeratedValues>
+                <usage>read</usage>
+                <enumeratedValue><name>Idle</name><value>0</value></enumeratedValue>
+              </enumeratedValues>
+              <enumeratedValues>
+                <enumeratedValue><name>Off</name><value>0</value></enumeratedValue>
+                <enumeratedValue><name>On</name><value>1</value></enumeratedValue>
+              </enumeratedValues>
+            </field>
+            <field>
+              <name>PRIO</name>
+              <bitRange>[7:4]</bitRange>
+              <access>read-only</access>
+            </field>
+          </fields>
+        </register>
+        <register>
+          <dim>2</dim>
+          <dimIncrement>2</dimIncrement>
+          <name>DATA[%s]</name>
+          <addressOffset>0x4</addressOffset>
+          <size>16</size>
+        </register>
+        <cluster>
+          <dim>2</dim>
+          <dimIncrement>0x10</dimIncrement>
+          <name>CH%s</name>
+          <addressOffset>0x10</addressOffset>
+          <register>
+            <name>CFG</name>
+            <addressOffset>0x4</addressOffset>
+            <fields>
+              <field>
+                <dim>2</dim>
+                <dimIncrement>1</dimIncrement>
+                <name>EN%s</name>
+                <bitRange>[0:0]</bitRange>
+              </field>
+            </fields>
+          </register>
+        </cluster>
+      </registers>
+    </peripheral>
+    <peripheral derivedFrom="UART0">
+      <name>UART1</name>
+      <baseAddress>0x40001000</baseAddress>
+    </peripheral>
+    <peripheral>
+      <name>EMPTY</name>
+      <baseAddress>0x50000000</baseAddress>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+const CSV: &str = "\
+peripheral,base,register,offset,size,field,bits,access,reset,description,values
+UART0,0x40000000,,,,,,,,Universal asynchronous receiver,
+UART0,0x40000000,CR,0x0,32,,,read-write,,\"Control, \"\"main\"\" register\",
+UART0,0x40000000,CR,0x0,32,MODE,[1:0],read-write,,Mode,Off=0; On=1
+UART0,0x40000000,CR,0x0,32,PRIO,[7:4],read-only,0x1,,
+UART0,0x40000000,DATA0,0x4,16,,,,0x0,,
+UART0,0x40000000,DATA1,0x6,16,,,,0x0,,
+UART0,0x40000000,CH0_CFG,0x14,32,EN0,[0:0],,0x0,,
+UART0,0x40000000,CH0_CFG,0x14,32,EN1,[1:1],,0x0,,
+UART0,0x40000000,CH1_CFG,0x24,32,EN0,[0:0],,0x0,,
+UART0,0x40000000,CH1_CFG,0x24,32,EN1,[1:1],,0x0,,
+UART1,0x40001000,,,,,,,,Universal asynchronous receiver,
+";
+
+#[test]
+fn export_device() {
+    let device = parser::parse(SVD).unwrap();
+    let csv = export(&device);
+    // The derived peripheral repeats the rows of UART0
+    assert!(csv.starts_with(CSV));
+    assert_eq!(csv.lines().count(), 22);
+    assert!(csv.ends_with("\nEMPTY,0x50000000,,,,,,,,,\n"));
+}
+
+#[test]
+fn export_derived_registers() {
+    let svd = SVD.replacen(
+        "      </registers>",
+        r#"        <register derivedFrom="CR">
+          <name>CR2</name>
+          <addressOffset>0x30</addressOffset>
+        </register>
+        <cluster derivedFrom="CH%s">
+          <name>AUX</name>
+          <addressOffset>0x40</addressOffset>
+        </cluster>
+      </registers>"#,
+        1,
+    );
+    let device = parser::parse(&svd).unwrap();
+    let csv = export(&device);
+    // The derived register and cluster repeat the rows of their sources
+    assert!(csv.contains(
+        "\
+UART0,0x40000000,CR2,0x30,32,,,read-write,,\"Control, \"\"main\"\" register\",
+UART0,0x40000000,CR2,0x30,32,MODE,[1:0],read-write,,Mode,Off=0; On=1
+UART0,0x40000000,CR2,0x30,32,PRIO,[7:4],read-only,0x1,,
+UART0,0x40000000,AUX_CFG,0x44,32,EN0,[0:0],,0x0,,
+UART0,0x40000000,AUX_CFG,0x44,32,EN1,[1:1],,0x0,,
+"
+    ));
+}
+
+#[test]
+fn round_trip() {
+    let device = parser::parse(SVD).unwrap();
+    let imported = import("DEV", &export(&device), ValidateLevel::Strict).unwrap();
+    assert_eq!(imported.name, "DEV");
+    let names: Vec<_> = imported
+        .peripherals
+        .iter()
+        .map(|p| p.name.as_str())
+        .collect();
+    assert_eq!(names, ["UART0", "UART1", "EMPTY"]);
+    let uart = &imported.peripherals[0];
+    assert_eq!(
+        uart.description.as_deref(),
+        Some("Universal asynchronous receiver")
+    );
+    let registers: Vec<&Register> = uart
+        .registers
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|rc| match rc {
+            RegisterCluster::Register(r) => r,
+            _ => panic!("Expected a register"),
+        })
+        .collect();
+    assert_eq!(registers.len(), 5);
+
+    let cr = registers[0];
+    assert_eq!(
+        cr.description.as_deref(),
+        Some("Control, \"main\" register")
+    );
+    assert_eq!(cr.properties.access, Some(Access::ReadWrite));
+    assert_eq!(cr.properties.reset_value, Some(0x10));
+    assert_eq!(cr.properties.reset_mask, Some(0xF0));
+    let fields = cr.fields.as_ref().unwrap();
+    assert_eq!(fields[0].enumerated_values[0].values[1].name, "On");
+    assert_eq!(fields[0].enumerated_values[0].values[1].value, Some(1));
+    assert_eq!(fields[1].bit_range.offset, 4);
+    assert_eq!(fields[1].bit_range.width, 4);
+    assert_eq!(fields[1].access, Some(Access::ReadOnly));
+
+    let data = registers[2];
+    assert_eq!(data.name, "DATA1");
+    assert_eq!(data.address_offset, 6);
+    assert_eq!(data.properties.size, Some(16));
+    assert_eq!(data.properties.reset_value, Some(0));
+    assert_eq!(data.properties.reset_mask, None);
+    assert_eq!(registers[4].name, "CH1_CFG");
+    assert_eq!(registers[4].address_offset, 0x24);
+
+    assert!(imported.peripherals[2].registers.is_none());
+    assert_eq!(export(&imported), export(&device));
+}
+
+#[test]
+fn import_errors() {
+    let import = |csv: &str| import("DEV", csv, ValidateLevel::Weak).unwrap_err();
+    let header = "Peripheral,Register,Offset,Field,Bits,Base,Reset,Values\n";
+
+    assert_eq!(
+        import("peripheral,register,offset,field,bits\n"),
+        Error::MissingColumn("base".to_string())
+    );
+    assert_eq!(
+        import(&format!("{}P,R,0x0,F,[1:0],0x1000,,\n,R,,,,,,\n", header)),
+        Error::MissingValue(3, "peripheral".to_string())
+    );
+    assert_eq!(
+        import(&format!("{}P,R,0x0,F,[1:0],0x1000,,\n", header).replace("0x1000", "")),
+        Error::MissingValue(2, "base".to_string())
+    );
+    assert_eq!(
+        import(&format!(
+            "{}P,R,0x0,F,[1:0],0x1000,,\nP,R,0x0,G,[2:2],0x2000,,\n",
+            header
+        )),
+        Error::Inconsistent(3, "base".to_string(), "0x2000".to_string())
+    );
+    assert_eq!(
+        import(&format!("{}P,R,0x0,F,[0:1],0x1000,,\n", header)),
+        Error::InvalidValue(2, "bits".to_string(), "[0:1]".to_string())
+    );
+    assert_eq!(
+        import(&format!("{}P,R,0x0,F,[32:32],0x1000,,\n", header)),
+        Error::InvalidValue(2, "bits".to_string(), "[32:32]".to_string())
+    );
+    // Bits and sizes beyond 64 bits
+    assert_eq!(
+        import(&format!("{}P,R,0x0,F,64,0x1000,,\n", header)),
+        Error::InvalidValue(2, "bits".to_string(), "64".to_string())
+    );
+    assert_eq!(
+        import("peripheral,base,register,offset,size,field,bits\nP,0x0,R,0x0,128,F,[63:0]\n"),
+        Error::InvalidValue(2, "size".to_string(), "128".to_string())
+    );
+    assert_eq!(
+        import(&format!("{}P,R,0x0,F,[1:0],0x1000,0x4,\n", header)),
+        Error::InvalidValue(2, "reset".to_string(), "0x4".to_string())
+    );
+    assert_eq!(
+        import(&format!("{}P,R,0x0,F,[1:0],0x1000,,A=0; B\n", header)),
+        Error::InvalidValue(2, "values".to_string(), "B".to_string())
+    );
+    assert_eq!(
+        import(&format!("{}P,R,0xG,F,[1:0],0x1000,,\n", header)),
+        Error::InvalidValue(2, "offset".to_string(), "0xG".to_string())
+    );
+    assert_eq!(
+        import(&format!("{}P,R,0x100000000,F,[1:0],0x1000,,\n", header)),
+        Error::InvalidValue(2, "offset".to_string(), "0x100000000".to_string())
+    );
+    // Names are only checked by strict validation
+    assert!(matches!(
+            svd_csv::import(
+                "DEV",
+                "peripheral,base,register,offset,field,bits\nP,0x0,R,0x0,F-G,0\n",
+                ValidateLevel::Strict
+            ),
+            Err(Error::Svd(2, column, _)) if column == "field"
+    ));
+}
@@ -0,0 +1,190 @@
+use crate::svd::{
+    diff::{self, ChangeKind, ElementKind, Impact, Property},
+    Access,
+};
+use svd_parser as parser;
+
+const OLD: &str = r#"
+<device>
+  <name>DEV</name>
+  <size>32</size>
+  <access>read-write</access>
+  <peripherals>
+    <peripheral>
+      <name>TIM1</name>
+      <baseAddress>0x40000000</baseAddress>
+      <registers>
+        <register>
+          <name>CR</name>
+          <addressOffset>0x0</addressOffset>
+          <fields>
+            <field>
+              <name>EN</name>
+              <bitRange>[0:0]</bitRange>
+            </field>
+            <field>
+              <name>MODE</name>
+              <bitRange>[2:1]</bitRange>
+              <enumeratedValues>
+                <enumeratedValue><name>A</name><value>0</value></enumeratedValue>
+              </enumeratedValues>
+            </field>
+          </fields>
+        </register>
+        <register>
+          <name>SR</name>
+          <addressOffset>0x4</addressOffset>
+        </register>
+      </registers>
+    </peripheral>
+    <peripheral>
+      <name>OLD</name>
+      <baseAddress>0x50000000</baseAddress>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+const NEW: &str = r#"
+<device>
+  <name>DEV</name>
+  <size>32</size>
+  <access>read-write</access>
+  <peripherals>
+    <peripheral>
+      <name>TIM1</name>
+      <baseAddress>0x40000000</baseAddress>
+      <registers>
+        <register>
+          <name>SR</name>
+          <addressOffset>0x4</addressOffset>
+          <access>read-only</access>
+        </register>
+        <register>
+          <name>CR</name>
+          <addressOffset>0x0</addressOffset>
+          <fields>
+            <field>
+              <name>MODE</name>
+              <bitOffset>1</bitOffset>
+              <bitWidth>3</bitWidth>
+              <enumeratedValues>
+                <enumeratedValue><name>A</name><value>0</value></enumeratedValue>
+                <enumeratedValue><name>B</name><value>1</value></enumeratedValue>
+              </enumeratedValues>
+            </field>
+          </fields>
+        </register>
+      </registers>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+#[test]
+fn diff_devices() {
+    let old = parser::parse(OLD).unwrap();
+    let new = parser::parse(NEW).unwrap();
+
+    assert!(old.diff(&old).is_empty());
+
+    let changes = old.diff(&new);
+    let summary: Vec<_> = changes
+        .iter()
+        .map(|c| (c.element, c.path.as_str()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (ElementKind::Field, "TIM1.CR.EN"),
+            (ElementKind::Field, "TIM1.CR.MODE"),
+            (ElementKind::EnumeratedValue, "TIM1.CR.MODE.read-write.B"),
+            (ElementKind::Register, "TIM1.SR"),
+            (ElementKind::Peripheral, "OLD"),
+        ]
+    );
+    assert_eq!(changes[0].kind, ChangeKind::Removed);
+    assert!(matches!(
+        changes[1].kind,
+        ChangeKind::Modified(Property::BitRange(o, n)) if o.width == 2 && n.width == 3
+    ));
+    assert_eq!(changes[2].kind, ChangeKind::Added);
+    assert_eq!(
+        changes[3].kind,
+        ChangeKind::Modified(Property::Access(
+            Some(Access::ReadWrite),
+            Some(Access::ReadOnly)
+        ))
+    );
+    assert_eq!(changes[4].kind, ChangeKind::Removed);
+
+    let impacts: Vec<_> = changes.iter().map(|c| c.impact).collect();
+    assert_eq!(
+        impacts,
+        vec![
+            Impact::Breaking,
+            Impact::Breaking,
+            Impact::Additive,
+            Impact::Breaking,
+            Impact::Breaking,
+        ]
+    );
+    assert_eq!(diff::impact(&changes), Some(Impact::Breaking));
+    assert_eq!(diff::impact(&changes[2..3]), Some(Impact::Additive));
+    assert_eq!(diff::impact(&[]), None);
+}
+
+#[test]
+fn classify_property_changes() {
+    use Access::*;
+    let access = |old, new| Property::Access(Some(old), Some(new)).impact();
+    assert_eq!(access(ReadWrite, ReadOnly), Impact::Breaking);
+    assert_eq!(access(ReadWrite, WriteOnly), Impact::Breaking);
+    assert_eq!(access(ReadWrite, ReadWriteOnce), Impact::Breaking);
+    assert_eq!(access(ReadOnly, ReadWrite), Impact::Additive);
+    assert_eq!(access(WriteOnce, WriteOnly), Impact::Additive);
+    assert_eq!(
+        Property::Access(None, Some(ReadWrite)).impact(),
+        Impact::Cosmetic
+    );
+    assert_eq!(
+        Property::Description(None, Some("Control register".to_string())).impact(),
+        Impact::Cosmetic
+    );
+    assert_eq!(
+        Property::ResetValue(Some(0), Some(1)).impact(),
+        Impact::Breaking
+    );
+    assert_eq!(
+        Property::ResetMask(None, Some(0xFF)).impact(),
+        Impact::Breaking
+    );
+    assert_eq!(Property::AddressOffset(0, 4).impact(), Impact::Breaking);
+}
+
+#[test]
+fn values_of_each_usage() {
+    let device = |write_value| {
+        let svd = OLD.replace(
+            "<enumeratedValue><name>A</name><value>0</value></enumeratedValue>\n              </enumeratedValues>",
+            &format!(
+                "<usage>read</usage>
+                <enumeratedValue><name>A</name><value>0</value></enumeratedValue>
+              </enumeratedValues>
+              <enumeratedValues>
+                <usage>write</usage>
+                <enumeratedValue><name>A</name><value>{}</value></enumeratedValue>
+              </enumeratedValues>",
+                write_value
+            ),
+        );
+        parser::parse(&svd).unwrap()
+    };
+    let changes = device(0).diff(&device(1));
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].path, "TIM1.CR.MODE.write.A");
+    assert_eq!(
+        changes[0].kind,
+        ChangeKind::Modified(Property::Value(Some(0), Some(1)))
+    );
+}
@@ -0,0 +1,139 @@
+use svd_doc::{render, Format};
+use svd_parser as parser;
+
+const SVD: &str = r#"
+<device>
+  <name>DEV</name>
+  <version>1.0</version>
+  <size>32</size>
+  <resetValue>0</resetValue>
+  <peripherals>
+    <peripheral>
+      <name>UART0</name>
+      <description>Serial port</description>
+      <baseAddress>0x40000000</baseAddress>
+      <interrupt><name>UART0</name><value>5</value></interrupt>
+      <registers>
+        <register>
+          <name>CR</name>
+          <description>Control register</description>
+          <addressOffset>0x0</addressOffset>
+          <resetValue>0x2</resetValue>
+          <fields>
+            <field>
+              <name>EN</name>
+              <bitRange>[0:0]</bitRange>
+              <enumeratedValues>
+                <enumeratedValue><name>Disabled</name><value>0</value></enumeratedValue>
+                <enumeratedValue><name>Enabled</name><value>1</value></enumeratedValue>
+              </enumeratedValues>
+            </field>
+            <field><name>MODE</name><bitRange>[3:1]</bitRange><access>read-only</access></field>
+          </fields>
+        </register>
+        <cluster>
+          <name>CH</name>
+          <addressOffset>0x10</addressOffset>
+          <register>
+            <name>CFG</name>
+            <addressOffset>0x4</addressOffset>
+          </register>
+          <register derivedFrom="CFG">
+            <name>CFG2</name>
+            <addressOffset>0x8</addressOffset>
+          </register>
+        </cluster>
+      </registers>
+    </peripheral>
+    <peripheral derivedFrom="UART0">
+      <name>UART1</name>
+      <baseAddress>0x40001000</baseAddress>
+      <interrupt><name>UART1</name><value>6</value></interrupt>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+#[test]
+fn markdown() {
+    let device = parser::parse(SVD).unwrap();
+    let doc = render(&device, Format::Markdown);
+    for expected in &[
+        "# <a id=\"dev\"></a>DEV",
+        "| 5 | UART0 | [UART0](#uart0) |  |",
+        "| [UART0](#uart0) | `0x40000000` |  |  | Serial port |",
+        "| [UART1](#uart1) | `0x40001000` |  | [UART0](#uart0) |  |",
+        "Registers are described in [UART0](#uart0).",
+        "| [CR](#uart0-cr) | `0x0` | 32 | read-write | `0x2` | Control register |",
+        "| [CH.CFG](#uart0-ch-cfg) | `0x14` | 32 | read-write | `0x0` |  |",
+        "### <a id=\"uart0-cr\"></a>UART0.CR",
+        "| Derived from | [CFG](#uart0-ch-cfg) |",
+        "| \\[0:0\\] | EN | read-write | `0x0` |  |",
+        "| \\[3:1\\] | MODE | read-only | `0x1` |  |",
+        "| `0x1` | Enabled |  |",
+    ] {
+        assert!(doc.contains(expected), "`{}` not in:\n{}", expected, doc);
+    }
+    // Bit-field diagram of CR
+    assert!(doc.contains(concat!(
+        " 15 14 13 12 11 10  9  8  7  6  5  4  3  2  1  0\n",
+        "|                 -                 |  MODE  |EN|\n",
+    )));
+}
+
+#[test]
+fn html() {
+    let device = parser::parse(SVD).unwrap();
+    let doc = render(&device, Format::Html);
+    for expected in &[
+        "<title>DEV</title>",
+        "<h2 id=\"uart0\">UART0</h2>",
+        "<td><a href=\"#uart0\">UART0</a></td>",
+        "<td colspan=\"3\">MODE</td><td colspan=\"1\">EN</td>",
+        "<td colspan=\"12\" class=\"reserved\"></td>",
+    ] {
+        assert!(doc.contains(expected), "`{}` not in:\n{}", expected, doc);
+    }
+}
+
+#[test]
+fn derived_clusters_and_values() {
+    let svd = SVD
+        .replace(
+            "<field><name>MODE</name><bitRange>[3:1]</bitRange><access>read-only</access></field>",
+            r#"<field>
+              <name>MODE</name>
+              <bitRange>[3:1]</bitRange>
+              <access>read-only</access>
+              <enumeratedValues derivedFrom="State"></enumeratedValues>
+            </field>"#,
+        )
+        .replace(
+            "<enumeratedValues>\n                <enumeratedValue><name>Disabled</name>",
+            "<enumeratedValues>\n                <name>State</name>\n                <enumeratedValue><name>Disabled</name>",
+        )
+        .replace(
+            "        </cluster>\n      </registers>",
+            r#"        </cluster>
+        <cluster derivedFrom="CH">
+          <name>CH2</name>
+          <description>Second channel</description>
+          <addressOffset>0x20</addressOffset>
+        </cluster>
+      </registers>"#,
+        );
+    let device = parser::parse(&svd).unwrap();
+    let doc = render(&device, Format::Markdown);
+    for expected in &[
+        "| [CH](#uart0-ch) | `0x10` |  |  |  |  |",
+        "| [CH2](#uart0-ch2) | `0x20` |  |  |  | Second channel |",
+        "### <a id=\"uart0-ch\"></a>UART0.CH",
+        "### <a id=\"uart0-ch2\"></a>UART0.CH2",
+        "| Derived from | [CH](#uart0-ch) |",
+        "Registers are described in [CH](#uart0-ch).",
+        "#### <a id=\"uart0-cr-en-state\"></a>EN values (State, read-write)",
+        "Derived from [State](#uart0-cr-en-state).",
+    ] {
+        assert!(doc.contains(expected), "`{}` not in:\n{}", expected, doc);
+    }
+}
@@ -0,0 +1,138 @@
+use crate::svd::{edit::Error, ClusterInfo, RegisterCluster, SvdError, ValidateLevel};
+use svd_parser as parser;
+
+const SVD: &str = r#"
+<device>
+  <name>DEV</name>
+  <size>32</size>
+  <access>read-write</access>
+  <peripherals>
+    <peripheral>
+      <name>TIM1</name>
+      <baseAddress>0x40000000</baseAddress>
+      <registers>
+        <register>
+          <name>CCR1</name>
+          <addressOffset>0x34</addressOffset>
+          <fields>
+            <field>
+              <name>CCR</name>
+              <bitRange>[15:0]</bitRange>
+              <enumeratedValues>
+                <name>CCR_VALUES</name>
+                <enumeratedValue><name>ZERO</name><value>0</value></enumeratedValue>
+              </enumeratedValues>
+            </field>
+          </fields>
+        </register>
+        <register derivedFrom="CCR1">
+          <name>CCR2</name>
+          <addressOffset>0x38</addressOffset>
+        </register>
+        <register>
+          <name>CCR_ALT</name>
+          <alternateRegister>CCR1</alternateRegister>
+          <addressOffset>0x34</addressOffset>
+        </register>
+        <register>
+          <name>CR</name>
+          <addressOffset>0x0</addressOffset>
+          <fields>
+            <field>
+              <name>MODE</name>
+              <bitRange>[1:0]</bitRange>
+              <enumeratedValues derivedFrom="TIM1.CCR1.CCR.CCR_VALUES"/>
+            </field>
+          </fields>
+        </register>
+      </registers>
+    </peripheral>
+    <peripheral derivedFrom="TIM1">
+      <name>TIM2</name>
+      <baseAddress>0x40001000</baseAddress>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+#[test]
+fn rename_updates_references() {
+    let mut device = parser::parse(SVD).unwrap();
+    device.rename("TIM1", "TIM0").unwrap();
+    device.rename("TIM0.CCR1", "CC1").unwrap();
+    device.rename("TIM0.CC1.CCR.CCR_VALUES", "VALUES").unwrap();
+
+    let tim = &device.peripherals[0];
+    let regs: Vec<_> = tim.reg_iter().collect();
+    assert_eq!(regs[1].derived_from.as_deref(), Some("CC1"));
+    assert_eq!(regs[2].alternate_register.as_deref(), Some("CC1"));
+    let mode = &regs[3].fields.as_ref().unwrap()[0];
+    assert_eq!(
+        mode.enumerated_values[0].derived_from.as_deref(),
+        Some("TIM0.CC1.CCR.VALUES")
+    );
+    assert_eq!(device.peripherals[1].derived_from.as_deref(), Some("TIM0"));
+
+    assert_eq!(
+        device.rename("TIM0.CC1", "CCR2"),
+        Err(SvdError::Edit(Error::AlreadyExists(
+            "TIM0.CCR2".to_string()
+        )))
+    );
+    assert_eq!(
+        device.rename("TIM0.CCR9", "CCR3"),
+        Err(SvdError::Edit(Error::NotFound("TIM0.CCR9".to_string())))
+    );
+}
+
+#[test]
+fn move_into_cluster() {
+    let mut device = parser::parse(SVD).unwrap();
+    let cluster = ClusterInfo::builder()
+        .name("CH".to_string())
+        .address_offset(0x30)
+        .children(Vec::new())
+        .build(ValidateLevel::Disabled)
+        .unwrap();
+    device.peripherals[0]
+        .registers
+        .as_mut()
+        .unwrap()
+        .push(RegisterCluster::Cluster(cluster.single()));
+
+    // Alternate registers must stay siblings
+    let err = device.clone().move_to("TIM1.CCR1", "TIM1.CH").unwrap_err();
+    assert!(matches!(
+        err,
+        SvdError::Edit(Error::BrokenReference(_, _, _))
+    ));
+
+    assert!(device.clone().move_to("TIM1.CCR_ALT", "TIM1.CH").is_err());
+    if let Some(RegisterCluster::Register(r)) =
+        device.peripherals[0].registers.as_mut().unwrap().get_mut(2)
+    {
+        r.alternate_register = None;
+    }
+    device.move_to("TIM1.CCR_ALT", "TIM1.CH").unwrap();
+    device.move_to("TIM1.CCR1", "TIM1.CH").unwrap();
+
+    let tim = &device.peripherals[0];
+    let regs: Vec<_> = tim.reg_iter().collect();
+    let names: Vec<_> = regs.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, ["CCR2", "CR", "CCR_ALT", "CCR1"]);
+    assert_eq!(regs[3].address_offset, 0x4);
+    assert_eq!(regs[2].address_offset, 0x4);
+    // Relative reference from outside the cluster becomes a full path
+    assert_eq!(regs[0].derived_from.as_deref(), Some("TIM1.CH.CCR1"));
+    let mode = &regs[1].fields.as_ref().unwrap()[0];
+    assert_eq!(
+        mode.enumerated_values[0].derived_from.as_deref(),
+        Some("TIM1.CH.CCR1.CCR.CCR_VALUES")
+    );
+
+    // Can't move before the start of the cluster
+    assert!(matches!(
+        device.move_to("TIM1.CR", "TIM1.CH"),
+        Err(SvdError::Edit(Error::InvalidOffset(_, _)))
+    ));
+}
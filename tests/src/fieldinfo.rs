@@ -1,6 +1,7 @@
 use super::run_test;
 use crate::svd::{
-    Access, BitRange, BitRangeType, EnumeratedValue, EnumeratedValues, FieldInfo, ValidateLevel,
+    writeconstraint::Error, Access, BitRange, BitRangeType, EnumeratedValue, EnumeratedValues,
+    FieldInfo, SvdError, Usage, ValidateLevel, WriteConstraint, WriteConstraintRange,
 };
 
 #[test]
@@ -70,3 +71,89 @@ fn decode_encode() {
 
     run_test::<FieldInfo>(&tests[..]);
 }
+
+#[test]
+fn write_constraint() {
+    let field = |constraint: WriteConstraint| {
+        FieldInfo::builder()
+            .name("MODE".to_string())
+            .bit_range(BitRange::from_offset_width(0, 3))
+            .write_constraint(Some(constraint))
+            .enumerated_values(vec![EnumeratedValues::builder()
+                .usage(Some(Usage::Write))
+                .values(vec![EnumeratedValue::builder()
+                    .name("ON".to_string())
+                    .value(Some(5))
+                    .build(ValidateLevel::Strict)
+                    .unwrap()])
+                .build(ValidateLevel::Strict)
+                .unwrap()])
+            .build(ValidateLevel::Strict)
+    };
+
+    let range = field(WriteConstraint::Range(WriteConstraintRange {
+        min: 1,
+        max: 6,
+    }))
+    .unwrap();
+    assert_eq!(range.is_write_allowed(6, None), Ok(()));
+    assert_eq!(
+        range.is_write_allowed(0, None),
+        Err(Error::OutOfRange(0, 1, 6))
+    );
+    assert_eq!(range.is_write_allowed(8, None), Err(Error::TooWide(8, 3)));
+
+    let enumerated = field(WriteConstraint::UseEnumeratedValues(true)).unwrap();
+    assert_eq!(enumerated.is_write_allowed(5, None), Ok(()));
+    assert_eq!(
+        enumerated.is_write_allowed(4, None),
+        Err(Error::NotEnumerated(4))
+    );
+
+    let as_read = field(WriteConstraint::WriteAsRead(true)).unwrap();
+    assert_eq!(as_read.is_write_allowed(5, None), Err(Error::WriteAsRead));
+
+    let mut read_only = range.clone();
+    read_only.access = Some(Access::ReadOnly);
+    assert_eq!(read_only.is_write_allowed(1, None), Err(Error::ReadOnly));
+    // Access of the register is used for fields without one
+    assert_eq!(
+        range.is_write_allowed(1, Some(Access::ReadOnly)),
+        Err(Error::ReadOnly)
+    );
+    assert_eq!(
+        read_only.is_write_allowed(1, Some(Access::ReadWrite)),
+        Err(Error::ReadOnly)
+    );
+    let mut write_only = range.clone();
+    write_only.access = Some(Access::WriteOnly);
+    assert_eq!(
+        write_only.is_write_allowed(1, Some(Access::ReadOnly)),
+        Ok(())
+    );
+
+    assert_eq!(
+        field(WriteConstraint::Range(WriteConstraintRange {
+            min: 0,
+            max: 8
+        })),
+        Err(SvdError::WriteConstraint(Error::RangeTooWide(0, 8, 3)))
+    );
+    assert_eq!(
+        field(WriteConstraint::Range(WriteConstraintRange {
+            min: 4,
+            max: 2
+        })),
+        Err(SvdError::WriteConstraint(Error::EmptyRange(4, 2)))
+    );
+    // Ranges are only checked by strict validation
+    assert!(FieldInfo::builder()
+        .name("MODE".to_string())
+        .bit_range(BitRange::from_offset_width(0, 3))
+        .write_constraint(Some(WriteConstraint::Range(WriteConstraintRange {
+            min: 0,
+            max: 8
+        })))
+        .build(ValidateLevel::Weak)
+        .is_ok());
+}
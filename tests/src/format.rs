@@ -0,0 +1,172 @@
+use crate::svd::{BitRange, Device, RegisterCluster, SvdError};
+use svd_encoder as encoder;
+use svd_parser as parser;
+
+const SVD: &str = r#"
+<device>
+  <name>DEV</name>
+  <version>1.0</version>
+  <cpu>
+    <name>CM4</name>
+    <revision>r0p1</revision>
+    <endian>little</endian>
+    <mpuPresent>true</mpuPresent>
+    <fpuPresent>true</fpuPresent>
+    <nvicPrioBits>3</nvicPrioBits>
+    <vendorSystickConfig>false</vendorSystickConfig>
+  </cpu>
+  <addressUnitBits>8</addressUnitBits>
+  <width>32</width>
+  <size>32</size>
+  <access>read-write</access>
+  <resetValue>0</resetValue>
+  <resetMask>0xFFFFFFFF</resetMask>
+  <peripherals>
+    <peripheral>
+      <name>UART0</name>
+      <description>Universal asynchronous receiver</description>
+      <baseAddress>0x40000000</baseAddress>
+      <addressBlock>
+        <offset>0x0</offset>
+        <size>0x400</size>
+        <usage>registers</usage>
+      </addressBlock>
+      <interrupt><name>UART0</name><value>5</value></interrupt>
+      <registers>
+        <register>
+          <name>CR</name>
+          <addressOffset>0x0</addressOffset>
+          <resetValue>0x12</resetValue>
+          <fields>
+            <field>
+              <name>MODE</name>
+              <bitRange>[1:0]</bitRange>
+              <writeConstraint><range><minimum>0</minimum><maximum>2</maximum></range></writeConstraint>
+              <enumeratedValues>
+                <name>MODE</name>
+                <usage>read-write</usage>
+                <enumeratedValue><name>Off</name><value>0</value></enumeratedValue>
+                <enumeratedValue><name>Other</name><isDefault>true</isDefault></enumeratedValue>
+              </enumeratedValues>
+            </field>
+            <field>
+              <name>PRIO</name>
+              <bitOffset>4</bitOffset>
+              <bitWidth>4</bitWidth>
+              <modifiedWriteValues>oneToClear</modifiedWriteValues>
+            </field>
+            <field>
+              <name>EN%s</name>
+              <dim>2</dim>
+              <dimIncrement>1</dimIncrement>
+              <lsb>8</lsb>
+              <msb>8</msb>
+              <access>read-only</access>
+            </field>
+          </fields>
+        </register>
+        <register>
+          <dim>2</dim>
+          <dimIncrement>4</dimIncrement>
+          <dimIndex>A,B</dimIndex>
+          <name>DATA%s</name>
+          <addressOffset>0x4</addressOffset>
+          <size>16</size>
+        </register>
+        <cluster>
+          <dim>2</dim>
+          <dimIncrement>0x10</dimIncrement>
+          <name>CH[%s]</name>
+          <addressOffset>0x10</addressOffset>
+          <register>
+            <name>CFG</name>
+            <addressOffset>0x0</addressOffset>
+            <readAction>clear</readAction>
+          </register>
+        </cluster>
+      </registers>
+    </peripheral>
+    <peripheral derivedFrom="UART0">
+      <name>UART1</name>
+      <baseAddress>0x40001000</baseAddress>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+fn round_trip(device: &Device, text: &str, load: fn(&str) -> Result<Device, SvdError>) {
+    let loaded = load(text).unwrap();
+    assert_eq!(&loaded, device);
+    assert_eq!(
+        encoder::encode(&loaded).unwrap(),
+        encoder::encode(device).unwrap()
+    );
+}
+
+#[test]
+fn yaml_round_trip() {
+    let device = parser::parse(SVD).unwrap();
+    let yaml = device.to_yaml().unwrap();
+    round_trip(&device, &yaml, Device::from_yaml);
+}
+
+#[test]
+fn toml_round_trip() {
+    let device = parser::parse(SVD).unwrap();
+    let toml = device.to_toml().unwrap();
+    round_trip(&device, &toml, Device::from_toml);
+
+    let svd = SVD.replace(
+        "<resetMask>0xFFFFFFFF</resetMask>",
+        "<resetMask>0xFFFFFFFFFFFFFFFF</resetMask>",
+    );
+    let device = parser::parse(&svd).unwrap();
+    let toml = device.to_toml().unwrap();
+    assert!(
+        toml.contains("resetMask = \"0xffffffffffffffff\""),
+        "{}",
+        toml
+    );
+    round_trip(&device, &toml, Device::from_toml);
+}
+
+#[test]
+fn author_yaml() {
+    let yaml = "
+name: DEV
+peripherals:
+  - name: UART0
+    baseAddress: 0x40000000
+    registers:
+      - register:
+          name: CR
+          addressOffset: 4
+          resetValue: '0b1100'
+          fields:
+            - name: EN
+              bitRange: '[3:2]'
+            - name: MODE
+              dim: 2
+              dimIncrement: 2
+              lsb: 4
+              msb: 5
+";
+    let device = Device::from_yaml(yaml).unwrap();
+    let cr = match &device.peripherals[0].registers.as_ref().unwrap()[0] {
+        RegisterCluster::Register(r) => r,
+        _ => panic!("Expected a register"),
+    };
+    assert_eq!(cr.properties.reset_value, Some(0b1100));
+    let fields = cr.fields.as_ref().unwrap();
+    assert_eq!(
+        fields[0].bit_range,
+        BitRange::from_bit_range("[3:2]").unwrap()
+    );
+    assert_eq!(fields[1].bit_range, BitRange::from_msb_lsb(5, 4));
+    assert!(encoder::encode(&device)
+        .unwrap()
+        .contains("<dimIncrement>0x2</dimIncrement>"));
+
+    assert!(Device::from_yaml("name: [").is_err());
+    assert!(Device::from_yaml("peripherals: []").is_err());
+}
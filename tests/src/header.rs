@@ -0,0 +1,247 @@
+use svd_header::{generate, Error};
+use svd_parser as parser;
+
+const SVD: &str = r#"
+<device>
+  <name>DEV</name>
+  <cpu>
+    <name>CM4</name>
+    <revision>r0p1</revision>
+    <endian>little</endian>
+    <mpuPresent>true</mpuPresent>
+    <fpuPresent>true</fpuPresent>
+    <nvicPrioBits>3</nvicPrioBits>
+    <vendorSystickConfig>false</vendorSystickConfig>
+  </cpu>
+  <size>32</size>
+  <peripherals>
+    <peripheral>
+      <name>UART0</name>
+      <headerStructName>UART</headerStructName>
+      <prependToName>U_</prependToName>
+      <baseAddress>0x40000000</baseAddress>
+      <interrupt><name>UART0</name><value>5</value></interrupt>
+      <registers>
+        <register>
+          <name>SR</name>
+          <description>Status register</description>
+          <addressOffset>0x0</addressOffset>
+          <access>read-only</access>
+          <fields>
+            <field>
+              <name>TXE</name>
+              <bitRange>[7:7]</bitRange>
+              <enumeratedValues>
+                <enumeratedValue><name>Full</name><value>0</value></enumeratedValue>
+                <enumeratedValue><name>Empty</name><value>1</value></enumeratedValue>
+              </enumeratedValues>
+            </field>
+          </fields>
+        </register>
+        <register>
+          <name>DR</name>
+          <addressOffset>0x4</addressOffset>
+          <size>8</size>
+          <access>write-only</access>
+        </register>
+        <register>
+          <name>RDR</name>
+          <addressOffset>0x4</addressOffset>
+          <size>8</size>
+          <access>read-only</access>
+          <alternateRegister>DR</alternateRegister>
+        </register>
+        <register>
+          <dim>2</dim>
+          <dimIncrement>4</dimIncrement>
+          <name>BUF[%s]</name>
+          <addressOffset>0x10</addressOffset>
+          <fields>
+            <field><name>DATA</name><bitRange>[15:8]</bitRange></field>
+          </fields>
+        </register>
+        <cluster>
+          <dim>2</dim>
+          <dimIncrement>0x10</dimIncrement>
+          <name>CH[%s]</name>
+          <addressOffset>0x20</addressOffset>
+          <register>
+            <name>CFG</name>
+            <addressOffset>0x0</addressOffset>
+            <fields>
+              <field><name>MODE</name><bitRange>[3:2]</bitRange></field>
+            </fields>
+          </register>
+        </cluster>
+      </registers>
+    </peripheral>
+    <peripheral derivedFrom="UART0">
+      <name>UART1</name>
+      <baseAddress>0x40001000</baseAddress>
+      <interrupt><name>UART1</name><value>6</value></interrupt>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+fn lines(header: &str) -> Vec<String> {
+    header
+        .lines()
+        .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect()
+}
+
+fn contains(lines: &[String], line: &str) -> bool {
+    lines.iter().any(|l| l.starts_with(line))
+}
+
+#[test]
+fn generate_header() {
+    let device = parser::parse(SVD).unwrap();
+    let header = generate(&device).unwrap();
+    let lines = lines(&header);
+    for expected in &[
+        "#ifndef DEV_H",
+        "Reset_IRQn = -15,",
+        "SysTick_IRQn = -1,",
+        "UART0_IRQn = 5,",
+        "UART1_IRQn = 6,",
+        "} IRQn_Type;",
+        "#define __CM4_REV 0x0001U",
+        "#define __NVIC_PRIO_BITS 3",
+        "#include \"core_cm4.h\"",
+        "__IM uint32_t U_SR; /*!< (@ 0x00000000) Status register */",
+        "union {",
+        "__OM uint8_t U_DR;",
+        "__IM uint8_t U_RDR;",
+        "__IM uint8_t RESERVED[3];",
+        "__IM uint32_t RESERVED1[2];",
+        "__IOM uint32_t U_BUF[2];",
+        "__IM uint32_t RESERVED2[2];",
+        "__IOM uint32_t U_CFG;",
+        "__IM uint32_t RESERVED[3];",
+        "} UART_CH_Type; /*!< Size = 16 (0x10) */",
+        "UART_CH_Type CH[2];",
+        "} UART_Type; /*!< Size = 64 (0x40) */",
+        "#define UART0_BASE 0x40000000UL",
+        "#define UART1_BASE 0x40001000UL",
+        "#define UART0 ((UART_Type*) UART0_BASE)",
+        "#define UART1 ((UART_Type*) UART1_BASE)",
+        "#define UART_U_SR_TXE_Pos (7UL)",
+        "#define UART_U_SR_TXE_Msk (0x80UL)",
+        "UART_U_SR_TXE_Empty = 1,",
+        "} UART_U_SR_TXE_Enum;",
+        "#define UART_U_BUF_DATA_Msk (0xff00UL)",
+        "#define UART_CH_U_CFG_MODE_Msk (0xcUL)",
+        "#endif /* DEV_H */",
+    ] {
+        assert!(
+            contains(&lines, expected),
+            "`{}` not in:\n{}",
+            expected,
+            header
+        );
+    }
+    // The derived peripheral shares the type
+    assert_eq!(header.matches("} UART_Type;").count(), 1);
+}
+
+#[test]
+fn overlap() {
+    let svd = SVD.replace(
+        "<name>BUF[%s]</name>\n          <addressOffset>0x10</addressOffset>",
+        "<name>BUF[%s]</name>\n          <addressOffset>0x2</addressOffset>",
+    );
+    let device = parser::parse(&svd).unwrap();
+    assert_eq!(
+        generate(&device),
+        Err(Error::Overlap("UART_Type".into(), "U_BUF".into()))
+    );
+}
+
+#[test]
+fn wide_masks() {
+    let svd = SVD.replace(
+        "      </registers>\n    </peripheral>\n    <peripheral derivedFrom",
+        r#"        <register>
+          <name>TS</name>
+          <addressOffset>0x40</addressOffset>
+          <size>64</size>
+          <fields>
+            <field><name>HIGH</name><bitRange>[47:32]</bitRange></field>
+          </fields>
+        </register>
+      </registers>
+    </peripheral>
+    <peripheral derivedFrom"#,
+    );
+    let device = parser::parse(&svd).unwrap();
+    let header = generate(&device).unwrap();
+    let lines = lines(&header);
+    assert!(contains(
+        &lines,
+        "#define UART_U_TS_HIGH_Msk (0xffff00000000ULL)"
+    ));
+    assert!(contains(&lines, "#define UART_U_SR_TXE_Msk (0x80UL)"));
+}
+
+#[test]
+fn shared_cluster_types() {
+    let svd = SVD.replace(
+        "      </registers>\n    </peripheral>\n    <peripheral derivedFrom",
+        r#"        <cluster>
+          <dim>2</dim>
+          <dimIncrement>0x10</dimIncrement>
+          <dimName>CHANNEL</dimName>
+          <name>RX[%s]</name>
+          <addressOffset>0x40</addressOffset>
+          <register><name>LEN</name><addressOffset>0x0</addressOffset></register>
+        </cluster>
+        <cluster>
+          <dim>2</dim>
+          <dimIncrement>0x10</dimIncrement>
+          <dimName>CHANNEL</dimName>
+          <name>TX[%s]</name>
+          <addressOffset>0x60</addressOffset>
+          <register><name>LEN</name><addressOffset>0x0</addressOffset></register>
+        </cluster>
+        <cluster derivedFrom="CH[%s]">
+          <dim>2</dim>
+          <dimIncrement>0x10</dimIncrement>
+          <name>ALT[%s]</name>
+          <addressOffset>0x80</addressOffset>
+        </cluster>
+        <cluster derivedFrom="UART0.RX[%s]">
+          <name>LAST</name>
+          <addressOffset>0xA0</addressOffset>
+        </cluster>
+      </registers>
+    </peripheral>
+    <peripheral derivedFrom"#,
+    );
+    let device = parser::parse(&svd).unwrap();
+    let header = generate(&device).unwrap();
+    let lines = lines(&header);
+    for expected in &[
+        "CHANNEL RX[2];",
+        "CHANNEL TX[2];",
+        "} UART_ALT_Type; /*!< Size = 16 (0x10) */",
+        "UART_ALT_Type ALT[2];",
+        "} UART_LAST_Type; /*!< Size = 4 (0x4) */",
+        "UART_LAST_Type LAST;",
+        "} UART_Type; /*!< Size = 164 (0xa4) */",
+    ] {
+        assert!(
+            contains(&lines, expected),
+            "`{}` not in:\n{}",
+            expected,
+            header
+        );
+    }
+    // Clusters with the same type name share the struct
+    assert_eq!(header.matches("} CHANNEL;").count(), 1);
+    // Derived clusters have the registers of their source
+    let alt =
+        &header[header.find("} UART_CH_Type;").unwrap()..header.find("} UART_ALT_Type;").unwrap()];
+    assert!(alt.contains("U_CFG;"), "{}", alt);
+}
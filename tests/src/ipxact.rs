@@ -0,0 +1,377 @@
+use super::svd::{
+    Access, ModifiedWriteValues, ReadAction, Register, RegisterCluster, Usage, WriteConstraint,
+};
+use svd_ipxact::{export, import, Diagnostic, Error};
+use svd_parser as parser;
+
+const IPXACT: &str = r#"
+<ipxact:component xmlns:ipxact="http://www.accellera.org/XMLSchema/IPXACT/1685-2014">
+  <ipxact:vendor>acme</ipxact:vendor>
+  <ipxact:library>ip</ipxact:library>
+  <ipxact:name>DEV</ipxact:name>
+  <ipxact:version>1.2</ipxact:version>
+  <ipxact:memoryMaps>
+    <ipxact:memoryMap>
+      <ipxact:name>MAP</ipxact:name>
+      <ipxact:addressBlock>
+        <ipxact:name>UART0</ipxact:name>
+        <ipxact:baseAddress>'h4000_0000</ipxact:baseAddress>
+        <ipxact:range>4096</ipxact:range>
+        <ipxact:width>32</ipxact:width>
+        <ipxact:register>
+          <ipxact:name>SR</ipxact:name>
+          <ipxact:addressOffset>0x0</ipxact:addressOffset>
+          <ipxact:size>32</ipxact:size>
+          <ipxact:access>read-write</ipxact:access>
+          <ipxact:field>
+            <ipxact:name>TXE</ipxact:name>
+            <ipxact:bitOffset>7</ipxact:bitOffset>
+            <ipxact:resets><ipxact:reset><ipxact:value>1</ipxact:value></ipxact:reset></ipxact:resets>
+            <ipxact:bitWidth>1</ipxact:bitWidth>
+            <ipxact:access>read-only</ipxact:access>
+            <ipxact:enumeratedValues>
+              <ipxact:enumeratedValue usage="read">
+                <ipxact:name>Full</ipxact:name>
+                <ipxact:value>0</ipxact:value>
+              </ipxact:enumeratedValue>
+              <ipxact:enumeratedValue usage="read">
+                <ipxact:name>Empty</ipxact:name>
+                <ipxact:value>1</ipxact:value>
+              </ipxact:enumeratedValue>
+              <ipxact:enumeratedValue>
+                <ipxact:name>Any</ipxact:name>
+                <ipxact:value>0</ipxact:value>
+              </ipxact:enumeratedValue>
+            </ipxact:enumeratedValues>
+          </ipxact:field>
+          <ipxact:field>
+            <ipxact:name>ERR</ipxact:name>
+            <ipxact:bitOffset>0</ipxact:bitOffset>
+            <ipxact:resets><ipxact:reset><ipxact:value>'h3</ipxact:value><ipxact:mask>'h1</ipxact:mask></ipxact:reset></ipxact:resets>
+            <ipxact:bitWidth>4</ipxact:bitWidth>
+            <ipxact:modifiedWriteValue>oneToClear</ipxact:modifiedWriteValue>
+            <ipxact:writeValueConstraint>
+              <ipxact:minimum>0</ipxact:minimum>
+              <ipxact:maximum>7</ipxact:maximum>
+            </ipxact:writeValueConstraint>
+            <ipxact:readAction>clear</ipxact:readAction>
+          </ipxact:field>
+          <ipxact:alternateRegisters>
+            <ipxact:alternateRegister>
+              <ipxact:name>SR_ALT</ipxact:name>
+              <ipxact:alternateGroups><ipxact:alternateGroup>DEBUG</ipxact:alternateGroup></ipxact:alternateGroups>
+              <ipxact:field>
+                <ipxact:name>ALL</ipxact:name>
+                <ipxact:bitOffset>0</ipxact:bitOffset>
+                <ipxact:bitWidth>32</ipxact:bitWidth>
+              </ipxact:field>
+            </ipxact:alternateRegister>
+          </ipxact:alternateRegisters>
+        </ipxact:register>
+        <ipxact:register>
+          <ipxact:name>BUF</ipxact:name>
+          <ipxact:dim>4</ipxact:dim>
+          <ipxact:addressOffset>'h10</ipxact:addressOffset>
+          <ipxact:size>32</ipxact:size>
+          <ipxact:field>
+            <ipxact:name>DATA</ipxact:name>
+            <ipxact:bitOffset>0</ipxact:bitOffset>
+            <ipxact:resets><ipxact:reset resetTypeRef="SOFT"><ipxact:value>0</ipxact:value></ipxact:reset></ipxact:resets>
+            <ipxact:bitWidth>8</ipxact:bitWidth>
+          </ipxact:field>
+        </ipxact:register>
+        <ipxact:registerFile>
+          <ipxact:name>CH</ipxact:name>
+          <ipxact:dim>2</ipxact:dim>
+          <ipxact:addressOffset>'h20</ipxact:addressOffset>
+          <ipxact:range>'h10</ipxact:range>
+          <ipxact:register>
+            <ipxact:name>CFG</ipxact:name>
+            <ipxact:addressOffset>0</ipxact:addressOffset>
+            <ipxact:size>16</ipxact:size>
+            <ipxact:field>
+              <ipxact:name>MODE</ipxact:name>
+              <ipxact:bitOffset>0</ipxact:bitOffset>
+              <ipxact:bitWidth>2</ipxact:bitWidth>
+            </ipxact:field>
+          </ipxact:register>
+        </ipxact:registerFile>
+      </ipxact:addressBlock>
+      <ipxact:bank bankAlignment="serial">
+        <ipxact:name>BANK</ipxact:name>
+      </ipxact:bank>
+      <ipxact:addressUnitBits>8</ipxact:addressUnitBits>
+    </ipxact:memoryMap>
+  </ipxact:memoryMaps>
+</ipxact:component>
+"#;
+
+fn registers(children: &[RegisterCluster]) -> Vec<&Register> {
+    children
+        .iter()
+        .filter_map(|rc| match rc {
+            RegisterCluster::Register(r) => Some(r),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn import_component() {
+    let import = import(IPXACT).unwrap();
+    let device = &import.device;
+    assert_eq!(device.name, "DEV");
+    assert_eq!(device.version.as_deref(), Some("1.2"));
+    assert_eq!(device.address_unit_bits, Some(8));
+
+    let uart = &device.peripherals[0];
+    assert_eq!(uart.base_address, 0x4000_0000);
+    assert_eq!(uart.address_block.as_ref().unwrap()[0].size, 4096);
+    let children = uart.registers.as_ref().unwrap();
+    let regs = registers(children);
+
+    let sr = regs[0];
+    assert_eq!(sr.name, "SR");
+    assert_eq!(sr.properties.access, Some(Access::ReadWrite));
+    assert_eq!(sr.properties.reset_value, Some(0x81));
+    assert_eq!(sr.properties.reset_mask, Some(0x81));
+    let fields = sr.fields.as_ref().unwrap();
+    let txe = &fields[0];
+    assert_eq!(txe.access, Some(Access::ReadOnly));
+    assert_eq!(txe.enumerated_values.len(), 2);
+    assert_eq!(txe.enumerated_values[0].usage(), Usage::Read);
+    assert_eq!(txe.enumerated_values[0].values[1].name, "Empty");
+    assert_eq!(txe.enumerated_values[1].usage(), Usage::ReadWrite);
+    let err = &fields[1];
+    assert_eq!(
+        err.modified_write_values,
+        Some(ModifiedWriteValues::OneToClear)
+    );
+    assert_eq!(err.read_action, Some(ReadAction::Clear));
+    assert!(matches!(
+        err.write_constraint,
+        Some(WriteConstraint::Range(r)) if r.min == 0 && r.max == 7
+    ));
+
+    let alt = regs[1];
+    assert_eq!(alt.name, "SR_ALT");
+    assert_eq!(alt.address_offset, 0);
+    assert_eq!(alt.alternate_group.as_deref(), Some("DEBUG"));
+
+    match regs[2] {
+        Register::Array(info, dim) => {
+            assert_eq!(info.name, "BUF[%s]");
+            assert_eq!((dim.dim, dim.dim_increment), (4, 4));
+            assert_eq!(info.properties.reset_value, None);
+        }
+        _ => panic!("Expected an array"),
+    }
+    match &children[3] {
+        RegisterCluster::Cluster(c) => {
+            assert_eq!(c.name, "CH[%s]");
+            assert_eq!(c.address_offset, 0x20);
+        }
+        _ => panic!("Expected a cluster"),
+    }
+
+    assert_eq!(
+        import.diagnostics,
+        vec![
+            Diagnostic {
+                path: "UART0.BUF.DATA".to_string(),
+                message: "reset of type `SOFT` is not supported".to_string(),
+            },
+            Diagnostic {
+                path: "BANK".to_string(),
+                message: "`bank` is not supported".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn import_errors() {
+    assert!(matches!(import("<device/>"), Err(Error::NotComponent(_))));
+    assert_eq!(
+        import(&IPXACT.replace(
+            "<ipxact:size>16</ipxact:size>",
+            "<ipxact:size>0xW</ipxact:size>"
+        ))
+        .unwrap_err(),
+        Error::InvalidValue("UART0.CH.CFG".to_string(), "0xW".to_string())
+    );
+    // Values which don't fit in 32 bits are not truncated
+    assert_eq!(
+        import(&IPXACT.replace(
+            "<ipxact:addressOffset>'h10</ipxact:addressOffset>",
+            "<ipxact:addressOffset>'h1_0000_0010</ipxact:addressOffset>"
+        ))
+        .unwrap_err(),
+        Error::InvalidValue("UART0.BUF".to_string(), "'h1_0000_0010".to_string())
+    );
+}
+
+#[test]
+fn import_reset_above_bit_63() {
+    let xml = IPXACT
+        .replace(
+            "<ipxact:size>32</ipxact:size>\n          <ipxact:access>read-write</ipxact:access>",
+            "<ipxact:size>128</ipxact:size>\n          <ipxact:access>read-write</ipxact:access>",
+        )
+        .replace(
+            "<ipxact:bitOffset>7</ipxact:bitOffset>",
+            "<ipxact:bitOffset>71</ipxact:bitOffset>",
+        );
+    let import = import(&xml).unwrap();
+    assert_eq!(
+        import.diagnostics[0],
+        Diagnostic {
+            path: "UART0.SR.TXE".to_string(),
+            message: "reset value above bit 63 is not imported".to_string(),
+        }
+    );
+    let uart = &import.device.peripherals[0];
+    let sr = registers(uart.registers.as_ref().unwrap())[0];
+    assert_eq!(sr.fields.as_ref().unwrap()[0].bit_range.offset, 71);
+    // Only the reset of ERR is kept
+    assert_eq!(sr.properties.reset_value, Some(0x1));
+    assert_eq!(sr.properties.reset_mask, Some(0x1));
+}
+
+#[test]
+fn import_parameterized() {
+    let xml = IPXACT
+        .replace(
+            "<ipxact:addressOffset>'h10</ipxact:addressOffset>",
+            "<ipxact:addressOffset>BUF_BASE + 'h10</ipxact:addressOffset>",
+        )
+        .replace(
+            "<ipxact:range>'h10</ipxact:range>",
+            "<ipxact:range>CH_SIZE</ipxact:range>",
+        )
+        .replace(
+            "<ipxact:name>Any</ipxact:name>\n                <ipxact:value>0</ipxact:value>",
+            "<ipxact:name>Any</ipxact:name>\n                <ipxact:value>ANY</ipxact:value>",
+        );
+    let import = import(&xml).unwrap();
+    let diagnostics: Vec<_> = import.diagnostics.iter().map(|d| d.to_string()).collect();
+    assert_eq!(
+        diagnostics,
+        vec![
+            "UART0.SR.TXE.Any: parameterized value `ANY` is not supported",
+            "UART0.BUF: parameterized value `BUF_BASE + 'h10` is not supported",
+            "UART0.CH: parameterized value `CH_SIZE` is not supported",
+            "BANK: `bank` is not supported",
+        ]
+    );
+    // The elements with parameterized values are left out
+    let uart = &import.device.peripherals[0];
+    let regs = registers(uart.registers.as_ref().unwrap());
+    let names: Vec<_> = regs.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, ["SR", "SR_ALT"]);
+    assert_eq!(uart.registers.as_ref().unwrap().len(), 2);
+    let txe = &regs[0].fields.as_ref().unwrap()[0];
+    assert_eq!(txe.enumerated_values.len(), 1);
+}
+
+#[test]
+fn export_round_trip() {
+    let device = import(IPXACT).unwrap().device;
+    let export = export(&device).unwrap();
+    assert!(export.diagnostics.is_empty(), "{:?}", export.diagnostics);
+    assert!(export.xml.contains("<ipxact:alternateRegister>"));
+    let again = import(&export.xml).unwrap();
+    assert!(again.diagnostics.is_empty());
+    assert_eq!(again.device.peripherals, device.peripherals);
+}
+
+#[test]
+fn export_diagnostics() {
+    let svd = r#"
+<device>
+  <name>DEV</name>
+  <cpu>
+    <name>CM4</name>
+    <revision>r0p1</revision>
+    <endian>little</endian>
+    <mpuPresent>true</mpuPresent>
+    <fpuPresent>true</fpuPresent>
+    <nvicPrioBits>3</nvicPrioBits>
+    <vendorSystickConfig>false</vendorSystickConfig>
+  </cpu>
+  <size>32</size>
+  <resetValue>0</resetValue>
+  <peripherals>
+    <peripheral>
+      <name>TIMER</name>
+      <baseAddress>0x40010000</baseAddress>
+      <interrupt><name>TIMER</name><value>3</value></interrupt>
+      <registers>
+        <register>
+          <name>CNT</name>
+          <addressOffset>0x0</addressOffset>
+          <fields>
+            <field>
+              <name>VAL</name>
+              <bitRange>[7:0]</bitRange>
+              <enumeratedValues>
+                <enumeratedValue><name>Zero</name><value>0</value></enumeratedValue>
+                <enumeratedValue><name>Other</name><isDefault>true</isDefault></enumeratedValue>
+              </enumeratedValues>
+            </field>
+          </fields>
+        </register>
+        <register>
+          <name>LOAD</name>
+          <addressOffset>0x4</addressOffset>
+          <readAction>modifyExternal</readAction>
+        </register>
+        <cluster>
+          <name>CH0</name>
+          <description>Channel</description>
+          <addressOffset>0x10</addressOffset>
+          <register><name>CFG</name><addressOffset>0x0</addressOffset></register>
+        </cluster>
+        <cluster derivedFrom="CH0">
+          <name>CH1</name>
+          <addressOffset>0x20</addressOffset>
+        </cluster>
+        <cluster derivedFrom="CH9">
+          <name>CH2</name>
+          <addressOffset>0x30</addressOffset>
+        </cluster>
+      </registers>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+    let device = parser::parse(svd).unwrap();
+    let export = export(&device).unwrap();
+    let diagnostics: Vec<_> = export.diagnostics.iter().map(|d| d.to_string()).collect();
+    assert_eq!(
+        diagnostics,
+        vec![
+            "DEV: `cpu` is not exported",
+            "TIMER: interrupts are not exported",
+            "TIMER.CNT.VAL.Other: `isDefault` enumerated value is not exported",
+            "TIMER.LOAD.LOAD: `readAction` `modifyExternal` is not supported",
+            "TIMER.CH2: `derivedFrom` is not resolved",
+        ]
+    );
+    let import = import(&export.xml).unwrap();
+    let regs = registers(import.device.peripherals[0].registers.as_ref().unwrap());
+    // A register without fields gets a field covering it
+    let load = regs[1].fields.as_ref().unwrap();
+    assert_eq!(load[0].name, "LOAD");
+    assert_eq!(load[0].bit_range.width, 32);
+    assert_eq!(regs[1].properties.reset_value, Some(0));
+
+    // The derived cluster gets the registers of its source
+    let ch1 = match &import.device.peripherals[0].registers.as_ref().unwrap()[3] {
+        RegisterCluster::Cluster(c) => c,
+        _ => panic!("Expected a cluster"),
+    };
+    assert_eq!(ch1.name, "CH1");
+    assert_eq!(ch1.description.as_deref(), Some("Channel"));
+    assert_eq!(registers(&ch1.children)[0].name, "CFG");
+}
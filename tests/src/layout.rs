@@ -0,0 +1,166 @@
+use super::svd::{
+    layout::{Diagnostic, Error, MemberKind},
+    SvdError,
+};
+use svd_parser as parser;
+
+const SVD: &str = r#"
+<device>
+  <name>DEV</name>
+  <size>32</size>
+  <peripherals>
+    <peripheral>
+      <name>UART0</name>
+      <baseAddress>0x40000000</baseAddress>
+      <registers>
+        <register>
+          <name>CR</name>
+          <addressOffset>0x0</addressOffset>
+        </register>
+        <register>
+          <name>SR</name>
+          <addressOffset>0x4</addressOffset>
+          <size>8</size>
+        </register>
+        <register>
+          <name>DR</name>
+          <addressOffset>0x4</addressOffset>
+          <size>8</size>
+          <alternateRegister>SR</alternateRegister>
+        </register>
+        <register>
+          <dim>2</dim>
+          <dimIncrement>4</dimIncrement>
+          <name>BUF[%s]</name>
+          <addressOffset>0x10</addressOffset>
+        </register>
+        <register>
+          <dim>2</dim>
+          <dimIncrement>8</dimIncrement>
+          <name>DATA%s</name>
+          <addressOffset>0x20</addressOffset>
+        </register>
+        <cluster>
+          <dim>2</dim>
+          <dimIncrement>0x10</dimIncrement>
+          <name>CH[%s]</name>
+          <addressOffset>0x30</addressOffset>
+          <register>
+            <name>CFG</name>
+            <addressOffset>0x0</addressOffset>
+          </register>
+          <register>
+            <name>MIS</name>
+            <addressOffset>0x6</addressOffset>
+          </register>
+        </cluster>
+      </registers>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+fn describe(kind: &MemberKind) -> String {
+    match kind {
+        MemberKind::Register { name, count, .. } => format!("{}{:?}", name, count),
+        MemberKind::Cluster { name, count, .. } => format!("{}{:?}", name, count),
+        MemberKind::Union(members) => {
+            let names: Vec<_> = members.iter().map(|m| describe(&m.kind)).collect();
+            format!("union({})", names.join(","))
+        }
+        _ => "reserved".to_string(),
+    }
+}
+
+#[test]
+fn peripheral_layout() {
+    let device = parser::parse(SVD).unwrap();
+    let layout = device.peripherals[0]
+        .layout(&device.default_register_properties)
+        .unwrap();
+    let members: Vec<_> = layout
+        .members
+        .iter()
+        .map(|m| (m.offset, m.size, describe(&m.kind)))
+        .collect();
+    assert_eq!(
+        members,
+        vec![
+            (0x0, 4, "CRNone".to_string()),
+            (0x4, 1, "union(SRNone,DRNone)".to_string()),
+            (0x5, 11, "reserved".to_string()),
+            (0x10, 8, "BUFSome(2)".to_string()),
+            (0x18, 8, "reserved".to_string()),
+            (0x20, 4, "DATA0None".to_string()),
+            (0x24, 4, "reserved".to_string()),
+            (0x28, 4, "DATA1None".to_string()),
+            (0x2c, 4, "reserved".to_string()),
+            (0x30, 0x20, "CHSome(2)".to_string()),
+        ]
+    );
+    assert_eq!(layout.size, 0x50);
+    assert_eq!(layout.alignment, 4);
+
+    match &layout.members[9].kind {
+        MemberKind::Cluster { layout, .. } => {
+            assert_eq!(layout.size, 0x10);
+            assert_eq!(layout.members.last().unwrap().offset, 0xa);
+        }
+        _ => panic!("Expected a cluster"),
+    }
+    assert_eq!(
+        layout.diagnostics,
+        vec![Diagnostic::Misaligned {
+            path: "CH.MIS".to_string(),
+            offset: 6,
+            alignment: 4,
+        }]
+    );
+}
+
+#[test]
+fn layout_errors() {
+    let svd = SVD.replace(
+        "<name>MIS</name>\n            <addressOffset>0x6</addressOffset>",
+        "<name>MIS</name>\n            <addressOffset>0x2</addressOffset>",
+    );
+    let device = parser::parse(&svd).unwrap();
+    assert_eq!(
+        device.peripherals[0].layout(&device.default_register_properties),
+        Err(SvdError::Layout(Error::Overlap(
+            "CH.CFG".to_string(),
+            "CH.MIS".to_string()
+        )))
+    );
+
+    let svd = SVD.replace(
+        "<dimIncrement>0x10</dimIncrement>",
+        "<dimIncrement>0x8</dimIncrement>",
+    );
+    let device = parser::parse(&svd).unwrap();
+    assert_eq!(
+        device.peripherals[0].layout(&device.default_register_properties),
+        Err(SvdError::Layout(Error::IncrementTooSmall(
+            "CH".to_string(),
+            8,
+            10
+        )))
+    );
+}
+
+#[test]
+fn tail_padding() {
+    // CR and SR only, 5 bytes of registers in a struct aligned to 4 bytes
+    let svd = SVD.replace(
+        "<register>\n          <name>DR</name>",
+        "</registers></peripheral><peripheral><name>OTHER</name><baseAddress>0x50000000</baseAddress><registers><register>\n          <name>DR</name>",
+    );
+    let device = parser::parse(&svd).unwrap();
+    let layout = device.peripherals[0]
+        .layout(&device.default_register_properties)
+        .unwrap();
+    assert_eq!(layout.size, 8);
+    let last = layout.members.last().unwrap();
+    assert_eq!((last.offset, last.size), (5, 3));
+    assert_eq!(last.kind, MemberKind::Reserved);
+}
@@ -43,18 +43,40 @@ pub fn run_test<
 
 mod access;
 mod addressblock;
+mod addressindex;
 //mod bitrange;
+mod consts;
 mod cpu;
+mod csv;
+mod diff;
 mod dimelement;
+mod doc;
+mod edit;
 mod endian;
 mod enumeratedvalue;
 //mod enumeratedvalues;
 mod field;
 mod fieldinfo;
+mod format;
+mod header;
 mod interrupt;
+mod ipxact;
+mod layout;
+mod merge;
 mod modifiedwritevalues;
+mod patch;
+mod query;
+mod rdl;
 mod register;
 mod registerinfo;
+mod rmw;
+mod schema;
+mod sim;
 //mod registerproperties;
+mod split;
+mod update;
 mod usage;
+mod value;
+mod view;
+mod visit;
 mod writeconstraint;
@@ -0,0 +1,153 @@
+use crate::svd::{
+    merge::{AddressPolicy, Config, InterruptPolicy, PeripheralPolicy},
+    Access, SvdError,
+};
+use svd_parser as parser;
+
+const CORE: &str = r#"
+<device>
+  <name>CORE</name>
+  <size>32</size>
+  <access>read-write</access>
+  <peripherals>
+    <peripheral>
+      <name>NVIC</name>
+      <baseAddress>0xE000E100</baseAddress>
+      <addressBlock><offset>0</offset><size>0x400</size><usage>registers</usage></addressBlock>
+      <registers>
+        <register><name>ISER0</name><addressOffset>0x0</addressOffset></register>
+      </registers>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+const VENDOR: &str = r#"
+<device>
+  <name>VENDOR</name>
+  <size>32</size>
+  <access>read-only</access>
+  <peripherals>
+    <peripheral>
+      <name>UART0</name>
+      <baseAddress>0x40000000</baseAddress>
+      <addressBlock><offset>0</offset><size>0x100</size><usage>registers</usage></addressBlock>
+      <interrupt><name>UART0</name><value>1</value></interrupt>
+      <registers>
+        <register><name>DR</name><addressOffset>0x0</addressOffset></register>
+      </registers>
+    </peripheral>
+    <peripheral>
+      <name>NVIC</name>
+      <baseAddress>0xE000E100</baseAddress>
+      <addressBlock><offset>0</offset><size>0x400</size><usage>registers</usage></addressBlock>
+      <interrupt><name>NVIC_ERR</name><value>2</value></interrupt>
+      <registers>
+        <register><name>ISER0</name><addressOffset>0x0</addressOffset></register>
+      </registers>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+#[test]
+fn merge_core_and_vendor() {
+    let mut core = parser::parse(CORE).unwrap();
+    let vendor = parser::parse(VENDOR).unwrap();
+
+    let err = core
+        .clone()
+        .merge(vendor.clone(), &Config::default())
+        .unwrap_err();
+    assert!(matches!(err, SvdError::Merge(_)));
+
+    let config = Config::default().peripheral(PeripheralPolicy::KeepExisting);
+    core.merge(vendor, &config).unwrap();
+    let names: Vec<_> = core.peripherals.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["NVIC", "UART0"]);
+    // Interrupts of the dropped peripheral are merged into the kept one
+    assert_eq!(core.peripherals[0].interrupt[0].name, "NVIC_ERR");
+    // Device default access of the vendor is moved to its peripherals
+    assert_eq!(
+        core.peripherals[1].default_register_properties.access,
+        Some(Access::ReadOnly)
+    );
+    assert_eq!(core.peripherals[0].default_register_properties.access, None);
+}
+
+#[test]
+fn merge_conflicts() {
+    let base = parser::parse(VENDOR).unwrap();
+    let mut other = parser::parse(CORE).unwrap();
+    other.peripherals[0].name = "NVIC2".to_string();
+
+    // Same address blocks
+    assert!(base
+        .clone()
+        .merge(other.clone(), &Config::default())
+        .is_err());
+    let config = Config::default().address(AddressPolicy::Allow);
+    base.clone().merge(other.clone(), &config).unwrap();
+
+    // Same interrupt number with a different name
+    other.peripherals[0].base_address = 0x5000_0000;
+    other.peripherals[0].interrupt = base.peripherals[0].interrupt.clone();
+    other.peripherals[0].interrupt[0].name = "TIMER".to_string();
+    assert!(base
+        .clone()
+        .merge(other.clone(), &Config::default())
+        .is_err());
+
+    let mut merged = base.clone();
+    let config = Config::default().interrupt(InterruptPolicy::Replace);
+    merged.merge(other.clone(), &config).unwrap();
+    assert!(merged.peripherals[0].interrupt.is_empty());
+    assert_eq!(merged.peripherals[2].interrupt[0].name, "TIMER");
+
+    let mut merged = base;
+    let config = Config::default().interrupt(InterruptPolicy::KeepExisting);
+    merged.merge(other, &config).unwrap();
+    assert_eq!(merged.peripherals[0].interrupt[0].name, "UART0");
+    assert!(merged.peripherals[2].interrupt.is_empty());
+}
+
+#[test]
+fn merge_keeps_effective_properties() {
+    let mut core = parser::parse(CORE).unwrap();
+    let mut vendor = parser::parse(VENDOR).unwrap();
+    vendor.default_register_properties.access = None;
+    vendor.peripherals.retain(|p| p.name == "UART0");
+
+    core.merge(vendor, &Config::default()).unwrap();
+    // The access of the device isn't inherited by the peripherals of the vendor
+    assert_eq!(core.default_register_properties.access, None);
+    assert_eq!(
+        core.peripherals[0].default_register_properties.access,
+        Some(Access::ReadWrite)
+    );
+    assert_eq!(core.peripherals[1].default_register_properties.access, None);
+    // Properties which are the same stay on the device
+    assert_eq!(core.default_register_properties.size, Some(32));
+    assert_eq!(core.peripherals[1].default_register_properties.size, None);
+}
+
+#[test]
+fn merge_checks_merged_peripherals() {
+    let core = parser::parse(CORE).unwrap();
+    let mut vendor = parser::parse(VENDOR).unwrap();
+    // Both peripherals of the vendor overlap each other, but not the core
+    vendor.peripherals[1].name = "UART1".to_string();
+    vendor.peripherals[1].base_address = 0x4000_0080;
+    vendor.peripherals[1].interrupt.clear();
+
+    let err = core
+        .clone()
+        .merge(vendor.clone(), &Config::default())
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "`Merge error: Peripheral `UART1` at 0x40000080 overlaps with peripheral `UART0` at 0x40000000"
+    );
+    let config = Config::default().address(AddressPolicy::Allow);
+    core.clone().merge(vendor, &config).unwrap();
+}
@@ -0,0 +1,221 @@
+use crate::svd::{
+    patch::{Error, Operation},
+    Access, SvdError, ValidateLevel,
+};
+use svd_parser as parser;
+
+const SVD: &str = r#"
+<device>
+  <name>DEV</name>
+  <size>32</size>
+  <access>read-write</access>
+  <peripherals>
+    <peripheral>
+      <dim>2</dim>
+      <dimIncrement>0x400</dimIncrement>
+      <dimIndex>A,B</dimIndex>
+      <name>GPIO%s</name>
+      <baseAddress>0x48000000</baseAddress>
+      <registers>
+        <register><name>MODER</name><addressOffset>0x0</addressOffset></register>
+      </registers>
+    </peripheral>
+    <peripheral>
+      <name>TIM1</name>
+      <baseAddress>0x40000000</baseAddress>
+      <registers>
+        <register>
+          <name>CR1</name>
+          <addressOffset>0x0</addressOffset>
+          <fields>
+            <field><name>CEN</name><bitRange>[0:0]</bitRange></field>
+            <field><name>DIR</name><bitRange>[4:4]</bitRange></field>
+          </fields>
+        </register>
+        <register><name>ISR</name><addressOffset>0x10</addressOffset></register>
+        <register><name>ICR</name><addressOffset>0x14</addressOffset></register>
+      </registers>
+    </peripheral>
+    <peripheral>
+      <name>UART0</name>
+      <baseAddress>0x40001000</baseAddress>
+      <registers>
+        <register><name>DR</name><addressOffset>0x0</addressOffset></register>
+      </registers>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+const PATCH: &str = r#"
+[
+  { "modify": { "register": "GPIO*.MODER", "with": { "access": "read-only", "description": "Mode", "resetMask": "0xffffffffffffffff" } } },
+  { "rename": { "field": "TIM1.CR1.CEN", "to": "EN" } },
+  { "delete": { "register": "TIM1.{ISR,ICR}" } },
+  { "copy": { "peripheral": "UART0", "name": "UART1" } },
+  { "modify": { "peripheral": "UART1", "with": { "baseAddress": "0x40001400" } } },
+  { "copy": { "register": "UART0.DR", "to": "TIM1", "name": "DATA" } },
+  { "derive": { "peripheral": "UART1", "from": "UART0" } },
+  { "add": {
+      "parent": "TIM1.CR1.DIR",
+      "enumeratedValues": {
+        "usage": "read-write",
+        "values": [
+          { "name": "UP", "value": 0 },
+          { "name": "DOWN", "value": 1 }
+        ]
+      }
+  } }
+]
+"#;
+
+#[test]
+fn apply_patch() {
+    let mut device = parser::parse(SVD).unwrap();
+    let patch: Vec<Operation> = serde_json::from_str(PATCH).unwrap();
+    device.patch(&patch, ValidateLevel::Weak).unwrap();
+
+    let names: Vec<_> = device.peripherals.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["GPIO%s", "TIM1", "UART0", "UART1"]);
+
+    let moder = device.peripherals[0].reg_iter().next().unwrap();
+    assert_eq!(moder.properties.access, Some(Access::ReadOnly));
+    assert_eq!(moder.description.as_deref(), Some("Mode"));
+    assert_eq!(moder.properties.reset_mask, Some(u64::MAX));
+
+    let regs: Vec<_> = device.peripherals[1]
+        .reg_iter()
+        .map(|r| r.name.as_str())
+        .collect();
+    assert_eq!(regs, ["CR1", "DATA"]);
+    let cr1 = device.peripherals[1].reg_iter().next().unwrap();
+    let fields = cr1.fields.as_ref().unwrap();
+    assert_eq!(fields[0].name, "EN");
+    assert_eq!(fields[1].enumerated_values[0].values[1].name, "DOWN");
+
+    let uart1 = &device.peripherals[3];
+    assert_eq!(uart1.base_address, 0x4000_1400);
+    assert_eq!(uart1.derived_from.as_deref(), Some("UART0"));
+    assert!(uart1.registers.is_none());
+
+    // Round trip through the serialized form
+    let json = serde_json::to_string(&patch).unwrap();
+    let again: Vec<Operation> = serde_json::from_str(&json).unwrap();
+    assert_eq!(again, patch);
+}
+
+#[test]
+fn patch_errors() {
+    let device = parser::parse(SVD).unwrap();
+    let check = |patch: &str, expected: Error| {
+        let patch: Vec<Operation> = serde_json::from_str(patch).unwrap();
+        let mut patched = device.clone();
+        assert_eq!(
+            patched.patch(&patch, ValidateLevel::Weak),
+            Err(SvdError::Patch(expected))
+        );
+        // Device is unchanged on error
+        assert_eq!(patched, device);
+    };
+
+    check(
+        r#"[{ "delete": { "register": "TIM1.CR1" } }, { "delete": { "register": "TIM*.CR9" } }]"#,
+        Error::NoMatch("TIM*.CR9".to_string()),
+    );
+    check(
+        r#"[{ "rename": { "register": "TIM1.ISR", "to": "ICR" } }]"#,
+        Error::AlreadyExists("TIM1.ICR".to_string()),
+    );
+    check(
+        r#"[{ "modify": { "field": "TIM1.CR1.CEN", "with": { "baseAddress": 0 } } }]"#,
+        Error::NotApplicable("baseAddress".to_string(), "TIM1.CR1.CEN".to_string()),
+    );
+    check(
+        r#"[{ "copy": { "register": "TIM1.ISR" } }]"#,
+        Error::AlreadyExists("TIM1.ISR".to_string()),
+    );
+
+    // Unknown properties are rejected
+    assert!(serde_json::from_str::<Vec<Operation>>(
+        r#"[{ "modify": { "register": "TIM1.ISR", "with": { "acess": "read-only" } } }]"#
+    )
+    .is_err());
+}
+
+#[test]
+fn rename_updates_references() {
+    let mut device = parser::parse(SVD).unwrap();
+    let patch: Vec<Operation> = serde_json::from_str(
+        r#"[
+  { "derive": { "peripheral": "TIM1", "from": "UART0" } },
+  { "rename": { "peripheral": "UART0", "to": "USART0" } }
+]"#,
+    )
+    .unwrap();
+    device.patch(&patch, ValidateLevel::Weak).unwrap();
+    assert_eq!(
+        device.peripherals[1].derived_from.as_deref(),
+        Some("USART0")
+    );
+}
+
+#[test]
+fn patch_enumerated_values() {
+    let mut device = parser::parse(SVD).unwrap();
+    let patch: Vec<Operation> = serde_json::from_str(
+        r#"[
+  { "add": {
+      "parent": "TIM1.CR1.DIR",
+      "enumeratedValues": { "usage": "read", "values": [{ "name": "UP", "value": 0 }] }
+  } },
+  { "add": {
+      "parent": "TIM1.CR1.DIR",
+      "enumeratedValues": { "usage": "write", "values": [{ "name": "UP", "value": 0 }] }
+  } },
+  { "add": { "parent": "TIM1.CR1.DIR.read", "enumeratedValue": { "name": "DOWN", "value": 1 } } },
+  { "modify": { "enumeratedValue": "TIM1.CR1.DIR.write.UP", "with": { "description": "Count up" } } },
+  { "modify": { "enumeratedValue": "TIM1.CR1.DIR.*.UP", "with": { "value": 3 } } },
+  { "rename": { "enumeratedValue": "TIM1.CR1.DIR.read.DOWN", "to": "DOWNWARDS" } },
+  { "delete": { "enumeratedValue": "TIM1.CR1.DIR.write.UP" } }
+]"#,
+    )
+    .unwrap();
+    device.patch(&patch, ValidateLevel::Weak).unwrap();
+
+    let cr1 = device.peripherals[1].reg_iter().next().unwrap();
+    let dir = &cr1.fields.as_ref().unwrap()[1];
+    let read = &dir.enumerated_values[0];
+    let names: Vec<_> = read.values.iter().map(|v| v.name.as_str()).collect();
+    assert_eq!(names, ["UP", "DOWNWARDS"]);
+    assert_eq!(read.values[0].value, Some(3));
+    assert_eq!(read.values[0].description, None);
+    assert_eq!(read.values[1].value, Some(1));
+    assert!(dir.enumerated_values[1].values.is_empty());
+
+    let check = |patch: &str, expected: Error| {
+        let patch: Vec<Operation> = serde_json::from_str(patch).unwrap();
+        assert_eq!(
+            device.clone().patch(&patch, ValidateLevel::Weak),
+            Err(SvdError::Patch(expected))
+        );
+    };
+    check(
+        r#"[{ "add": { "parent": "TIM1.CR1.DIR.read", "enumeratedValue": { "name": "UP" } } }]"#,
+        Error::AlreadyExists("TIM1.CR1.DIR.read.UP".to_string()),
+    );
+    check(
+        r#"[{ "add": { "parent": "TIM1.CR1.DIR.read-write", "enumeratedValue": { "name": "X" } } }]"#,
+        Error::NoMatch("TIM1.CR1.DIR.read-write".to_string()),
+    );
+    check(
+        r#"[{ "modify": { "enumeratedValue": "TIM1.CR1.DIR.read.UP", "with": { "usage": "read" } } }]"#,
+        Error::NotApplicable("usage".to_string(), "TIM1.CR1.DIR.read.UP".to_string()),
+    );
+    check(
+        r#"[{ "derive": { "enumeratedValue": "TIM1.CR1.DIR.read.UP", "from": "X" } }]"#,
+        Error::NotApplicable(
+            "derivedFrom".to_string(),
+            "TIM1.CR1.DIR.read.UP".to_string(),
+        ),
+    );
+}
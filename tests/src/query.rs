@@ -0,0 +1,109 @@
+use crate::svd::{
+    query::{Element, Query},
+    SvdError,
+};
+use svd_parser as parser;
+
+const SVD: &str = r#"
+<device>
+  <name>DEV</name>
+  <size>32</size>
+  <access>read-write</access>
+  <peripherals>
+    <peripheral>
+      <dim>3</dim>
+      <dimIncrement>0x400</dimIncrement>
+      <dimIndex>A,B,C</dimIndex>
+      <name>GPIO%s</name>
+      <baseAddress>0x48000000</baseAddress>
+      <registers>
+        <register><name>MODER</name><addressOffset>0x0</addressOffset></register>
+        <register><name>IDR</name><addressOffset>0x10</addressOffset></register>
+      </registers>
+    </peripheral>
+    <peripheral>
+      <name>TIM1</name>
+      <baseAddress>0x40000000</baseAddress>
+      <registers>
+        <register>
+          <name>CR1</name>
+          <addressOffset>0x0</addressOffset>
+          <fields>
+            <field><name>CEN</name><bitRange>[0:0]</bitRange></field>
+            <field><name>UDIS</name><bitRange>[1:1]</bitRange></field>
+            <field><name>OPM</name><bitRange>[3:3]</bitRange></field>
+          </fields>
+        </register>
+        <register>
+          <name>CR2</name>
+          <addressOffset>0x4</addressOffset>
+          <fields>
+            <field><name>TI1S</name><bitRange>[7:7]</bitRange></field>
+          </fields>
+        </register>
+        <register><name>ISR</name><addressOffset>0x10</addressOffset></register>
+        <register><name>ICR</name><addressOffset>0x14</addressOffset></register>
+        <register><name>IER</name><addressOffset>0x18</addressOffset></register>
+      </registers>
+    </peripheral>
+    <peripheral>
+      <name>TIM9</name>
+      <baseAddress>0x40001000</baseAddress>
+      <registers>
+        <register>
+          <name>CR1</name>
+          <addressOffset>0x0</addressOffset>
+          <fields>
+            <field><name>CEN</name><bitRange>[0:0]</bitRange></field>
+          </fields>
+        </register>
+      </registers>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+fn paths(device: &crate::svd::Device, pattern: &str) -> Vec<String> {
+    device
+        .query(pattern)
+        .unwrap()
+        .into_iter()
+        .map(|m| m.path)
+        .collect()
+}
+
+#[test]
+fn query_globs() {
+    let device = parser::parse(SVD).unwrap();
+
+    // Template matches, instances are not listed separately
+    assert_eq!(paths(&device, "GPIO*.MODER"), ["GPIO%s.MODER"]);
+    // Instances match
+    assert_eq!(
+        paths(&device, "GPIO[AC].MODER"),
+        ["GPIOA.MODER", "GPIOC.MODER"]
+    );
+    let m = device.query("GPIOB").unwrap();
+    assert_eq!(m[0].index.as_deref(), Some("B"));
+    assert!(matches!(m[0].element, Element::Peripheral(_)));
+
+    assert_eq!(paths(&device, "TIM[1-8].CR?.*EN"), ["TIM1.CR1.CEN"]);
+    assert_eq!(paths(&device, "*.{ISR,ICR}"), ["TIM1.ISR", "TIM1.ICR"]);
+    assert_eq!(
+        paths(&device, "TIM?.CR1.{C,U}*"),
+        ["TIM1.CR1.CEN", "TIM1.CR1.UDIS", "TIM9.CR1.CEN"]
+    );
+    assert!(matches!(
+        device.query("TIM1.CR2.*").unwrap()[0].element,
+        Element::Field(f) if f.name == "TI1S"
+    ));
+
+    let q = Query::new("TIM*.I[!E]R").unwrap();
+    assert!(q.is_match("TIM1.ISR"));
+    assert!(!q.is_match("TIM1.IER"));
+    assert!(!q.is_match("TIM1"));
+
+    assert!(matches!(Query::new("TIM[1-8"), Err(SvdError::Query(_))));
+    assert!(matches!(Query::new("TIM.{A,B"), Err(SvdError::Query(_))));
+    assert!(matches!(Query::new("TIM..CR"), Err(SvdError::Query(_))));
+}
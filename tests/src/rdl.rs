@@ -0,0 +1,263 @@
+use svd_parser as parser;
+use svd_rdl::generate;
+
+const SVD: &str = r#"
+<device>
+  <name>DEV</name>
+  <description>Test "device"</description>
+  <size>32</size>
+  <resetValue>0</resetValue>
+  <resetMask>0xFFFFFFFF</resetMask>
+  <peripherals>
+    <peripheral>
+      <name>UART0</name>
+      <description>Universal
+        asynchronous receiver</description>
+      <baseAddress>0x40000000</baseAddress>
+      <interrupt><name>UART0</name><value>5</value></interrupt>
+      <registers>
+        <register>
+          <name>SR</name>
+          <displayName>Status</displayName>
+          <addressOffset>0x0</addressOffset>
+          <access>read-only</access>
+          <resetValue>0x80</resetValue>
+          <readAction>clear</readAction>
+          <fields>
+            <field>
+              <name>TXE</name>
+              <bitRange>[7:7]</bitRange>
+            </field>
+            <field>
+              <name>ERR</name>
+              <bitRange>[1:0]</bitRange>
+              <access>read-write</access>
+              <modifiedWriteValues>oneToClear</modifiedWriteValues>
+            </field>
+          </fields>
+        </register>
+        <register>
+          <name>ALT</name>
+          <addressOffset>0x0</addressOffset>
+          <alternateRegister>SR</alternateRegister>
+        </register>
+        <register>
+          <name>CR</name>
+          <addressOffset>0x4</addressOffset>
+          <size>16</size>
+          <resetMask>0xFF00</resetMask>
+          <fields>
+            <field>
+              <name>MODE</name>
+              <description>Mode</description>
+              <bitRange>[1:0]</bitRange>
+              <enumeratedValues>
+                <name>MODE</name>
+                <usage>read</usage>
+                <enumeratedValue><name>Idle</name><value>0</value></enumeratedValue>
+              </enumeratedValues>
+              <enumeratedValues>
+                <enumeratedValue><name>Off</name><description>Disabled</description><value>0</value></enumeratedValue>
+                <enumeratedValue><name>On</name><value>1</value></enumeratedValue>
+                <enumeratedValue><name>Other</name><isDefault>true</isDefault></enumeratedValue>
+              </enumeratedValues>
+            </field>
+            <field>
+              <name>type</name>
+              <bitRange>[9:8]</bitRange>
+              <access>writeOnce</access>
+            </field>
+          </fields>
+        </register>
+        <register>
+          <dim>4</dim>
+          <dimIncrement>4</dimIncrement>
+          <name>DATA[%s]</name>
+          <addressOffset>0x10</addressOffset>
+          <access>write-only</access>
+        </register>
+        <cluster>
+          <dim>2</dim>
+          <dimIncrement>0x10</dimIncrement>
+          <dimIndex>A,B</dimIndex>
+          <name>CH%s</name>
+          <addressOffset>0x20</addressOffset>
+          <register>
+            <name>CFG</name>
+            <addressOffset>0x0</addressOffset>
+            <fields>
+              <field>
+                <dim>2</dim>
+                <dimIncrement>4</dimIncrement>
+                <name>EN%s</name>
+                <bitRange>[0:0]</bitRange>
+              </field>
+            </fields>
+          </register>
+        </cluster>
+      </registers>
+    </peripheral>
+    <peripheral derivedFrom="UART0">
+      <name>UART1</name>
+      <baseAddress>0x40001000</baseAddress>
+    </peripheral>
+    <peripheral>
+      <dim>2</dim>
+      <dimIncrement>0x100</dimIncrement>
+      <name>TIMER[%s]</name>
+      <baseAddress>0x40010000</baseAddress>
+      <registers>
+        <register>
+          <name>CNT</name>
+          <addressOffset>0x0</addressOffset>
+        </register>
+      </registers>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+const RDL: &str = r#"// SystemRDL description of DEV, generated from the SVD description.
+
+addrmap UART0 {
+    desc = "Universal asynchronous receiver";
+    reg {
+        name = "Status";
+        field {
+            sw = r;
+            hw = rw;
+            onread = rclr;
+        } TXE[7:7] = 0x1;
+        field {
+            sw = rw;
+            hw = rw;
+            onwrite = woclr;
+            onread = rclr;
+        } ERR[1:0] = 0x0;
+    } SR @ 0x0;
+    // ALT is an alternate of SR and is not exported
+    reg {
+        regwidth = 16;
+        enum MODE_e {
+            Off = 2'h0 { desc = "Disabled"; };
+            On = 2'h1;
+        };
+        field {
+            desc = "Mode";
+            sw = rw;
+            hw = r;
+            encode = MODE_e;
+        } MODE[1:0];
+        field {
+            sw = w1;
+            hw = r;
+        } \type[9:8] = 0x0;
+    } CR @ 0x4;
+    reg {
+        field {
+            sw = w;
+            hw = r;
+        } DATA[31:0] = 0x0;
+    } DATA[4] @ 0x10 += 0x4;
+    regfile {
+        reg {
+            field {
+                sw = rw;
+                hw = r;
+            } EN0[0:0] = 0x0, EN1[4:4] = 0x0;
+        } CFG @ 0x0;
+    } CHA @ 0x20, CHB @ 0x30;
+};
+
+addrmap TIMER {
+    reg {
+        field {
+            sw = rw;
+            hw = r;
+        } CNT[31:0] = 0x0;
+    } CNT @ 0x0;
+};
+
+addrmap DEV {
+    desc = "Test \"device\"";
+    UART0 UART0 @ 0x40000000;
+    UART0 UART1 @ 0x40001000;
+    TIMER TIMER[2] @ 0x40010000 += 0x100;
+};
+"#;
+
+#[test]
+fn generate_rdl() {
+    let device = parser::parse(SVD).unwrap();
+    let rdl = generate(&device);
+    assert_eq!(rdl, RDL);
+}
+
+#[test]
+fn derived_registers_and_clusters() {
+    let svd = r#"
+<device>
+  <name>DEV</name>
+  <size>32</size>
+  <peripherals>
+    <peripheral>
+      <name>DMA</name>
+      <baseAddress>0x40000000</baseAddress>
+      <registers>
+        <cluster>
+          <name>CH0</name>
+          <description>Channel 0</description>
+          <addressOffset>0x0</addressOffset>
+          <register>
+            <name>CFG</name>
+            <addressOffset>0x0</addressOffset>
+            <size>16</size>
+            <fields>
+              <field><name>EN</name><bitRange>[0:0]</bitRange></field>
+            </fields>
+          </register>
+          <register derivedFrom="CFG">
+            <name>CFG2</name>
+            <addressOffset>0x4</addressOffset>
+          </register>
+        </cluster>
+        <cluster derivedFrom="CH0">
+          <name>CH1</name>
+          <addressOffset>0x10</addressOffset>
+        </cluster>
+        <register derivedFrom="DMA.CH0.CFG">
+          <name>CTRL</name>
+          <addressOffset>0x20</addressOffset>
+        </register>
+      </registers>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+    let device = parser::parse(svd).unwrap();
+    let rdl = generate(&device);
+    let channel = r#"regfile {
+        desc = "Channel 0";
+        reg {
+            regwidth = 16;
+            field {
+                sw = rw;
+                hw = r;
+            } EN[0:0];
+        } CFG @ 0x0;
+        reg {
+            regwidth = 16;
+            field {
+                sw = rw;
+                hw = r;
+            } EN[0:0];
+        } CFG2 @ 0x4;
+    }"#;
+    assert!(rdl.contains(&format!("{} CH0 @ 0x0;", channel)), "{}", rdl);
+    assert!(rdl.contains(&format!("{} CH1 @ 0x10;", channel)), "{}", rdl);
+    assert!(
+        rdl.contains("        } EN[0:0];\n    } CTRL @ 0x20;"),
+        "{}",
+        rdl
+    );
+}
@@ -0,0 +1,121 @@
+use crate::svd::Access;
+use svd_parser as parser;
+
+const SVD: &str = r#"
+<device>
+  <name>DEV</name>
+  <peripherals>
+    <peripheral>
+      <name>UART</name>
+      <baseAddress>0x40000000</baseAddress>
+      <registers>
+        <register>
+          <name>SR</name>
+          <addressOffset>0x0</addressOffset>
+          <size>16</size>
+          <modifiedWriteValues>oneToClear</modifiedWriteValues>
+          <fields>
+            <field><name>RXNE</name><bitRange>[0:0]</bitRange></field>
+            <field><name>TC</name><bitRange>[1:1]</bitRange><modifiedWriteValues>zeroToClear</modifiedWriteValues></field>
+            <field><name>EN</name><bitRange>[2:2]</bitRange><modifiedWriteValues>modify</modifiedWriteValues></field>
+            <field><name>TOG</name><bitRange>[3:3]</bitRange><modifiedWriteValues>oneToToggle</modifiedWriteValues></field>
+            <field><name>SET</name><bitRange>[4:4]</bitRange><modifiedWriteValues>zeroToSet</modifiedWriteValues></field>
+            <field>
+              <name>FLAGS</name>
+              <bitRange>[11:8]</bitRange>
+              <access>read-only</access>
+              <readAction>clear</readAction>
+            </field>
+          </fields>
+        </register>
+        <register>
+          <name>CFG</name>
+          <addressOffset>0x8</addressOffset>
+          <fields>
+            <field><name>LOCK</name><bitRange>[0:0]</bitRange><access>read-writeOnce</access></field>
+            <field><name>KEY</name><bitRange>[7:4]</bitRange><access>writeOnce</access></field>
+            <field><name>MODE</name><bitRange>[9:8]</bitRange></field>
+          </fields>
+        </register>
+        <register>
+          <name>DR</name>
+          <addressOffset>0x4</addressOffset>
+          <size>8</size>
+          <readAction>modify</readAction>
+        </register>
+      </registers>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+#[test]
+fn masks() {
+    let device = parser::parse(SVD).unwrap();
+    let regs: Vec<_> = device.peripherals[0].reg_iter().collect();
+    let props = device.default_register_properties;
+
+    let sr = regs[0].rmw_masks(props);
+    assert_eq!(sr.one_to_clear, 0b0001);
+    assert_eq!(sr.zero_to_clear, 0b0010);
+    assert_eq!(sr.zero_to_set, 0b1_0000);
+    assert_eq!(sr.toggle, 0b1000);
+    assert_eq!(sr.one_to_set, 0);
+    assert_eq!(sr.read_side_effects, 0xf00);
+    assert_eq!(sr.write_back, 0xf06);
+
+    // Field level masks don't inherit from the register
+    let rxne = &regs[0].fields.as_ref().unwrap()[0];
+    let masks = rxne.rmw_masks();
+    assert_eq!(masks.one_to_clear, 0);
+    assert_eq!(masks.write_back, 1);
+
+    // Fields which can be written once are not written back
+    let cfg = regs[1].rmw_masks(props);
+    assert_eq!(cfg.write_back, 0x300);
+    let fields = regs[1].fields.as_ref().unwrap();
+    assert_eq!(fields[0].rmw_masks().write_back, 0);
+    assert_eq!(fields[1].rmw_masks().write_back, 0);
+
+    let dr = regs[2].rmw_masks(props);
+    assert_eq!(dr.read_side_effects, 0xff);
+    assert_eq!(dr.write_back, 0);
+}
+
+#[test]
+fn inherited_properties() {
+    let svd = SVD.replace(
+        "<baseAddress>0x40000000</baseAddress>",
+        "<baseAddress>0x40000000</baseAddress>\n      <size>8</size>\n      <access>write-only</access>",
+    );
+    let device = parser::parse(&svd).unwrap();
+    let uart = &device.peripherals[0];
+    let props = uart
+        .default_register_properties
+        .inherit(&device.default_register_properties);
+    let regs: Vec<_> = uart.reg_iter().collect();
+
+    // CFG is write-only by inheritance, so nothing is written back
+    let cfg = regs[1].rmw_masks(props);
+    assert_eq!(cfg.write_back, 0);
+
+    // DR has its own size
+    let dr = regs[2].rmw_masks(props);
+    assert_eq!(dr.read_side_effects, 0xff);
+
+    // A register without fields takes the size of the peripheral
+    let mut data = regs[2].clone();
+    data.properties.size = None;
+    data.read_action = None;
+    let masks = data.rmw_masks(props);
+    assert_eq!(masks.write_back, 0);
+    assert_eq!(masks.one_to_clear, 0);
+    let read_write = props.access(Some(Access::ReadWrite));
+    assert_eq!(data.rmw_masks(read_write).write_back, 0xff);
+
+    // A read-only register by inheritance has no write side effects
+    let read_only = props.access(Some(Access::ReadOnly));
+    let sr = regs[0].rmw_masks(read_only);
+    assert_eq!(sr.one_to_clear, 0);
+    assert_eq!(sr.toggle, 0);
+}
@@ -0,0 +1,450 @@
+use crate::svd::{
+    schema::{self, FORMAT_VERSION, SCHEMA_ID},
+    Device,
+};
+use serde_json::{json, Value};
+use svd_parser as parser;
+
+const SVD: &str = r#"
+<device schemaVersion="1.3">
+  <name>DEV</name>
+  <version>1.0</version>
+  <cpu>
+    <name>CM33</name>
+    <revision>r0p1</revision>
+    <endian>little</endian>
+    <mpuPresent>true</mpuPresent>
+    <fpuPresent>true</fpuPresent>
+    <fpuDP>false</fpuDP>
+    <nvicPrioBits>3</nvicPrioBits>
+    <vendorSystickConfig>false</vendorSystickConfig>
+    <deviceNumInterrupts>32</deviceNumInterrupts>
+  </cpu>
+  <addressUnitBits>8</addressUnitBits>
+  <width>32</width>
+  <size>32</size>
+  <protection>s</protection>
+  <resetValue>0</resetValue>
+  <resetMask>0xFFFFFFFF</resetMask>
+  <peripherals>
+    <peripheral>
+      <name>UART0</name>
+      <groupName>UART</groupName>
+      <baseAddress>0x40000000</baseAddress>
+      <addressBlock>
+        <offset>0x0</offset>
+        <size>0x400</size>
+        <usage>registers</usage>
+        <protection>n</protection>
+      </addressBlock>
+      <interrupt><name>UART0</name><description>UART 0</description><value>5</value></interrupt>
+      <registers>
+        <register>
+          <name>CR</name>
+          <alternateGroup>ALT</alternateGroup>
+          <addressOffset>0x0</addressOffset>
+          <access>read-writeOnce</access>
+          <writeConstraint><writeAsRead>true</writeAsRead></writeConstraint>
+          <fields>
+            <field>
+              <name>MODE</name>
+              <bitRange>[1:0]</bitRange>
+              <writeConstraint><range><minimum>0</minimum><maximum>2</maximum></range></writeConstraint>
+              <enumeratedValues>
+                <name>MODE</name>
+                <usage>read-write</usage>
+                <enumeratedValue><name>Off</name><description>Off</description><value>0</value></enumeratedValue>
+                <enumeratedValue><name>Other</name><isDefault>true</isDefault></enumeratedValue>
+              </enumeratedValues>
+            </field>
+            <field>
+              <name>PRIO</name>
+              <bitOffset>4</bitOffset>
+              <bitWidth>4</bitWidth>
+              <modifiedWriteValues>oneToClear</modifiedWriteValues>
+              <readAction>modifyExternal</readAction>
+            </field>
+            <field>
+              <name>EN%s</name>
+              <dim>2</dim>
+              <dimIncrement>1</dimIncrement>
+              <lsb>8</lsb>
+              <msb>8</msb>
+              <access>read-only</access>
+            </field>
+          </fields>
+        </register>
+        <register>
+          <dim>2</dim>
+          <dimIncrement>4</dimIncrement>
+          <dimIndex>A,B</dimIndex>
+          <dimName>DATA</dimName>
+          <dimArrayIndex>
+            <headerEnumName>DATA_INDEX</headerEnumName>
+            <enumeratedValue><name>A</name><value>0</value></enumeratedValue>
+            <enumeratedValue><name>B</name><value>1</value></enumeratedValue>
+          </dimArrayIndex>
+          <name>DATA%s</name>
+          <addressOffset>0x4</addressOffset>
+          <size>16</size>
+        </register>
+        <cluster>
+          <dim>2</dim>
+          <dimIncrement>0x10</dimIncrement>
+          <name>CH[%s]</name>
+          <headerStructName>CHANNEL</headerStructName>
+          <addressOffset>0x10</addressOffset>
+          <register>
+            <name>CFG</name>
+            <addressOffset>0x0</addressOffset>
+            <readAction>clear</readAction>
+          </register>
+        </cluster>
+      </registers>
+    </peripheral>
+    <peripheral derivedFrom="UART0">
+      <name>UART1</name>
+      <baseAddress>0x40001000</baseAddress>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+/// Errors of `value` against `schema`, as `path: message`
+fn validate(schema: &Value, value: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    Validator { root: schema }.validate(schema, value, "", &mut errors);
+    errors
+}
+
+/// Validator of the draft-07 keywords used by the schema.
+/// Other keywords panic, so that none of them is silently ignored.
+struct Validator<'a> {
+    root: &'a Value,
+}
+
+impl<'a> Validator<'a> {
+    fn is_valid(&self, schema: &Value, value: &Value) -> bool {
+        let mut errors = Vec::new();
+        self.validate(schema, value, "", &mut errors);
+        errors.is_empty()
+    }
+
+    fn validate(&self, schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+        let schema = schema.as_object().expect("Schemas are objects");
+        let mut error = |message: String| errors.push(format!("{}: {}", path, message));
+        let mut nested = Vec::new();
+        for (keyword, s) in schema {
+            match keyword.as_str() {
+                "$schema" | "$id" | "title" | "version" | "definitions" => {}
+                "$ref" => {
+                    let r = s.as_str().unwrap();
+                    let name = r.strip_prefix("#/definitions/").expect("Local reference");
+                    let definition = &self.root["definitions"][name];
+                    assert!(!definition.is_null(), "Unknown reference {}", r);
+                    self.validate(definition, value, path, &mut nested);
+                }
+                "type" => {
+                    let types: Vec<&str> = match s {
+                        Value::Array(types) => types.iter().map(|t| t.as_str().unwrap()).collect(),
+                        ty => vec![ty.as_str().unwrap()],
+                    };
+                    let matches = |ty: &&str| match *ty {
+                        "object" => value.is_object(),
+                        "array" => value.is_array(),
+                        "string" => value.is_string(),
+                        "boolean" => value.is_boolean(),
+                        "integer" => value.is_u64() || value.is_i64(),
+                        "null" => value.is_null(),
+                        ty => panic!("Unknown type {}", ty),
+                    };
+                    if !types.iter().any(matches) {
+                        error(format!("{} is not of type {:?}", value, types.join(", ")));
+                    }
+                }
+                "enum" => {
+                    if !s.as_array().unwrap().contains(value) {
+                        error(format!("{} is not one of {}", value, s));
+                    }
+                }
+                "const" => {
+                    if s != value {
+                        error(format!("{} was expected", s));
+                    }
+                }
+                "minimum" => {
+                    if let (Some(min), Some(v)) = (s.as_i64(), value.as_i64()) {
+                        if v < min {
+                            error(format!("{} is less than the minimum of {}", v, min));
+                        }
+                    }
+                }
+                "maximum" => {
+                    if let (Some(max), Some(v)) = (s.as_u64(), value.as_u64()) {
+                        if v > max {
+                            error(format!("{} is greater than the maximum of {}", v, max));
+                        }
+                    }
+                }
+                "pattern" => {
+                    if let Some(text) = value.as_str() {
+                        let pattern = s.as_str().unwrap();
+                        if !regex::Regex::new(pattern).unwrap().is_match(text) {
+                            error(format!("{} does not match {:?}", value, pattern));
+                        }
+                    }
+                }
+                "items" => {
+                    for (i, v) in value.as_array().into_iter().flatten().enumerate() {
+                        self.validate(s, v, &format!("{}/{}", path, i), &mut nested);
+                    }
+                }
+                "properties" => {
+                    for (key, v) in value.as_object().into_iter().flatten() {
+                        if let Some(property) = s.get(key) {
+                            self.validate(property, v, &format!("{}/{}", path, key), &mut nested);
+                        }
+                    }
+                }
+                "additionalProperties" => {
+                    assert_eq!(s, &json!(false));
+                    let properties = &schema["properties"];
+                    for key in value.as_object().into_iter().flatten().map(|(k, _)| k) {
+                        if properties.get(key).is_none() {
+                            error(format!(
+                                "Additional properties are not allowed ('{}' was unexpected)",
+                                key
+                            ));
+                        }
+                    }
+                }
+                "required" => {
+                    if let Some(map) = value.as_object() {
+                        for key in s.as_array().unwrap() {
+                            if !map.contains_key(key.as_str().unwrap()) {
+                                error(format!("{} is a required property", key));
+                            }
+                        }
+                    }
+                }
+                "dependencies" => {
+                    if let Some(map) = value.as_object() {
+                        for (key, required) in s.as_object().unwrap() {
+                            for other in required.as_array().unwrap() {
+                                if map.contains_key(key)
+                                    && !map.contains_key(other.as_str().unwrap())
+                                {
+                                    error(format!("{} is a required property", other));
+                                }
+                            }
+                        }
+                    }
+                }
+                "oneOf" => {
+                    let schemas = s.as_array().unwrap();
+                    match schemas.iter().filter(|s| self.is_valid(s, value)).count() {
+                        1 => {}
+                        0 => error(format!("{} is not valid under any of the schemas", value)),
+                        _ => error(format!(
+                            "{} is valid under more than one of the schemas",
+                            value
+                        )),
+                    }
+                }
+                "anyOf" => {
+                    if !s
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .any(|s| self.is_valid(s, value))
+                    {
+                        error(format!("{} is not valid under any of the schemas", value));
+                    }
+                }
+                "not" => {
+                    if self.is_valid(s, value) {
+                        error(format!("{} is not allowed for {}", s, value));
+                    }
+                }
+                keyword => panic!("Unsupported keyword {}", keyword),
+            }
+        }
+        errors.extend(nested);
+    }
+}
+
+fn errors(value: &Value) -> Vec<String> {
+    validate(&schema::json_schema(), value)
+}
+
+/// Errors of a value of a definition, which are hidden by the alternatives of its parents
+fn definition_errors(name: &str, value: &Value) -> Vec<String> {
+    let schema = schema::json_schema();
+    let definition = json!({
+        "$schema": schema["$schema"],
+        "definitions": schema["definitions"],
+        "$ref": format!("#/definitions/{}", name),
+    });
+    validate(&definition, value)
+}
+
+fn collect_refs(value: &Value, refs: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::String(r)) = map.get("$ref") {
+                refs.push(r["#/definitions/".len()..].to_string());
+            }
+            map.values().for_each(|v| collect_refs(v, refs));
+        }
+        Value::Array(values) => values.iter().for_each(|v| collect_refs(v, refs)),
+        _ => {}
+    }
+}
+
+#[test]
+fn version() {
+    let schema = schema::json_schema();
+    assert_eq!(schema["$schema"], "http://json-schema.org/draft-07/schema#");
+    assert_eq!(schema["$id"], SCHEMA_ID);
+    assert_eq!(schema["version"], FORMAT_VERSION);
+    assert!(SCHEMA_ID.ends_with(FORMAT_VERSION));
+    assert_eq!(
+        schema["properties"]["formatVersion"]["const"],
+        FORMAT_VERSION
+    );
+
+    // Every reference is defined, and every definition is used
+    let mut refs = Vec::new();
+    collect_refs(&schema, &mut refs);
+    let definitions = schema["definitions"].as_object().unwrap();
+    for r in &refs {
+        assert!(definitions.contains_key(r), "Unknown reference {}", r);
+    }
+    for name in definitions.keys() {
+        assert!(refs.contains(name), "Unused definition {}", name);
+    }
+}
+
+#[test]
+fn serialized_devices_are_valid() {
+    let device = parser::parse(SVD).unwrap();
+    let value = serde_json::to_value(&device).unwrap();
+    assert_eq!(errors(&value), Vec::<String>::new());
+
+    let versioned = schema::versioned(&device);
+    assert_eq!(versioned["formatVersion"], FORMAT_VERSION);
+    assert_eq!(versioned["$schema"], SCHEMA_ID);
+    assert_eq!(errors(&versioned), Vec::<String>::new());
+    // The version marks are ignored when loading
+    assert_eq!(serde_json::from_value::<Device>(versioned).unwrap(), device);
+}
+
+#[test]
+fn toml_documents_are_valid() {
+    // TOML writes the values above `i64::MAX` as strings
+    let svd = SVD.replace(
+        "<resetMask>0xFFFFFFFF</resetMask>",
+        "<resetMask>0xFFFFFFFFFFFFFFFF</resetMask>",
+    );
+    let device = parser::parse(&svd).unwrap();
+    let value: Value = toml::from_str(&device.to_toml().unwrap()).unwrap();
+    assert_eq!(value["resetMask"], "0xffffffffffffffff");
+    assert_eq!(errors(&value), Vec::<String>::new());
+
+    let mut value = value;
+    value["resetMask"] = json!("0xFFFF_FFFF");
+    check(
+        errors(&value),
+        "/resetMask: \"0xFFFF_FFFF\" is not valid under any of the schemas",
+    );
+}
+
+fn check(errors: Vec<String>, expected: &str) {
+    assert!(
+        errors.iter().any(|e| e.contains(expected)),
+        "{:?} doesn't contain {}",
+        errors,
+        expected
+    );
+}
+
+#[test]
+fn invalid_documents() {
+    let device = parser::parse(SVD).unwrap();
+    let valid = schema::versioned(&device);
+    let edit = |edit: fn(&mut Value)| {
+        let mut value = valid.clone();
+        edit(&mut value);
+        errors(&value)
+    };
+    check(
+        edit(|v| v["formatVersion"] = json!("2.0")),
+        &format!("/formatVersion: \"{}\" was expected", FORMAT_VERSION),
+    );
+    check(
+        edit(|v| {
+            v.as_object_mut().unwrap().remove("name");
+        }),
+        ": \"name\" is a required property",
+    );
+    check(
+        edit(|v| v["access"] = json!("read")),
+        "/access: \"read\" is not one of",
+    );
+    check(
+        edit(|v| v["resetMask"] = json!(-1)),
+        "/resetMask: -1 is not valid under any of the schemas",
+    );
+    check(
+        edit(|v| v["peripherals"][0]["bas"] = json!(0)),
+        "/peripherals/0: Additional properties are not allowed ('bas' was unexpected)",
+    );
+    check(
+        edit(|v| v["peripherals"][0]["registers"][2] = json!({ "cluster": {}, "register": {} })),
+        "/peripherals/0/registers/2: {\"cluster\":{},\"register\":{}} is not valid under any of the schemas",
+    );
+
+    let registers = &valid["peripherals"][0]["registers"];
+    let register = |index: usize, edit: fn(&mut Value)| {
+        let mut value = registers[index]["register"].clone();
+        edit(&mut value);
+        definition_errors("Register", &value)
+    };
+    check(
+        register(1, |r| {
+            r.as_object_mut().unwrap().remove("dimIncrement");
+        }),
+        ": \"dimIncrement\" is a required property",
+    );
+    check(
+        register(0, |r| r["writeConstraint"] = json!({ "writeAsRead": true })),
+        "/writeConstraint: {\"writeAsRead\":true} is not valid under any of the schemas",
+    );
+
+    let field = |edit: fn(&mut Value)| {
+        let mut value = registers[0]["register"]["fields"][0].clone();
+        edit(&mut value);
+        definition_errors("Field", &value)
+    };
+    assert_eq!(field(|_| {}), Vec::<String>::new());
+    check(
+        field(|f| f["lsb"] = json!(0)),
+        "is not valid under any of the schemas",
+    );
+    check(
+        field(|f| {
+            let f = f.as_object_mut().unwrap();
+            f.remove("bitRange");
+            f.insert("bitOffset".into(), json!(0));
+        }),
+        "is not valid under any of the schemas",
+    );
+    check(
+        field(|f| f["bitRange"] = json!("1:0")),
+        "/bitRange: \"1:0\" does not match",
+    );
+    check(
+        field(|f| f["enumeratedValues"][0]["values"][1]["isDefault"] = json!(true)),
+        "/enumeratedValues/0/values/1: Additional properties are not allowed ('isDefault' was unexpected)",
+    );
+}
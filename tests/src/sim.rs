@@ -0,0 +1,115 @@
+use crate::svd::addressindex::RegisterInstance;
+use svd_parser as parser;
+use svd_sim::{Error, Hook, Registers, Simulator};
+
+const SVD: &str = r#"
+<device>
+  <name>DEV</name>
+  <size>32</size>
+  <resetValue>0</resetValue>
+  <resetMask>0xffffffff</resetMask>
+  <peripherals>
+    <peripheral>
+      <name>UART0</name>
+      <baseAddress>0x40000000</baseAddress>
+      <registers>
+        <register>
+          <name>SR</name>
+          <addressOffset>0x0</addressOffset>
+          <resetValue>0xc0</resetValue>
+          <fields>
+            <field><name>TXE</name><bitRange>[7:7]</bitRange><access>read-only</access></field>
+            <field><name>TC</name><bitRange>[6:6]</bitRange><modifiedWriteValues>oneToClear</modifiedWriteValues></field>
+            <field><name>ERR</name><bitRange>[3:0]</bitRange><readAction>clear</readAction></field>
+          </fields>
+        </register>
+        <register>
+          <name>DR</name>
+          <addressOffset>0x4</addressOffset>
+          <access>write-only</access>
+        </register>
+        <register>
+          <name>CR</name>
+          <addressOffset>0x8</addressOffset>
+          <fields>
+            <field><name>EN</name><bitRange>[0:0]</bitRange></field>
+            <field><name>TOG</name><bitRange>[1:1]</bitRange><modifiedWriteValues>oneToToggle</modifiedWriteValues></field>
+            <field><name>LOCK</name><bitRange>[2:2]</bitRange><access>read-writeOnce</access></field>
+          </fields>
+        </register>
+      </registers>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+#[test]
+fn access_semantics() {
+    let device = parser::parse(SVD).unwrap();
+    let mut sim = Simulator::new(&device);
+    let sr = sim.registers().address_of("UART0.SR").unwrap();
+    let dr = sim.registers().address_of("UART0.DR").unwrap();
+    let cr = sim.registers().address_of("UART0.CR").unwrap();
+
+    assert_eq!(sim.read(sr), Ok(0xc0));
+    // Read-only TXE is kept, TC is cleared by writing one
+    sim.write(sr, 0xff).unwrap();
+    assert_eq!(sim.read(sr), Ok(0x8f));
+    // ERR is cleared by the previous read
+    assert_eq!(sim.read(sr), Ok(0x80));
+
+    // Write-only register reads as zero
+    sim.write(dr, 0x55).unwrap();
+    assert_eq!(sim.read(dr), Ok(0));
+    assert_eq!(sim.registers().get(dr), Some(0x55));
+
+    sim.write(cr, 0b111).unwrap();
+    assert_eq!(sim.read(cr), Ok(0b111));
+    sim.write(cr, 0b010).unwrap();
+    // TOG toggled back, LOCK kept its first written value
+    assert_eq!(sim.read(cr), Ok(0b100));
+
+    sim.reset();
+    assert_eq!(sim.read(cr), Ok(0));
+    sim.write(cr, 0).unwrap();
+    sim.write(cr, 0b100).unwrap();
+    assert_eq!(sim.read(cr), Ok(0));
+
+    assert_eq!(sim.read(sr + 2), Err(Error::Misaligned(sr + 2, sr)));
+    assert_eq!(sim.read(0x5000_0000), Err(Error::NoRegister(0x5000_0000)));
+}
+
+/// Transmits immediately: writing DR sets TC, the transmission complete flag
+struct Uart {
+    sent: Vec<u64>,
+}
+
+impl Hook for Uart {
+    fn write(&mut self, registers: &mut Registers, register: &RegisterInstance, written: u64) {
+        if register.register.name == "DR" {
+            self.sent.push(written);
+            let sr = registers.address_of("UART0.SR").unwrap();
+            let value = registers.get(sr).unwrap();
+            registers.set(sr, value | 0x40);
+        }
+    }
+}
+
+#[test]
+fn hooks() {
+    let device = parser::parse(SVD).unwrap();
+    let mut sim = Simulator::new(&device);
+    let sr = sim.registers().address_of("UART0.SR").unwrap();
+    let dr = sim.registers().address_of("UART0.DR").unwrap();
+
+    sim.write(sr, 0x40).unwrap();
+    assert_eq!(sim.read(sr), Ok(0x80));
+    sim.hook("UART0", Uart { sent: Vec::new() }).unwrap();
+    sim.write(dr, 0x41).unwrap();
+    assert_eq!(sim.read(sr), Ok(0xc0));
+
+    assert_eq!(
+        sim.hook("UART1", Uart { sent: Vec::new() }),
+        Err(Error::NoPeripheral("UART1".to_string()))
+    );
+}
@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+use svd_encoder as encoder;
+use svd_parser as parser;
+
+const DEVICE: &str = r#"
+<device>
+  <name>DEV</name>
+  <size>32</size>
+  <access>read-write</access>
+  <peripherals>
+    <peripheral>
+      <name>UART0</name>
+      <baseAddress>0x40000000</baseAddress>
+      <registers>
+        <register><name>DR</name><addressOffset>0x0</addressOffset></register>
+      </registers>
+    </peripheral>
+    <peripheral derivedFrom="UART0">
+      <name>UART1</name>
+      <baseAddress>0x40001000</baseAddress>
+    </peripheral>
+    <peripheral>
+      <dim>2</dim>
+      <dimIncrement>0x100</dimIncrement>
+      <name>TIM[%s]</name>
+      <baseAddress>0x40002000</baseAddress>
+      <registers>
+        <register><name>CNT</name><addressOffset>0x0</addressOffset></register>
+      </registers>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+#[test]
+fn split_round_trip() {
+    let device = parser::parse(DEVICE).unwrap();
+    let split = encoder::encode_split(&device).unwrap();
+
+    let files: Vec<_> = split.peripherals.iter().map(|(f, _)| f.as_str()).collect();
+    assert_eq!(files, ["UART0.svd", "UART1.svd", "TIM.svd"]);
+    assert!(split.manifest.contains(r#"<include file="UART1.svd"/>"#));
+
+    let files: HashMap<_, _> = split.peripherals.into_iter().collect();
+    let parsed = parser::parse_split(&split.manifest, |file| {
+        files
+            .get(file)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("no such file"))
+    })
+    .unwrap();
+    assert_eq!(parsed, device);
+
+    let err = parser::parse_split(&split.manifest, |_| Ok("<device/>".to_string())).unwrap_err();
+    assert!(format!("{:?}", err).contains("UART0.svd"));
+}
@@ -0,0 +1,64 @@
+use crate::svd::{
+    Access, BitRange, CpuBuilder, FieldInfo, FieldInfoBuilder, PeripheralInfoBuilder, RegisterInfo,
+    RegisterInfoBuilder, ValidateLevel,
+};
+
+#[test]
+fn deserialize_builders() {
+    let lvl = ValidateLevel::Weak;
+    let mut reg = RegisterInfo::builder()
+        .name("CR".to_string())
+        .description(Some("Control".to_string()))
+        .address_offset(4)
+        .size(Some(32))
+        .build(lvl)
+        .unwrap();
+    let update: RegisterInfoBuilder =
+        serde_json::from_str(r#"{ "access": "read-only", "resetValue": 1, "resetMask": "0xff" }"#)
+            .unwrap();
+    reg.modify_from(update, lvl).unwrap();
+    assert_eq!(reg.name, "CR");
+    assert_eq!(reg.description.as_deref(), Some("Control"));
+    assert_eq!(reg.address_offset, 4);
+    assert_eq!(reg.properties.size, Some(32));
+    assert_eq!(reg.properties.access, Some(Access::ReadOnly));
+    assert_eq!(reg.properties.reset_value, Some(1));
+    assert_eq!(reg.properties.reset_mask, Some(0xff));
+
+    let mut field = FieldInfo::builder()
+        .name("MODE".to_string())
+        .bit_range(BitRange::from_offset_width(0, 2))
+        .build(lvl)
+        .unwrap();
+    let update: FieldInfoBuilder = serde_json::from_str(r#"{ "bitWidth": 3 }"#).unwrap();
+    field.modify_from(update, lvl).unwrap();
+    assert_eq!(field.bit_range.offset, 0);
+    assert_eq!(field.bit_range.width, 3);
+    let update: FieldInfoBuilder =
+        serde_json::from_str(r#"{ "description": "Mode", "bitRange": "[7:4]" }"#).unwrap();
+    field.modify_from(update, lvl).unwrap();
+    assert_eq!(field.bit_range, BitRange::from_bit_range("[7:4]").unwrap());
+    assert_eq!(field.description.as_deref(), Some("Mode"));
+    let update: FieldInfoBuilder = serde_json::from_str(r#"{ "lsb": 1, "msb": 2 }"#).unwrap();
+    field.modify_from(update, lvl).unwrap();
+    assert_eq!(field.bit_range, BitRange::from_msb_lsb(2, 1));
+    assert!(serde_json::from_str::<FieldInfoBuilder>(r#"{ "msb": 2 }"#).is_err());
+
+    let update: PeripheralInfoBuilder =
+        serde_json::from_str(r#"{ "baseAddress": "0xffffffff00000400", "size": 16 }"#).unwrap();
+    assert_eq!(
+        update,
+        PeripheralInfoBuilder::default()
+            .base_address(0xffff_ffff_0000_0400)
+            .default_register_properties(crate::svd::RegisterProperties::new().size(Some(16)))
+    );
+
+    let update: CpuBuilder =
+        serde_json::from_str(r#"{ "fpuDP": true, "nvicPrioBits": 4 }"#).unwrap();
+    assert_eq!(
+        update,
+        CpuBuilder::default()
+            .fpu_double_precision(Some(true))
+            .nvic_priority_bits(4)
+    );
+}
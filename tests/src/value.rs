@@ -0,0 +1,184 @@
+use crate::svd::{registerinfo, value::Error, writeconstraint, Access, SvdError};
+use svd_parser as parser;
+
+const SVD: &str = r#"
+<device>
+  <name>DEV</name>
+  <peripherals>
+    <peripheral>
+      <name>TIM1</name>
+      <baseAddress>0x40000000</baseAddress>
+      <registers>
+        <register>
+          <name>CR</name>
+          <addressOffset>0x0</addressOffset>
+          <size>16</size>
+          <resetValue>0x1234</resetValue>
+          <resetMask>0xff0f</resetMask>
+          <fields>
+            <field>
+              <name>MODE</name>
+              <bitRange>[1:0]</bitRange>
+              <enumeratedValues>
+                <usage>read</usage>
+                <enumeratedValue><name>IDLE</name><value>0</value></enumeratedValue>
+                <enumeratedValue><name>BUSY</name><description>Busy</description><isDefault>true</isDefault></enumeratedValue>
+                <enumeratedValue><name>DONE</name><value>3</value></enumeratedValue>
+              </enumeratedValues>
+              <enumeratedValues>
+                <usage>write</usage>
+                <enumeratedValue><name>START</name><value>1</value></enumeratedValue>
+              </enumeratedValues>
+            </field>
+            <field>
+              <dim>2</dim>
+              <dimIncrement>4</dimIncrement>
+              <name>CH%s</name>
+              <bitRange>[7:4]</bitRange>
+              <writeConstraint><range><minimum>1</minimum><maximum>9</maximum></range></writeConstraint>
+            </field>
+            <field>
+              <name>STATUS</name>
+              <bitRange>[15:12]</bitRange>
+              <access>read-only</access>
+            </field>
+          </fields>
+        </register>
+      </registers>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+#[test]
+fn decode() {
+    let device = parser::parse(SVD).unwrap();
+    let cr = device.peripherals[0].reg_iter().next().unwrap();
+    let props = device.default_register_properties;
+
+    let value = cr.decode(0x1_a50d, props);
+    let fields: Vec<_> = value
+        .fields
+        .iter()
+        .map(|f| (f.field.name.as_str(), f.value))
+        .collect();
+    assert_eq!(
+        fields,
+        [("MODE", 1), ("CH0", 0), ("CH1", 5), ("STATUS", 0xa)]
+    );
+    let mode = value.field("MODE").unwrap();
+    let ev = mode.enumerated_value.unwrap();
+    assert_eq!(ev.name, "BUSY");
+    assert_eq!(ev.description.as_deref(), Some("Busy"));
+    assert_eq!(value.uncovered_mask, 0x000c);
+    assert_eq!(value.uncovered(), 0x000c);
+
+    let value = cr.decode(3, props);
+    let mode = value.field("MODE").unwrap();
+    assert_eq!(mode.enumerated_value.unwrap().name, "DONE");
+    assert!(value.field("CH0").unwrap().enumerated_value.is_none());
+}
+
+#[test]
+fn compose() {
+    let device = parser::parse(SVD).unwrap();
+    let cr = device.peripherals[0].reg_iter().next().unwrap();
+    let props = device.default_register_properties;
+
+    assert_eq!(cr.value_builder(props).build(), Ok(0x1204));
+    let value = cr
+        .value_builder(props)
+        .enumerated("MODE", "START")
+        .field("CH1", 9)
+        .build();
+    assert_eq!(value, Ok(0x1905));
+
+    let check = |builder: crate::svd::value::ValueBuilder, expected: Error| {
+        assert_eq!(builder.build(), Err(SvdError::Value(expected)));
+    };
+    check(
+        cr.value_builder(props).field("CH2", 1),
+        Error::UnknownField("CH2".to_string()),
+    );
+    check(
+        cr.value_builder(props).enumerated("MODE", "DONE"),
+        Error::UnknownEnumeratedValue("MODE".to_string(), "DONE".to_string()),
+    );
+    check(
+        cr.value_builder(props).field("MODE", 4),
+        Error::WriteNotAllowed("MODE".to_string(), writeconstraint::Error::TooWide(4, 2)),
+    );
+    check(
+        cr.value_builder(props).field("STATUS", 0),
+        Error::WriteNotAllowed("STATUS".to_string(), writeconstraint::Error::ReadOnly),
+    );
+    check(
+        cr.value_builder(props).field("CH0", 10),
+        Error::WriteNotAllowed(
+            "CH0".to_string(),
+            writeconstraint::Error::OutOfRange(10, 1, 9),
+        ),
+    );
+}
+
+#[test]
+fn inherited_properties() {
+    let svd = SVD.replace(
+        "<name>DEV</name>",
+        "<name>DEV</name>\n  <size>8</size>\n  <resetValue>0x08</resetValue>",
+    );
+    let svd = svd
+        .replace("<size>16</size>", "")
+        .replace("<resetValue>0x1234</resetValue>", "")
+        .replace("<resetMask>0xff0f</resetMask>", "");
+    let device = parser::parse(&svd).unwrap();
+    let cr = device.peripherals[0].reg_iter().next().unwrap();
+    let props = device.default_register_properties;
+
+    // Only the bits of the inherited size are uncovered
+    assert_eq!(cr.decode(0, props).uncovered_mask, 0x0c);
+    assert_eq!(cr.value_builder(props).field("CH0", 1).build(), Ok(0x18));
+}
+
+#[test]
+fn register_write_allowed() {
+    let device = parser::parse(SVD).unwrap();
+    let cr = device.peripherals[0].reg_iter().next().unwrap();
+    let props = device.default_register_properties;
+
+    // Read-only STATUS is skipped
+    assert_eq!(cr.is_write_allowed(0xf191, props), Ok(()));
+    assert_eq!(
+        cr.is_write_allowed(0x091, props),
+        Err(registerinfo::Error::WriteNotAllowed(
+            "CH1".to_string(),
+            writeconstraint::Error::OutOfRange(0, 1, 9)
+        ))
+    );
+    // Bits outside of the register
+    assert_eq!(
+        cr.is_write_allowed(0x1_0191, props),
+        Err(registerinfo::Error::TooWide(0x1_0191, 16))
+    );
+
+    // All fields are skipped in a read-only register
+    let props = props.access(Some(Access::ReadOnly));
+    assert_eq!(cr.is_write_allowed(0x0, props), Ok(()));
+}
+
+#[test]
+fn field_beyond_64_bits() {
+    let svd = SVD.replace(
+        "<bitRange>[15:12]</bitRange>",
+        "<bitRange>[71:68]</bitRange>",
+    );
+    let device = parser::parse(&svd).unwrap();
+    let cr = device.peripherals[0].reg_iter().next().unwrap();
+    let props = device.default_register_properties;
+
+    // The bits of STATUS are outside of any value
+    let value = cr.decode(0xffff, props);
+    assert_eq!(value.field("STATUS").unwrap().value, 0);
+    assert_eq!(value.uncovered_mask, 0xf00c);
+    assert_eq!(cr.rmw_masks(props).write_back, 0x0ff3);
+}
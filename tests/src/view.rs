@@ -0,0 +1,151 @@
+use crate::svd::{query::Element, Access};
+use svd_parser as parser;
+
+const SVD: &str = r#"
+<device>
+  <name>DEV</name>
+  <size>32</size>
+  <access>read-write</access>
+  <peripherals>
+    <peripheral>
+      <name>TIM1</name>
+      <baseAddress>0x40000000</baseAddress>
+      <access>read-only</access>
+      <registers>
+        <cluster>
+          <name>CH</name>
+          <addressOffset>0x10</addressOffset>
+          <size>16</size>
+          <register>
+            <name>CCR</name>
+            <addressOffset>0x4</addressOffset>
+            <fields>
+              <field><name>VAL</name><bitRange>[15:0]</bitRange></field>
+            </fields>
+          </register>
+        </cluster>
+        <register>
+          <name>CR</name>
+          <addressOffset>0x0</addressOffset>
+          <access>write-only</access>
+          <fields>
+            <field><name>EN</name><bitRange>[0:0]</bitRange></field>
+          </fields>
+        </register>
+      </registers>
+    </peripheral>
+    <peripheral>
+      <name>TIM2</name>
+      <baseAddress>0x40001000</baseAddress>
+      <registers>
+        <register><name>CR</name><addressOffset>0x8</addressOffset></register>
+      </registers>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+#[test]
+fn navigate() {
+    let device = parser::parse(SVD).unwrap();
+    let view = device.view();
+    assert_eq!(view.len(), 8);
+
+    let paths: Vec<_> = view.iter().map(|(_, n)| n.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        [
+            "TIM1",
+            "TIM1.CH",
+            "TIM1.CH.CCR",
+            "TIM1.CH.CCR.VAL",
+            "TIM1.CR",
+            "TIM1.CR.EN",
+            "TIM2",
+            "TIM2.CR"
+        ]
+    );
+
+    let val = view.find("TIM1.CH.CCR.VAL").unwrap();
+    let node = &view[val];
+    assert_eq!(node.name(), "VAL");
+    assert_eq!(node.address, 0x4000_0014);
+    assert_eq!(node.properties.size, Some(16));
+    assert_eq!(node.properties.access, Some(Access::ReadOnly));
+
+    let ancestors: Vec<_> = view.ancestors(val).map(|id| view.path(id)).collect();
+    assert_eq!(ancestors, ["TIM1.CH.CCR", "TIM1.CH", "TIM1"]);
+    assert_eq!(view.peripheral_of(val), view.peripherals()[0]);
+
+    let tim1 = view.peripherals()[0];
+    let cr = view.child(tim1, "CR").unwrap();
+    assert_eq!(view.parent(cr), Some(tim1));
+    assert_eq!(view[cr].properties.access, Some(Access::WriteOnly));
+    assert_eq!(view[cr].properties.size, Some(32));
+
+    let crs: Vec<_> = view
+        .by_name("CR")
+        .iter()
+        .map(|&id| view[id].address)
+        .collect();
+    assert_eq!(crs, [0x4000_0000, 0x4000_1008]);
+    assert!(view.by_name("NONE").is_empty());
+
+    // Look up the element borrowed from the device
+    let reg = device.peripherals[1].reg_iter().next().unwrap();
+    let id = view.id_of(Element::Register(reg)).unwrap();
+    assert_eq!(view.path(id), "TIM2.CR");
+    assert_eq!(view.peripheral_of(id), view.peripherals()[1]);
+}
+
+#[test]
+fn derived_and_field_properties() {
+    let svd = SVD
+        .replace(
+            "<field><name>EN</name><bitRange>[0:0]</bitRange></field>",
+            "<field><name>EN</name><bitRange>[0:0]</bitRange></field>
+            <field><name>ST</name><bitRange>[1:1]</bitRange><access>read-only</access></field>",
+        )
+        .replace(
+            "<peripheral>\n      <name>TIM2</name>",
+            "<peripheral derivedFrom=\"TIM1\">\n      <name>TIM2</name>",
+        )
+        .replace(
+            "<register><name>CR</name><addressOffset>0x8</addressOffset></register>",
+            "<register><name>CR</name><addressOffset>0x8</addressOffset></register>
+        <register derivedFrom=\"CR\"><name>CR2</name><addressOffset>0xC</addressOffset></register>",
+        );
+    let device = parser::parse(&svd).unwrap();
+    let view = device.view();
+
+    // Fields have their own access
+    let en = view.find("TIM1.CR.EN").unwrap();
+    assert_eq!(view[en].properties.access, Some(Access::WriteOnly));
+    let st = view.find("TIM1.CR.ST").unwrap();
+    assert_eq!(view[st].properties.access, Some(Access::ReadOnly));
+
+    // Properties come from the peripheral it is derived from
+    let tim2 = view.find("TIM2").unwrap();
+    assert_eq!(view[tim2].properties.access, Some(Access::ReadOnly));
+    let cr = view.find("TIM2.CR").unwrap();
+    assert_eq!(view[cr].properties.access, Some(Access::ReadOnly));
+
+    // and from the sibling register
+    let mut device = device;
+    device.peripherals[1]
+        .reg_iter_mut()
+        .next()
+        .unwrap()
+        .properties
+        .access = Some(Access::WriteOnce);
+    let view = device.view();
+    let cr2 = view.find("TIM2.CR2").unwrap();
+    assert_eq!(view[cr2].properties.access, Some(Access::WriteOnce));
+
+    // Only the elements of the device are found, not equal copies of them
+    let reg = device.peripherals[0].reg_iter().nth(1).unwrap();
+    let id = view.id_of(Element::Register(reg)).unwrap();
+    assert_eq!(view.path(id), "TIM1.CR");
+    let copy = reg.clone();
+    assert_eq!(view.id_of(Element::Register(&copy)), None);
+}
@@ -0,0 +1,121 @@
+use crate::svd::{
+    visit::{self, Context, Fold, Visit, VisitMut},
+    Access, Device, EnumeratedValue, Field, Register,
+};
+use svd_parser as parser;
+
+const SVD: &str = r#"
+<device>
+  <name>DEV</name>
+  <size>32</size>
+  <access>read-write</access>
+  <peripherals>
+    <peripheral>
+      <name>TIM1</name>
+      <baseAddress>0x40000000</baseAddress>
+      <access>read-only</access>
+      <registers>
+        <cluster>
+          <name>CH</name>
+          <addressOffset>0x10</addressOffset>
+          <size>16</size>
+          <register>
+            <name>CCR</name>
+            <addressOffset>0x0</addressOffset>
+            <fields>
+              <field>
+                <name>VAL</name>
+                <bitRange>[15:0]</bitRange>
+              </field>
+            </fields>
+          </register>
+        </cluster>
+        <register>
+          <name>CR</name>
+          <addressOffset>0x0</addressOffset>
+          <access>write-only</access>
+          <fields>
+            <field>
+              <name>MODE</name>
+              <bitRange>[1:0]</bitRange>
+              <enumeratedValues>
+                <enumeratedValue><name>A</name><value>0</value></enumeratedValue>
+                <enumeratedValue><name>B</name><value>1</value></enumeratedValue>
+              </enumeratedValues>
+            </field>
+          </fields>
+        </register>
+      </registers>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+#[derive(Default)]
+struct Collect(Vec<(String, Option<u32>, Option<Access>)>);
+
+impl Visit for Collect {
+    fn visit_register(&mut self, r: &Register, ctx: &Context) {
+        let props = r.properties.inherit(&ctx.properties);
+        self.0.push((ctx.path.clone(), props.size, props.access));
+        visit::walk_register(self, r, ctx);
+    }
+    fn visit_field(&mut self, f: &Field, ctx: &Context) {
+        self.0
+            .push((ctx.path.clone(), ctx.properties.size, ctx.properties.access));
+        visit::walk_field(self, f, ctx);
+    }
+    fn visit_enumerated_value(&mut self, _e: &EnumeratedValue, ctx: &Context) {
+        self.0.push((ctx.path.clone(), None, None));
+    }
+}
+
+#[test]
+fn visit_paths_and_properties() {
+    let device = parser::parse(SVD).unwrap();
+    let mut v = Collect::default();
+    v.visit_device(&device, &Context::new());
+    use Access::*;
+    assert_eq!(
+        v.0,
+        vec![
+            ("TIM1.CH.CCR".to_string(), Some(16), Some(ReadOnly)),
+            ("TIM1.CH.CCR.VAL".to_string(), Some(16), Some(ReadOnly)),
+            ("TIM1.CR".to_string(), Some(32), Some(WriteOnly)),
+            ("TIM1.CR.MODE".to_string(), Some(32), Some(WriteOnly)),
+            ("TIM1.CR.MODE.read-write.A".to_string(), None, None),
+            ("TIM1.CR.MODE.read-write.B".to_string(), None, None),
+        ]
+    );
+}
+
+struct Prefix;
+
+impl VisitMut for Prefix {
+    fn visit_field_mut(&mut self, f: &mut Field, ctx: &Context) {
+        f.description = Some(ctx.path.clone());
+    }
+}
+
+impl Fold for Prefix {
+    fn fold_register(&mut self, mut r: Register, ctx: &Context) -> Register {
+        r.name = format!("{}_{}", ctx.path.replace('.', "_"), r.name);
+        visit::fold_register(self, r, ctx)
+    }
+}
+
+#[test]
+fn visit_mut_and_fold() {
+    let mut device: Device = parser::parse(SVD).unwrap();
+    Prefix.visit_device_mut(&mut device, &Context::new());
+    let cr = device.peripherals[0].reg_iter().last().unwrap();
+    let mode = &cr.fields.as_ref().unwrap()[0];
+    assert_eq!(mode.description.as_deref(), Some("TIM1.CR.MODE"));
+
+    let device = Prefix.fold_device(device, &Context::new());
+    let names: Vec<_> = device.peripherals[0]
+        .reg_iter()
+        .map(|r| r.name.as_str())
+        .collect();
+    assert_eq!(names, ["TIM1_CH_CCR_CCR", "TIM1_CR_CR"]);
+}